@@ -4,7 +4,11 @@ use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, QueryResponse, Reply, Respon
 use cw2::set_contract_version;
 use mitosis_interface::denom_manager::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
-use crate::{error::ContractError, state::rbac::OWNER, CONTRACT_NAME, CONTRACT_VERSION};
+use crate::{
+    error::ContractError,
+    state::{rbac::OWNER, PAUSED},
+    CONTRACT_NAME, CONTRACT_VERSION,
+};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -16,6 +20,7 @@ pub fn instantiate(
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     OWNER.save(deps.storage, &info.sender)?;
+    PAUSED.save(deps.storage, &Default::default())?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -38,7 +43,12 @@ pub fn execute(
 
     match msg {
         ExecuteMsg::AddAlias { token, denom } => denoms::add_alias(deps, env, info, token, denom),
-        ExecuteMsg::ChangeOwner { new_owner } => rbac::change_owner(deps, env, info, new_owner),
+        ExecuteMsg::ChangeOwner {
+            new_owner,
+            expires_at,
+        } => rbac::change_owner(deps, env, info, new_owner, expires_at),
+        ExecuteMsg::AcceptOwner {} => rbac::accept_owner(deps, env, info),
+        ExecuteMsg::CancelOwner {} => rbac::cancel_owner(deps, env, info),
         ExecuteMsg::GrantRole { role, addr } => rbac::grant_role(deps, env, info, role, addr),
         ExecuteMsg::RevokeRole { role, addr } => rbac::revoke_role(deps, env, info, role, addr),
         ExecuteMsg::Pause { expires_at } => gov::pause(deps, env, info, expires_at),
@@ -58,6 +68,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<QueryResponse, Cont
 
     match msg {
         QueryMsg::GetConfig {} => query::get_config(deps, _env),
+        QueryMsg::GetPendingOwner {} => query::get_pending_owner(deps),
         QueryMsg::Convert { token } => query::get_convert(deps, _env, token),
     }
 }
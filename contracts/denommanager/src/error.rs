@@ -24,6 +24,12 @@ pub enum ContractError {
     #[error("Role Error: Addr {addr:?} has not role {role:?}")]
     RoleNotExist { addr: Addr, role: String },
 
+    #[error("No pending owner")]
+    NoPendingOwner {},
+
+    #[error("Pending owner expired")]
+    PendingOwnerExpired {},
+
     #[error("{msg:?}")]
     InvalidArgument { msg: String },
 }
@@ -2,37 +2,48 @@ use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response};
 
 use crate::{
     error::ContractError,
-    state::{rbac::assert_owned, PAUSED},
+    state::{
+        rbac::{assert_owned, assert_pauser},
+        PAUSED,
+    },
 };
 
+// pause is open to guardians as well as the owner. A pause without expires_at
+// lasts until release.
 pub fn pause(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    expires_at: u64,
+    expires_at: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut pause_info = PAUSED
         .load(deps.storage)?
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    assert_owned(deps.storage, info.sender.clone())?;
+    let role = assert_pauser(deps.storage, info.sender.clone())?;
 
-    if env.block.time.seconds() >= expires_at {
-        return Err(ContractError::InvalidArgument {
-            msg: "expires_at must be in the future".to_string(),
-        });
+    if let Some(expires_at) = expires_at {
+        if env.block.time.seconds() >= expires_at {
+            return Err(ContractError::InvalidArgument {
+                msg: "expires_at must be in the future".to_string(),
+            });
+        }
     }
 
     pause_info.paused = true;
-    pause_info.expires_at = Some(expires_at);
+    pause_info.expires_at = expires_at;
 
     PAUSED.save(deps.storage, &pause_info)?;
 
     let response = Response::new().add_attributes(vec![
         attr("action", "pause"),
         attr("executor", info.sender),
-        attr("expires_at", pause_info.expires_at.unwrap().to_string()),
+        attr("role", role),
+        attr(
+            "expires_at",
+            expires_at.map_or("never".to_string(), |e| e.to_string()),
+        ),
     ]);
 
     Ok(response)
@@ -65,7 +76,10 @@ mod test {
 
     use crate::{
         error::ContractError,
-        state::{rbac::OWNER, PauseInfo, PAUSED},
+        state::{
+            rbac::{ADDR_ROLE, GUARDIAN_ROLE, OWNER},
+            PauseInfo, PAUSED,
+        },
     };
 
     use super::*;
@@ -91,7 +105,7 @@ mod test {
             .unwrap();
         let info = mock_info(abuser.as_str(), &[]);
 
-        let unauth_pause = pause(deps.as_mut(), env.clone(), info.clone(), 0).unwrap_err();
+        let unauth_pause = pause(deps.as_mut(), env.clone(), info.clone(), Some(0)).unwrap_err();
         assert!(matches!(unauth_pause, ContractError::Unauthorized {}));
 
         PAUSED
@@ -130,7 +144,7 @@ mod test {
             deps.as_mut(),
             env.clone(),
             info.clone(),
-            env.block.time.seconds() + 1,
+            Some(env.block.time.seconds() + 1),
         )
         .unwrap_err();
 
@@ -160,7 +174,7 @@ mod test {
             deps.as_mut(),
             env.clone(),
             info,
-            env.block.time.seconds() - 1,
+            Some(env.block.time.seconds() - 1),
         )
         .unwrap_err();
 
@@ -182,13 +196,14 @@ mod test {
 
         let expires_at: u64 = env.block.time.seconds() + 1;
 
-        let response = pause(deps.as_mut(), env, info, expires_at).unwrap();
+        let response = pause(deps.as_mut(), env, info, Some(expires_at)).unwrap();
 
         assert_eq!(
             response.attributes,
             vec![
                 attr("action", "pause"),
                 attr("executor", owner.to_string()),
+                attr("role", "owner"),
                 attr("expires_at", expires_at.to_string())
             ]
         )
@@ -225,4 +240,50 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn test_guardian_pause() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let guardian = Addr::unchecked(ADDR2);
+        let info = mock_info(guardian.as_str(), &[]);
+
+        mock_owner(deps.as_mut().storage, owner);
+        ADDR_ROLE
+            .save(
+                deps.as_mut().storage,
+                (GUARDIAN_ROLE.to_string(), guardian.clone()),
+                &true,
+            )
+            .unwrap();
+        PAUSED
+            .save(deps.as_mut().storage, &Default::default())
+            .unwrap();
+
+        // paused until the owner releases it
+        let response = pause(deps.as_mut(), env.clone(), info.clone(), None).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "pause"),
+                attr("executor", guardian.to_string()),
+                attr("role", "guardian"),
+                attr("expires_at", "never"),
+            ]
+        );
+
+        let mut later = env;
+        later.block.time = later.block.time.plus_seconds(365 * 24 * 3600);
+        let paused = PAUSED
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .refresh(deps.as_mut().storage, &later)
+            .unwrap();
+        assert!(paused.paused);
+
+        let unauth_release = release(deps.as_mut(), later, info).unwrap_err();
+        assert!(matches!(unauth_release, ContractError::Unauthorized {}));
+    }
 }
@@ -5,11 +5,13 @@ use crate::{
     state::{rbac, PAUSED},
 };
 
+// change_owner only proposes new_owner, the ownership moves once it accepts.
 pub fn change_owner(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     new_owner: Addr,
+    expires_at: Option<u64>,
 ) -> Result<Response, ContractError> {
     PAUSED
         .load(deps.storage)?
@@ -18,12 +20,59 @@ pub fn change_owner(
 
     rbac::assert_owned(deps.storage, info.sender.clone())?;
 
-    rbac::change_owner(deps.storage, new_owner.clone())?;
+    if matches!(expires_at, Some(expires_at) if expires_at <= env.block.time.seconds()) {
+        return Err(ContractError::InvalidArgument {
+            msg: "expires_at must be in the future".to_string(),
+        });
+    }
+
+    rbac::propose_owner(deps.storage, new_owner.clone(), expires_at)?;
 
     let response = Response::new().add_attributes(vec![
         attr("action", "change_owner"),
         attr("executor", info.sender),
         attr("new_owner", new_owner),
+        attr(
+            "expires_at",
+            expires_at.map_or("never".to_string(), |e| e.to_string()),
+        ),
+    ]);
+
+    Ok(response)
+}
+
+pub fn accept_owner(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    let previous_owner = rbac::OWNER.load(deps.storage)?;
+    rbac::accept_owner(deps.storage, info.sender.clone(), env.block.time.seconds())?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "accept_owner"),
+        attr("executor", info.sender),
+        attr("previous_owner", previous_owner),
+    ]);
+
+    Ok(response)
+}
+
+pub fn cancel_owner(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    rbac::assert_owned(deps.storage, info.sender.clone())?;
+
+    let pending_owner = rbac::cancel_owner(deps.storage)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "cancel_owner"),
+        attr("executor", info.sender),
+        attr("pending_owner", pending_owner),
     ]);
 
     Ok(response)
@@ -90,7 +139,7 @@ mod test {
     };
 
     use crate::state::{
-        rbac::{ADDR_ROLE, GATEWAY_ROLE, OWNER},
+        rbac::{ADDR_ROLE, GATEWAY_ROLE, OWNER, PENDING_OWNER},
         PauseInfo,
     };
 
@@ -138,7 +187,7 @@ mod test {
         stop(deps.as_mut().storage, env.block.time.seconds());
 
         let change_owner_err =
-            change_owner(deps.as_mut(), env.clone(), info.clone(), addr.clone()).unwrap_err();
+            change_owner(deps.as_mut(), env.clone(), info.clone(), addr.clone(), None).unwrap_err();
         assert!(matches!(change_owner_err, ContractError::PausedError {}));
 
         let grant_role_err = grant_role(
@@ -167,7 +216,7 @@ mod test {
         resume(deps.as_mut().storage, env.block.time.seconds());
         mock_owner(deps.as_mut().storage, owner);
 
-        let unauthorized_err = change_owner(deps.as_mut(), env, info, abuser).unwrap_err();
+        let unauthorized_err = change_owner(deps.as_mut(), env, info, abuser, None).unwrap_err();
         assert!(matches!(unauthorized_err, ContractError::Unauthorized {}))
     }
 
@@ -175,27 +224,145 @@ mod test {
     fn test_successful_change_owner() {
         let mut deps = mock_dependencies();
         let env = mock_env();
+        let now = env.block.time.seconds();
 
         let owner = Addr::unchecked(ADDR1);
         let new_owner = Addr::unchecked(ADDR2);
         let info = mock_info(owner.as_str(), &[]);
 
-        resume(deps.as_mut().storage, env.block.time.seconds());
+        resume(deps.as_mut().storage, now);
         mock_owner(deps.as_mut().storage, owner.clone());
 
-        let changed_owner = change_owner(deps.as_mut(), env, info, new_owner.clone()).unwrap();
+        let past_err = change_owner(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            new_owner.clone(),
+            Some(now),
+        )
+        .unwrap_err();
+        assert!(matches!(past_err, ContractError::InvalidArgument { .. }));
+
+        let changed_owner = change_owner(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            new_owner.clone(),
+            Some(now + 100),
+        )
+        .unwrap();
         assert_eq!(
             changed_owner.attributes,
             vec![
                 attr("action", "change_owner"),
-                attr("executor", owner),
-                attr("new_owner", new_owner.as_str())
+                attr("executor", owner.clone()),
+                attr("new_owner", new_owner.as_str()),
+                attr("expires_at", (now + 100).to_string())
+            ]
+        );
+
+        // the owner stays until the proposal is accepted
+        assert_eq!(OWNER.load(&deps.storage).unwrap(), owner);
+
+        let accepted =
+            accept_owner(deps.as_mut(), env, mock_info(new_owner.as_str(), &[])).unwrap();
+        assert_eq!(
+            accepted.attributes,
+            vec![
+                attr("action", "accept_owner"),
+                attr("executor", new_owner.as_str()),
+                attr("previous_owner", owner.as_str())
             ]
         );
 
         // is it actually changed?
         let current_owner = OWNER.load(&deps.storage).unwrap();
         assert_eq!(new_owner, current_owner);
+        assert!(PENDING_OWNER.may_load(&deps.storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_accept_owner_errors() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let now = env.block.time.seconds();
+
+        let owner = Addr::unchecked(ADDR1);
+        let new_owner = Addr::unchecked(ADDR2);
+
+        resume(deps.as_mut().storage, now);
+        mock_owner(deps.as_mut().storage, owner.clone());
+
+        let no_pending_err = accept_owner(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(new_owner.as_str(), &[]),
+        )
+        .unwrap_err();
+        assert!(matches!(no_pending_err, ContractError::NoPendingOwner {}));
+
+        change_owner(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            new_owner.clone(),
+            Some(now + 100),
+        )
+        .unwrap();
+
+        let abuser_err =
+            accept_owner(deps.as_mut(), env.clone(), mock_info(owner.as_str(), &[])).unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let expired_err =
+            accept_owner(deps.as_mut(), env, mock_info(new_owner.as_str(), &[])).unwrap_err();
+        assert!(matches!(expired_err, ContractError::PendingOwnerExpired {}));
+        assert_eq!(OWNER.load(&deps.storage).unwrap(), owner);
+    }
+
+    #[test]
+    fn test_cancel_owner() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let new_owner = Addr::unchecked(ADDR2);
+
+        resume(deps.as_mut().storage, env.block.time.seconds());
+        mock_owner(deps.as_mut().storage, owner.clone());
+
+        change_owner(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            new_owner.clone(),
+            None,
+        )
+        .unwrap();
+
+        let abuser_err = cancel_owner(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(new_owner.as_str(), &[]),
+        )
+        .unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+
+        let cancelled =
+            cancel_owner(deps.as_mut(), env.clone(), mock_info(owner.as_str(), &[])).unwrap();
+        assert_eq!(
+            cancelled.attributes,
+            vec![
+                attr("action", "cancel_owner"),
+                attr("executor", owner.as_str()),
+                attr("pending_owner", new_owner.as_str())
+            ]
+        );
+
+        let accept_err =
+            accept_owner(deps.as_mut(), env, mock_info(new_owner.as_str(), &[])).unwrap_err();
+        assert!(matches!(accept_err, ContractError::NoPendingOwner {}));
     }
 
     #[test]
@@ -1,9 +1,12 @@
 use cosmwasm_std::{to_binary, Deps, Env, QueryResponse};
-use mitosis_interface::denom_manager::{ConfigResponse, ConvertResponse};
+use mitosis_interface::denom_manager::{ConfigResponse, ConvertResponse, PendingOwnerResponse};
 
 use crate::{
     error::ContractError,
-    state::{denoms::convert_denoms, rbac::OWNER},
+    state::{
+        denoms::convert_denoms,
+        rbac::{OWNER, PENDING_OWNER},
+    },
 };
 
 pub fn get_config(deps: Deps, _env: Env) -> Result<QueryResponse, ContractError> {
@@ -12,6 +15,15 @@ pub fn get_config(deps: Deps, _env: Env) -> Result<QueryResponse, ContractError>
     Ok(to_binary(&ConfigResponse { owner })?)
 }
 
+pub fn get_pending_owner(deps: Deps) -> Result<QueryResponse, ContractError> {
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+
+    Ok(to_binary(&PendingOwnerResponse {
+        pending_owner: pending.as_ref().map(|p| p.owner.clone()),
+        expires_at: pending.and_then(|p| p.expires_at),
+    })?)
+}
+
 pub fn get_convert(deps: Deps, _env: Env, token: String) -> Result<QueryResponse, ContractError> {
     let alias = convert_denoms(deps.storage, token.clone())?;
 
@@ -1,37 +1,63 @@
-use cosmwasm_std::{
-    attr, entry_point, BankMsg, Deps, DepsMut, Env, MessageInfo, QueryResponse, Reply, Response,
-};
-use cw2::set_contract_version;
-use mitosis_interface::gateway::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use cosmwasm_std::{entry_point, Deps, DepsMut, Env, MessageInfo, QueryResponse, Reply, Response};
+use cw2::{get_contract_version, set_contract_version};
+use mitosis_interface::gateway::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
 
 use crate::{
     errors::ContractError,
-    execute::consts::REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
-    state::{context::WITHDRAW, DENOM_MANAGER, LIQUIDITY_MANAGER, OWNER, PUBLIC_KEY},
-    verify::pub_to_addr,
+    execute::{
+        batch,
+        consts::{
+            REPLY_BATCH_MESSAGE, REPLY_HOOK_REFUND_TRANSFER, REPLY_IBC_RECEIVE_FAILURE,
+            REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+        },
+        hooks,
+        managers::validate_operation_timeout,
+        withdraw,
+    },
+    ibc,
+    migrations::migrate_legacy,
+    state::{
+        policy::{validate_policy, EXECUTE_POLICY},
+        signers::{SignerSet, SIGNER_SET},
+        ADDRESS_PREFIX, DENOM_MANAGER, LIQUIDITY_MANAGER, OPERATION_TIMEOUT, OWNER, PAUSED,
+    },
+    verify::{address_prefix, validate_prefix},
     CONTRACT_NAME, CONTRACT_VERSION,
 };
 
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     OWNER.save(deps.storage, &info.sender)?;
+    PAUSED.save(deps.storage, &Default::default())?;
     LIQUIDITY_MANAGER.save(deps.storage, &msg.liquidity_manager)?;
     DENOM_MANAGER.save(deps.storage, &msg.denom_manager)?;
 
-    let public_key_addr = pub_to_addr(msg.public_key.clone().into(), "osmo")?;
+    validate_operation_timeout(msg.operation_timeout)?;
+    OPERATION_TIMEOUT.save(deps.storage, &msg.operation_timeout)?;
 
-    if public_key_addr != info.sender {
-        return Err(ContractError::InvalidPubKey {});
-    }
+    let prefix = match msg.address_prefix {
+        Some(prefix) => prefix,
+        None => address_prefix(env.contract.address.as_str())?,
+    };
+    validate_prefix(&prefix)?;
+    ADDRESS_PREFIX.save(deps.storage, &prefix)?;
+
+    SIGNER_SET.save(
+        deps.storage,
+        &SignerSet::new(msg.signers, msg.threshold, 0)?,
+    )?;
+
+    let execute_policy = msg.execute_policy.unwrap_or_default();
+    validate_policy(deps.api, &execute_policy)?;
+    EXECUTE_POLICY.save(deps.storage, &execute_policy)?;
 
-    PUBLIC_KEY.save(deps.storage, &msg.public_key)?;
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", info.sender))
@@ -44,53 +70,168 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    use crate::execute::{gov, managers, operation, rbac};
+    use crate::execute::{
+        gov, ibc, limits, managers, operation, ops, policy, rbac, roots, signers,
+    };
 
     match msg {
         ExecuteMsg::ChangeOwner {
             new_owner,
-            new_public_key,
-        } => rbac::change_owner(deps, env, info, new_owner, new_public_key),
+            expires_at,
+        } => rbac::change_owner(deps, env, info, new_owner, expires_at),
+        ExecuteMsg::AcceptOwner {} => rbac::accept_owner(deps, env, info),
+        ExecuteMsg::CancelOwner {} => rbac::cancel_owner(deps, env, info),
+        ExecuteMsg::GrantRole { role, addr } => rbac::grant_role(deps, env, info, role, addr),
+        ExecuteMsg::RevokeRole { role, addr } => rbac::revoke_role(deps, env, info, role, addr),
         ExecuteMsg::ChangeLiquidityManager {
             new_liquidity_manager,
         } => managers::change_liquidity_manager(deps, env, info, new_liquidity_manager),
         ExecuteMsg::ChangeDenomManager { new_denom_manager } => {
             managers::change_denom_manager(deps, env, info, new_denom_manager)
         }
+        ExecuteMsg::ChangeOperationTimeout { operation_timeout } => {
+            managers::change_operation_timeout(deps, env, info, operation_timeout)
+        }
         ExecuteMsg::Send { op_id, op_args } => operation::send(deps, env, info, op_id, op_args),
+        ExecuteMsg::SendMulti { op_id, op_args } => {
+            operation::send_multi(deps, env, info, op_id, op_args)
+        }
+        ExecuteMsg::Refund { seq } => operation::refund(deps, env, info, seq),
+        ExecuteMsg::SettleRefund { withdrawal_id } => {
+            operation::settle_refund(deps, env, info, withdrawal_id)
+        }
+        ExecuteMsg::HookSend {
+            channel,
+            original_sender,
+            op_id,
+            op_args,
+        } => hooks::hook_send(deps, env, info, channel, original_sender, op_id, op_args),
+        ExecuteMsg::RetryRefund { seq } => hooks::retry_refund(deps, env, info, seq),
         ExecuteMsg::Execute {
             msgs,
             req_op_id,
-            signature,
-        } => operation::execute(deps, env, info, msgs, req_op_id, signature),
+            expires_at,
+            signatures,
+        } => operation::execute(deps, env, info, msgs, req_op_id, expires_at, signatures),
+        ExecuteMsg::CommitRoot {
+            root,
+            req_op_id,
+            expires_at,
+            signatures,
+        } => roots::commit_root(deps, env, info, root, req_op_id, expires_at, signatures),
+        ExecuteMsg::ExecuteLeaf { root, leaf, proof } => {
+            roots::execute_leaf(deps, env, info, root, leaf, proof)
+        }
+        ExecuteMsg::RevokeRoot { root } => roots::revoke_root(deps, info, root),
+        ExecuteMsg::ExecuteBatch {
+            msgs,
+            req_op_id,
+            expires_at,
+            signatures,
+        } => batch::execute_batch(deps, env, info, msgs, req_op_id, expires_at, signatures),
+        ExecuteMsg::UpdateSignerSet {
+            signers,
+            threshold,
+            req_op_id,
+            expires_at,
+            signatures,
+        } => signers::update_signer_set(
+            deps, env, info, signers, threshold, req_op_id, expires_at, signatures,
+        ),
+        ExecuteMsg::RotateSigner {
+            old_public_key,
+            new_public_key,
+            scheme,
+            grace_period,
+            req_op_id,
+            expires_at,
+            signatures,
+        } => signers::rotate_signer(
+            deps,
+            env,
+            info,
+            old_public_key,
+            new_public_key,
+            scheme,
+            grace_period,
+            req_op_id,
+            expires_at,
+            signatures,
+        ),
+        ExecuteMsg::Withdraw {
+            to,
+            amounts,
+            req_op_id,
+            expires_at,
+            signatures,
+        } => withdraw::withdraw(
+            deps, env, info, to, amounts, req_op_id, expires_at, signatures,
+        ),
+        ExecuteMsg::RegisterOp {
+            op_id,
+            name,
+            args,
+            enabled,
+        } => ops::register_op(deps, env, info, op_id, name, args, enabled),
+        ExecuteMsg::SetExecutePolicy { policy } => {
+            policy::set_execute_policy(deps, env, info, policy)
+        }
+        ExecuteMsg::SetRateLimit { denom, limit } => {
+            limits::set_rate_limit(deps, env, info, denom, limit)
+        }
+        ExecuteMsg::SetOutflowWeight { denom, weight } => {
+            limits::set_outflow_weight(deps, env, info, denom, weight)
+        }
+        ExecuteMsg::SetIbcCounterparty {
+            connection_id,
+            port_id,
+        } => ibc::set_ibc_counterparty(deps, env, info, connection_id, port_id),
+        ExecuteMsg::SetSendChannel { channel_id } => {
+            ibc::set_send_channel(deps, env, info, channel_id)
+        }
+        ExecuteMsg::HandlePacket { channel_id, packet } => {
+            ibc::handle_packet(deps, env, info, channel_id, packet)
+        }
+        ExecuteMsg::UpdateOperationStatus { seq, status } => {
+            operation::update_operation_status(deps, env, info, seq, status)
+        }
         ExecuteMsg::Pause { expires_at } => gov::pause(deps, env, info, expires_at),
         ExecuteMsg::Release {} => gov::release(deps, env, info),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("cannot migrate from {}", stored.contract),
+        });
+    }
+
+    let legacy = migrate_legacy(deps.storage, &env, &msg)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("legacy_layout", legacy.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(msg) => hooks::ibc_lifecycle_complete(deps, msg),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
-        REPLY_WITHDRAW_SUBMESSAGE_SUCCESS => {
-            let withdraw_context = WITHDRAW.load(deps.storage)?;
-
-            let send_msg = BankMsg::Send {
-                to_address: withdraw_context.to_address.clone().into_string(),
-                amount: vec![withdraw_context.amount.clone()],
-            };
-
-            let resp = Response::new().add_message(send_msg).add_attributes(vec![
-                attr("action", "reply_withdraw"),
-                attr("to", withdraw_context.to_address),
-                attr("amount", withdraw_context.amount.to_string()),
-            ]);
-            Ok(resp)
-        }
+        REPLY_WITHDRAW_SUBMESSAGE_SUCCESS => withdraw::reply_withdraw(deps, env, msg),
+        REPLY_HOOK_REFUND_TRANSFER => hooks::reply_refund_transfer(deps, msg),
+        REPLY_IBC_RECEIVE_FAILURE => ibc::reply_receive_failure(msg),
+        REPLY_BATCH_MESSAGE => batch::reply_batch_message(deps, env, msg),
         id => Err(ContractError::ReplyIdNotFound { id }),
     }
 }
@@ -101,5 +242,38 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<QueryResponse, Contr
 
     match msg {
         QueryMsg::GetConfig {} => query::get_config(deps, env),
+        QueryMsg::GetPendingOwner {} => query::get_pending_owner(deps),
+        QueryMsg::IsExecuted { req_op_id } => query::get_is_executed(deps, req_op_id),
+        QueryMsg::GetRoot { root } => query::get_root(deps, env, root),
+        QueryMsg::GetSignerSet {} => query::get_signer_set(deps),
+        QueryMsg::GetExecutePolicy {} => query::get_execute_policy(deps),
+        QueryMsg::GetRateLimit { denom } => query::get_rate_limit(deps, env, denom),
+        QueryMsg::GetRateLimits {} => query::get_rate_limits(deps, env),
+        QueryMsg::GetOutflowWeights {} => query::get_outflow_weights(deps),
+        QueryMsg::GetChannel { channel_id } => query::get_channel(deps, channel_id),
+        QueryMsg::GetChannels { start_after, limit } => {
+            query::get_channels(deps, start_after, limit)
+        }
+        QueryMsg::GetBatchOutcomes {
+            req_op_id,
+            start_after,
+            limit,
+        } => query::get_batch_outcomes(deps, req_op_id, start_after, limit),
+        QueryMsg::GetOp { op_id } => query::get_op(deps, op_id),
+        QueryMsg::GetOps { start_after, limit } => query::get_ops(deps, start_after, limit),
+        QueryMsg::GetOperation { seq } => query::get_operation(deps, seq),
+        QueryMsg::GetOperations { start_after, limit } => {
+            query::get_operations(deps, start_after, limit)
+        }
+        QueryMsg::GetOperationsBySender {
+            sender,
+            start_after,
+            limit,
+        } => query::get_operations_by_sender(deps, sender, start_after, limit),
+        QueryMsg::GetOperationsByStatus {
+            status,
+            start_after,
+            limit,
+        } => query::get_operations_by_status(deps, status, start_after, limit),
     }
 }
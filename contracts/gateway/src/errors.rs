@@ -27,15 +27,81 @@ pub enum ContractError {
     #[error("Role Error: Addr {addr:?} has not role {role:?}")]
     RoleNotExist { addr: Addr, role: String },
 
+    #[error("No pending owner")]
+    NoPendingOwner {},
+
+    #[error("Pending owner expired")]
+    PendingOwnerExpired {},
+
     #[error("{msg:?}")]
     InvalidArgument { msg: String },
 
     #[error("Pay Error: You must send one asset")]
     MustPayOne {},
 
+    #[error("Pay Error: No funds sent")]
+    NoFunds {},
+
+    #[error("Denom {denom:?} is not supported by the denom manager")]
+    UnsupportedDenom { denom: String },
+
     #[error("Withdraw not flushed")]
     WithdrawNotFlushed {},
 
+    #[error("Message {index} not allowed: {reason}")]
+    MsgNotAllowed { index: usize, reason: String },
+
+    #[error("Unknown counterparty {port_id} on {connection_id}")]
+    UnknownCounterparty {
+        connection_id: String,
+        port_id: String,
+    },
+
+    #[error("Channel {channel_id} is not open")]
+    ChannelNotOpen { channel_id: String },
+
+    #[error("Invalid IBC channel: {reason}")]
+    InvalidChannel { reason: String },
+
+    #[error("Unsupported packet")]
+    UnsupportedPacket {},
+
+    #[error("Hook sender does not match {channel}/{original_sender}")]
+    InvalidHookSender {
+        channel: String,
+        original_sender: String,
+    },
+
+    #[error("No failed refund for operation {seq}")]
+    NoFailedRefund { seq: u64 },
+
+    #[error("No queued refund for withdrawal {withdrawal_id}")]
+    NoQueuedRefund { withdrawal_id: u64 },
+
+    #[error("Withdrawal {withdrawal_id} is still queued")]
+    WithdrawalQueued { withdrawal_id: u64 },
+
+    #[error("Merkle root {root} not found")]
+    RootNotFound { root: String },
+
+    #[error("Invalid Merkle proof")]
+    InvalidProof {},
+
+    #[error("Another batch is being executed")]
+    BatchInProgress {},
+
+    #[error("Batch context not found")]
+    BatchContextNotFound {},
+
+    #[error("Transfer context not found")]
+    TransferContextNotFound {},
+
+    #[error("Withdraw context not found")]
+    WithdrawContextNotFound {},
+
+    #[error("Withdraw failed: {reason}")]
+    WithdrawFailed { reason: String },
+
     #[error("Unbond not flushed")]
     UnbondNotFlushed {},
 
@@ -44,4 +110,49 @@ pub enum ContractError {
 
     #[error("invalid pub key")]
     InvalidPubKey {},
+
+    #[error("Request {req_op_id:?} already executed")]
+    AlreadyExecuted { req_op_id: u64 },
+
+    #[error("Signature expired")]
+    SignatureExpired {},
+
+    #[error("Unknown signer: {public_key:?}")]
+    UnknownSigner { public_key: String },
+
+    #[error("Duplicate signature from {public_key:?}")]
+    DuplicateSigner { public_key: String },
+
+    #[error("Signature threshold not met: {weight:?} < {threshold:?}")]
+    ThresholdNotMet { weight: u64, threshold: u64 },
+
+    #[error("Invalid operation status transition: {from:?} -> {to:?}")]
+    InvalidStatusTransition { from: String, to: String },
+
+    #[error("Operation {seq:?} passed its deadline")]
+    OperationExpired { seq: u64 },
+
+    #[error("Operation {seq:?} has not reached its deadline")]
+    OperationNotExpired { seq: u64 },
+
+    #[error("Unknown operation: {op_id:?}")]
+    UnknownOp { op_id: u64 },
+
+    #[error("Operation {op_id:?} is disabled")]
+    OpDisabled { op_id: u64 },
+
+    #[error("Operation {op_id:?} expects {expected:?} args, got {actual:?}")]
+    WrongArgCount {
+        op_id: u64,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Operation {op_id:?} arg {index:?} is not a valid {expected}: {value:?}")]
+    InvalidOpArg {
+        op_id: u64,
+        index: usize,
+        expected: String,
+        value: String,
+    },
 }
@@ -0,0 +1,330 @@
+use cosmwasm_std::{attr, CosmosMsg, DepsMut, Env, MessageInfo, Reply, Response, SubMsg};
+use cw_utils::Expiration;
+use mitosis_interface::gateway::{execute_batch_sign_bytes, SignerSignature};
+
+use crate::{
+    errors::ContractError,
+    execute::{
+        consts::REPLY_BATCH_MESSAGE, limits::collect_outflows, operation::authorize_execute,
+    },
+    state::{
+        batch::{record_outcome, start_batch},
+        limits::release_outflows,
+        LIQUIDITY_MANAGER,
+    },
+};
+
+// execute_batch is Execute with every message dispatched as its own submessage.
+// A failing message is reverted alone and recorded as failed under req_op_id,
+// so its leg can be retried with a new request. The whole batch is checked
+// against the outflow caps, and a failing message gives its outflows back.
+pub fn execute_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msgs: Vec<CosmosMsg>,
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    signatures: Vec<SignerSignature>,
+) -> Result<Response, ContractError> {
+    let total = u32::try_from(msgs.len())
+        .ok()
+        .filter(|total| *total > 0)
+        .ok_or_else(|| ContractError::InvalidArgument {
+            msg: "batch must have between 1 and 2^32-1 messages".to_string(),
+        })?;
+
+    let sign_bytes = execute_batch_sign_bytes(
+        env.block.chain_id.clone(),
+        env.contract.address.clone(),
+        req_op_id,
+        expires_at,
+        msgs.clone(),
+    )?;
+
+    if let Some(resp) = authorize_execute(
+        deps.branch(),
+        &env,
+        &info,
+        &msgs,
+        req_op_id,
+        expires_at,
+        &signatures,
+        &sign_bytes,
+    )? {
+        return Ok(resp);
+    }
+
+    let lmgr = LIQUIDITY_MANAGER.load(deps.storage)?;
+    let outflows = msgs
+        .iter()
+        .map(|msg| collect_outflows(std::slice::from_ref(msg), &lmgr))
+        .collect();
+    start_batch(deps.storage, req_op_id, total, outflows)?;
+
+    let resp = Response::new()
+        .add_submessages(
+            msgs.into_iter()
+                .map(|msg| SubMsg::reply_always(msg, REPLY_BATCH_MESSAGE)),
+        )
+        .add_attributes(vec![
+            attr("action", "execute_batch"),
+            attr("executor", info.sender),
+            attr("req_op_id", req_op_id.to_string()),
+            attr("count", total.to_string()),
+        ]);
+
+    Ok(resp)
+}
+
+pub fn reply_batch_message(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let (req_op_id, outcome, outflows) = record_outcome(deps.storage, msg.result)?;
+    if !outcome.success {
+        release_outflows(deps.storage, &outflows, env.block.time.seconds())?;
+    }
+
+    let mut resp = Response::new().add_attributes(vec![
+        attr("action", "batch_message"),
+        attr("req_op_id", req_op_id.to_string()),
+        attr("index", outcome.index.to_string()),
+        attr("success", outcome.success.to_string()),
+    ]);
+    if let Some(error) = outcome.error {
+        resp = resp.add_attribute("error", error);
+    }
+
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coins,
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, BankMsg, HexBinary, Storage, SubMsgResponse, SubMsgResult, Uint128,
+    };
+    use mitosis_interface::gateway::{
+        ExecutePolicy, MessageOutcome, MsgKind, RateLimit, SignatureScheme, Signer,
+    };
+
+    use crate::{
+        execute::operation::execute,
+        state::{
+            batch::{query_outcomes, BATCH},
+            limits::{query_rate_limit, set_rate_limit},
+            policy::EXECUTE_POLICY,
+            signers::{SignerSet, SIGNER_SET},
+            PAUSED,
+        },
+    };
+
+    use super::*;
+
+    const PUBLIC_KEYS: [&str; 2] = [
+        "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1",
+        "02ec18c82501c5088119251679b538e9cf8eae502956cc862c7778aa148365e886",
+    ];
+
+    // signatures of mock_batch_msgs with req_op_id 0 on mock_env's chain and contract
+    const SIGNATURES: [&str; 2] = [
+        "2d8f886938bcb1523864dc464068ca2c4825c8c95499b1696356905f1c9ac7e44e9b4b21b602434e1764036a1c5fd09786a2106d4adacd2f1c2ad85ef27d0ca7",
+        "2129e838b8cf43def912f3d087058ef19df7300813c53c1f849ecc249fc819f76594f3e43143ca5e35ae99294dcd6860f7a5a7c59d36119b46b9bb47ca2ad76d",
+    ];
+
+    // 2-of-2 signer set
+    fn mock_signer(storage: &mut dyn Storage) {
+        let signers = PUBLIC_KEYS
+            .iter()
+            .map(|key| Signer {
+                public_key: HexBinary::from_hex(key).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            })
+            .collect();
+
+        PAUSED.save(storage, &Default::default()).unwrap();
+        LIQUIDITY_MANAGER
+            .save(storage, &Addr::unchecked("contract"))
+            .unwrap();
+        SIGNER_SET
+            .save(storage, &SignerSet::new(signers, 2, 0).unwrap())
+            .unwrap();
+        EXECUTE_POLICY
+            .save(
+                storage,
+                &ExecutePolicy {
+                    kinds: vec![MsgKind::Bank],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+
+    fn mock_signatures() -> Vec<SignerSignature> {
+        PUBLIC_KEYS
+            .iter()
+            .zip(SIGNATURES)
+            .map(|(public_key, signature)| SignerSignature {
+                public_key: HexBinary::from_hex(public_key).unwrap(),
+                signature: HexBinary::from_hex(signature).unwrap(),
+            })
+            .collect()
+    }
+
+    fn mock_batch_msgs() -> Vec<CosmosMsg> {
+        vec![
+            BankMsg::Send {
+                to_address: "ADDR1".to_string(),
+                amount: coins(100000, "uosmo"),
+            }
+            .into(),
+            BankMsg::Send {
+                to_address: "ADDR2".to_string(),
+                amount: coins(50, "uosmo"),
+            }
+            .into(),
+        ]
+    }
+
+    fn mock_reply(result: SubMsgResult) -> Reply {
+        Reply {
+            id: REPLY_BATCH_MESSAGE,
+            result,
+        }
+    }
+
+    #[test]
+    fn test_execute_batch() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("relayer", &[]);
+
+        mock_signer(deps.as_mut().storage);
+        set_rate_limit(
+            deps.as_mut().storage,
+            "uosmo",
+            Some(RateLimit {
+                max_amount: Uint128::new(250000),
+                window: 86400,
+            }),
+        )
+        .unwrap();
+
+        // batch signatures don't authorize an all-or-nothing execute
+        let other_mode = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_batch_msgs(),
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(other_mode, ContractError::InvalidPubKey {}));
+
+        let result = execute_batch(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_batch_msgs(),
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.messages,
+            mock_batch_msgs()
+                .into_iter()
+                .map(|msg| SubMsg::reply_always(msg, REPLY_BATCH_MESSAGE))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(result.attributes[3], attr("count", "2"));
+
+        // the second message fails without reverting the first
+        reply_batch_message(
+            deps.as_mut(),
+            env.clone(),
+            mock_reply(SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            })),
+        )
+        .unwrap();
+        let failed = reply_batch_message(
+            deps.as_mut(),
+            env.clone(),
+            mock_reply(SubMsgResult::Err("insufficient funds".to_string())),
+        )
+        .unwrap();
+        assert_eq!(
+            failed.attributes[1..],
+            vec![
+                attr("req_op_id", "0"),
+                attr("index", "1"),
+                attr("success", "false"),
+                attr("error", "insufficient funds"),
+            ]
+        );
+        assert!(BATCH.may_load(&deps.storage).unwrap().is_none());
+
+        // only the message that went through counts against the cap
+        assert_eq!(
+            query_rate_limit(&deps.storage, "uosmo".to_string(), env.block.time.seconds())
+                .unwrap()
+                .used,
+            Uint128::new(100000)
+        );
+
+        assert_eq!(
+            query_outcomes(&deps.storage, 0, None, None).unwrap(),
+            vec![
+                MessageOutcome {
+                    index: 0,
+                    success: true,
+                    error: None,
+                },
+                MessageOutcome {
+                    index: 1,
+                    success: false,
+                    error: Some("insufficient funds".to_string()),
+                },
+            ]
+        );
+
+        let replay = execute_batch(
+            deps.as_mut(),
+            env,
+            info,
+            mock_batch_msgs(),
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            replay,
+            ContractError::AlreadyExecuted { req_op_id: 0 }
+        ));
+    }
+
+    #[test]
+    fn test_execute_batch_empty() {
+        let mut deps = mock_dependencies();
+
+        mock_signer(deps.as_mut().storage);
+
+        let empty = execute_batch(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            vec![],
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(empty, ContractError::InvalidArgument { .. }));
+    }
+}
@@ -1 +1,7 @@
 pub const REPLY_WITHDRAW_SUBMESSAGE_SUCCESS: u64 = 1;
+pub const REPLY_IBC_RECEIVE_FAILURE: u64 = 2;
+pub const REPLY_HOOK_REFUND_TRANSFER: u64 = 3;
+pub const REPLY_BATCH_MESSAGE: u64 = 4;
+
+// a year, which keeps send deadlines and ibc timeouts far from overflowing
+pub const MAX_OPERATION_TIMEOUT: u64 = 365 * 24 * 60 * 60;
@@ -0,0 +1,639 @@
+use cosmwasm_std::{
+    attr, Binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Reply, Response, Storage, SubMsg,
+};
+use cw_utils::one_coin;
+use mitosis_interface::gateway::{HookOrigin, IbcLifecycleComplete};
+use prost::Message;
+
+use crate::{
+    errors::ContractError,
+    execute::{
+        consts::REPLY_HOOK_REFUND_TRANSFER,
+        operation::{coins_to_string, deposit_operation},
+    },
+    proto::{MsgTransfer, MsgTransferResponse, ProtoCoin, MSG_TRANSFER_TYPE_URL},
+    state::{
+        hooks::{
+            dequeue_transfer, enqueue_transfers, fail_transfer, RefundTransfer, FAILED_REFUNDS,
+            INFLIGHT_TRANSFERS,
+        },
+        load_address_prefix,
+        operations::query_operation,
+        OPERATION_TIMEOUT,
+    },
+    verify::derive_intermediate_sender,
+};
+
+pub const TRANSFER_PORT: &str = "transfer";
+
+// hook_send is the Send of an ibc-hooks transfer. The hook runs from an address
+// derived from the receiving channel and the remote sender, which is checked so
+// the operation can be recorded against, and refunded to, the remote sender.
+pub fn hook_send(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel: String,
+    original_sender: String,
+    op_id: u64,
+    op_args: Vec<String>,
+) -> Result<Response, ContractError> {
+    let prefix = load_address_prefix(deps.storage, &env)?;
+    if derive_intermediate_sender(&channel, &original_sender, &prefix)? != info.sender {
+        return Err(ContractError::InvalidHookSender {
+            channel,
+            original_sender,
+        });
+    }
+
+    let amount = one_coin(&info).map_err(|_| ContractError::MustPayOne {})?;
+    let origin = HookOrigin {
+        channel,
+        original_sender,
+    };
+
+    deposit_operation(
+        deps,
+        env,
+        info,
+        vec![amount],
+        op_id,
+        op_args,
+        "hook_send",
+        Some(origin),
+    )
+}
+
+// refund_transfers sends the funds of a hook send back to its remote sender. The
+// memo asks ibc-hooks to report the outcome through sudo.
+pub fn refund_transfers(
+    storage: &mut dyn Storage,
+    env: &Env,
+    seq: u64,
+    origin: &HookOrigin,
+    amounts: &[Coin],
+) -> Result<Vec<SubMsg>, ContractError> {
+    let timeout = OPERATION_TIMEOUT.load(storage)?;
+    let memo = format!("{{\"ibc_callback\":\"{}\"}}", env.contract.address);
+
+    let mut msgs = vec![];
+    for amount in amounts {
+        let transfer = MsgTransfer {
+            source_port: TRANSFER_PORT.to_string(),
+            source_channel: origin.channel.clone(),
+            token: Some(ProtoCoin {
+                denom: amount.denom.clone(),
+                amount: amount.amount.to_string(),
+            }),
+            sender: env.contract.address.to_string(),
+            receiver: origin.original_sender.clone(),
+            timeout_timestamp: env.block.time.plus_seconds(timeout).nanos(),
+            memo: memo.clone(),
+        };
+
+        msgs.push(SubMsg::reply_on_success(
+            CosmosMsg::Stargate {
+                type_url: MSG_TRANSFER_TYPE_URL.to_string(),
+                value: Binary::from(transfer.encode_to_vec()),
+            },
+            REPLY_HOOK_REFUND_TRANSFER,
+        ));
+    }
+
+    enqueue_transfers(
+        storage,
+        amounts
+            .iter()
+            .map(|amount| RefundTransfer {
+                seq,
+                channel: origin.channel.clone(),
+                amount: amount.clone(),
+            })
+            .collect(),
+    )?;
+
+    Ok(msgs)
+}
+
+// reply_refund_transfer tracks a transfer by its packet sequence until ibc-hooks
+// reports its acknowledgement or timeout.
+pub fn reply_refund_transfer(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let transfer = dequeue_transfer(deps.storage)?;
+
+    let data = msg
+        .result
+        .into_result()
+        .map_err(|reason| ContractError::WithdrawFailed { reason })?
+        .data
+        .unwrap_or_default();
+    let sequence = MsgTransferResponse::decode(data.as_slice())
+        .map_err(|err| ContractError::InvalidArgument {
+            msg: format!("invalid transfer response: {err}"),
+        })?
+        .sequence;
+
+    INFLIGHT_TRANSFERS.save(deps.storage, (&transfer.channel, sequence), &transfer)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refund_transfer"),
+        attr("seq", transfer.seq.to_string()),
+        attr("channel", transfer.channel),
+        attr("sequence", sequence.to_string()),
+    ]))
+}
+
+// ibc_lifecycle_complete keeps the funds of a failed transfer, which ICS-20 has
+// returned to the gateway, for RetryRefund.
+pub fn ibc_lifecycle_complete(
+    deps: DepsMut,
+    msg: IbcLifecycleComplete,
+) -> Result<Response, ContractError> {
+    let (channel, sequence, success) = match msg {
+        IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        } => (channel, sequence, success),
+        IbcLifecycleComplete::IbcTimeout { channel, sequence } => (channel, sequence, false),
+    };
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", "ibc_lifecycle_complete"),
+        attr("channel", channel.clone()),
+        attr("sequence", sequence.to_string()),
+    ]);
+
+    let Some(transfer) = INFLIGHT_TRANSFERS.may_load(deps.storage, (&channel, sequence))? else {
+        return Ok(response);
+    };
+    INFLIGHT_TRANSFERS.remove(deps.storage, (&channel, sequence));
+
+    if !success {
+        fail_transfer(deps.storage, &transfer)?;
+    }
+
+    response = response.add_attributes(vec![
+        attr("seq", transfer.seq.to_string()),
+        attr("success", success.to_string()),
+    ]);
+
+    Ok(response)
+}
+
+pub fn retry_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seq: u64,
+) -> Result<Response, ContractError> {
+    let amounts = FAILED_REFUNDS
+        .may_load(deps.storage, seq)?
+        .ok_or(ContractError::NoFailedRefund { seq })?;
+    FAILED_REFUNDS.remove(deps.storage, seq);
+
+    let origin = query_operation(deps.storage, seq)?
+        .origin
+        .ok_or(ContractError::NoFailedRefund { seq })?;
+
+    let msgs = refund_transfers(deps.storage, &env, seq, &origin, &amounts)?;
+
+    Ok(Response::new().add_submessages(msgs).add_attributes(vec![
+        attr("action", "retry_refund"),
+        attr("executor", info.sender),
+        attr("seq", seq.to_string()),
+        attr("amount", coins_to_string(&amounts)),
+    ]))
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coin, coins,
+        testing::{mock_dependencies, mock_env, mock_info, MockStorage},
+        to_binary, Addr, ContractResult, Event, SubMsgResponse, SubMsgResult, SystemResult,
+    };
+    use mitosis_interface::{
+        denom_manager::ConvertResponse,
+        gateway::{OpSpec, OperationStatus},
+    };
+
+    use crate::{
+        execute::{
+            consts::REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+            operation::refund,
+            withdraw::{reply_withdraw, withdraw_submsgs},
+        },
+        state::{
+            hooks::HOOK_REFUND,
+            operations::record_operation,
+            ops::OP_SPECS,
+            refunds::{QueuedRefund, QUEUED_REFUNDS},
+            ADDRESS_PREFIX, DENOM_MANAGER, LIQUIDITY_MANAGER,
+        },
+        verify::address_prefix,
+    };
+
+    use super::*;
+
+    const GATEWAY: &str = "osmo1f6j7u6875p2cvyrgjr0d2uecyzah0kgeek38h39czwdhe5em3zyselm50a";
+    // intermediary of channel-0/cosmos1sender
+    const INTERMEDIARY: &str = "osmo1d9wa3m5gfzfut69wlaqal6r03vt3sjdgr2g7zk0fex79zqttqdvsns7vym";
+    const TIMEOUT: u64 = 3600;
+
+    fn mock_gateway_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(GATEWAY);
+        env
+    }
+
+    fn mock_origin() -> HookOrigin {
+        HookOrigin {
+            channel: "channel-0".to_string(),
+            original_sender: "cosmos1sender".to_string(),
+        }
+    }
+
+    fn mock_transfer_reply(sequence: u64) -> Reply {
+        Reply {
+            id: REPLY_HOOK_REFUND_TRANSFER,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(
+                    MsgTransferResponse { sequence }.encode_to_vec(),
+                )),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_derive_intermediate_sender() {
+        assert_eq!(
+            derive_intermediate_sender("channel-0", "cosmos1sender", "osmo").unwrap(),
+            INTERMEDIARY
+        );
+        assert_eq!(address_prefix(GATEWAY).unwrap(), "osmo");
+    }
+
+    #[test]
+    fn test_hook_send() {
+        let mut deps = mock_dependencies();
+        let env = mock_gateway_env();
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("lmgr"))
+            .unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        OP_SPECS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &OpSpec {
+                    op_id: 1,
+                    name: "transfer".to_string(),
+                    args: vec![],
+                    enabled: true,
+                },
+            )
+            .unwrap();
+        DENOM_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("dmgr"))
+            .unwrap();
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&ConvertResponse {
+                    token: "ibc/atom".to_string(),
+                    alias: "atom".to_string(),
+                })
+                .unwrap(),
+            ))
+        });
+
+        // the remote sender can't be claimed by anyone else
+        let impostor = hook_send(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("osmo1impostor", &coins(100, "ibc/atom")),
+            "channel-0".to_string(),
+            "cosmos1sender".to_string(),
+            1,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(impostor, ContractError::InvalidHookSender { .. }));
+
+        let other_channel = hook_send(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INTERMEDIARY, &coins(100, "ibc/atom")),
+            "channel-1".to_string(),
+            "cosmos1sender".to_string(),
+            1,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            other_channel,
+            ContractError::InvalidHookSender { .. }
+        ));
+
+        let result = hook_send(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INTERMEDIARY, &coins(100, "ibc/atom")),
+            "channel-0".to_string(),
+            "cosmos1sender".to_string(),
+            1,
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(result.attributes[0], attr("action", "hook_send"));
+
+        let op = query_operation(&deps.storage, 0).unwrap();
+        assert_eq!(op.sender, Addr::unchecked("cosmos1sender"));
+        assert_eq!(op.aliases, vec!["atom".to_string()]);
+        assert_eq!(op.origin, Some(mock_origin()));
+        assert_eq!(op.status, OperationStatus::Pending);
+
+        // the intermediary is derived with the configured prefix
+        ADDRESS_PREFIX
+            .save(deps.as_mut().storage, &"neutron".to_string())
+            .unwrap();
+        let other_prefix = hook_send(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(INTERMEDIARY, &coins(100, "ibc/atom")),
+            "channel-0".to_string(),
+            "cosmos1sender".to_string(),
+            1,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            other_prefix,
+            ContractError::InvalidHookSender { .. }
+        ));
+
+        let intermediary =
+            derive_intermediate_sender("channel-0", "cosmos1sender", "neutron").unwrap();
+        assert!(intermediary.starts_with("neutron1"));
+        hook_send(
+            deps.as_mut(),
+            env,
+            mock_info(&intermediary, &coins(100, "ibc/atom")),
+            "channel-0".to_string(),
+            "cosmos1sender".to_string(),
+            1,
+            vec![],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_refund_lifecycle() {
+        let mut deps = mock_dependencies();
+        let env = mock_gateway_env();
+
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+
+        let amounts = vec![coin(100, "ibc/atom"), coin(200, "uosmo")];
+        let msgs =
+            refund_transfers(deps.as_mut().storage, &env, 0, &mock_origin(), &amounts).unwrap();
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(
+            msgs[0].msg,
+            CosmosMsg::Stargate {
+                type_url: MSG_TRANSFER_TYPE_URL.to_string(),
+                value: Binary::from(
+                    MsgTransfer {
+                        source_port: "transfer".to_string(),
+                        source_channel: "channel-0".to_string(),
+                        token: Some(ProtoCoin {
+                            denom: "ibc/atom".to_string(),
+                            amount: "100".to_string(),
+                        }),
+                        sender: GATEWAY.to_string(),
+                        receiver: "cosmos1sender".to_string(),
+                        timeout_timestamp: env.block.time.plus_seconds(TIMEOUT).nanos(),
+                        memo: format!("{{\"ibc_callback\":\"{GATEWAY}\"}}"),
+                    }
+                    .encode_to_vec()
+                ),
+            }
+        );
+
+        reply_refund_transfer(deps.as_mut(), mock_transfer_reply(7)).unwrap();
+        reply_refund_transfer(deps.as_mut(), mock_transfer_reply(8)).unwrap();
+
+        // the first transfer arrives, the second times out
+        ibc_lifecycle_complete(
+            deps.as_mut(),
+            IbcLifecycleComplete::IbcAck {
+                channel: "channel-0".to_string(),
+                sequence: 7,
+                ack: "".to_string(),
+                success: true,
+            },
+        )
+        .unwrap();
+        ibc_lifecycle_complete(
+            deps.as_mut(),
+            IbcLifecycleComplete::IbcTimeout {
+                channel: "channel-0".to_string(),
+                sequence: 8,
+            },
+        )
+        .unwrap();
+
+        assert!(INFLIGHT_TRANSFERS.is_empty(&deps.storage));
+        assert_eq!(
+            FAILED_REFUNDS.load(&deps.storage, 0).unwrap(),
+            vec![coin(200, "uosmo")]
+        );
+
+        // unknown transfers are ignored
+        ibc_lifecycle_complete(
+            deps.as_mut(),
+            IbcLifecycleComplete::IbcTimeout {
+                channel: "channel-0".to_string(),
+                sequence: 8,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            FAILED_REFUNDS.load(&deps.storage, 0).unwrap(),
+            vec![coin(200, "uosmo")]
+        );
+    }
+
+    #[test]
+    fn test_refund_hook_send() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_gateway_env();
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("lmgr"))
+            .unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        record_operation(
+            deps.as_mut().storage,
+            Addr::unchecked("cosmos1sender"),
+            coins(100, "ibc/atom"),
+            vec!["atom".to_string()],
+            1,
+            vec![],
+            env.block.height,
+            env.block.time.seconds(),
+            Some(mock_origin()),
+        )
+        .unwrap();
+
+        // anyone can refund, the funds only go back to the remote sender
+        refund(deps.as_mut(), env.clone(), mock_info("osmo1anyone", &[]), 0).unwrap();
+        assert!(HOOK_REFUND.may_load(&deps.storage).unwrap().is_some());
+
+        let ok = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        });
+        let result = reply_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            Reply {
+                id: REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+                result: ok,
+            },
+        )
+        .unwrap();
+        let mut expected = MockStorage::new();
+        OPERATION_TIMEOUT.save(&mut expected, &TIMEOUT).unwrap();
+        assert_eq!(
+            result.messages,
+            refund_transfers(
+                &mut expected,
+                &env,
+                0,
+                &mock_origin(),
+                &coins(100, "ibc/atom"),
+            )
+            .unwrap()
+        );
+        assert!(HOOK_REFUND.may_load(&deps.storage).unwrap().is_none());
+
+        // the transfer timed out and is sent again
+        reply_refund_transfer(deps.as_mut(), mock_transfer_reply(1)).unwrap();
+        ibc_lifecycle_complete(
+            deps.as_mut(),
+            IbcLifecycleComplete::IbcTimeout {
+                channel: "channel-0".to_string(),
+                sequence: 1,
+            },
+        )
+        .unwrap();
+
+        env.block.time = env.block.time.plus_seconds(60);
+        let result =
+            retry_refund(deps.as_mut(), env.clone(), mock_info("osmo1anyone", &[]), 0).unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert!(FAILED_REFUNDS.may_load(&deps.storage, 0).unwrap().is_none());
+
+        let nothing_failed =
+            retry_refund(deps.as_mut(), env, mock_info("osmo1anyone", &[]), 0).unwrap_err();
+        assert!(matches!(
+            nothing_failed,
+            ContractError::NoFailedRefund { seq: 0 }
+        ));
+    }
+
+    #[test]
+    fn test_refund_hook_send_queued() {
+        let mut deps = mock_dependencies();
+        let env = mock_gateway_env();
+        let amounts = vec![coin(100, "ibc/atom"), coin(200, "uosmo")];
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("lmgr"))
+            .unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        record_operation(
+            deps.as_mut().storage,
+            Addr::unchecked("cosmos1sender"),
+            amounts.clone(),
+            vec!["atom".to_string(), "osmo".to_string()],
+            1,
+            vec![],
+            env.block.height,
+            env.block.time.seconds(),
+            Some(mock_origin()),
+        )
+        .unwrap();
+
+        // a hook send is withdrawn to the gateway itself
+        let result = refund(deps.as_mut(), env.clone(), mock_info("osmo1anyone", &[]), 0).unwrap();
+        assert_eq!(
+            result.messages,
+            withdraw_submsgs(&Addr::unchecked("lmgr"), None, &amounts).unwrap()
+        );
+
+        // the first leg is queued by the liquidity manager until it is settled
+        let queued = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("_contract_address", "lmgr")
+                .add_attribute("action", "queue_withdraw")
+                .add_attribute("withdrawal_id", "3")],
+            data: None,
+        });
+        let result = reply_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            Reply {
+                id: REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+                result: queued,
+            },
+        )
+        .unwrap();
+        assert!(result.messages.is_empty());
+        assert_eq!(result.attributes[4], attr("withdrawal_id", "3"));
+        assert_eq!(
+            QUEUED_REFUNDS.load(&deps.storage, 3).unwrap(),
+            QueuedRefund {
+                seq: 0,
+                liquidity_manager: Addr::unchecked("lmgr"),
+                amount: coin(100, "ibc/atom"),
+            }
+        );
+        assert!(FAILED_REFUNDS.may_load(&deps.storage, 0).unwrap().is_none());
+        assert!(HOOK_REFUND.may_load(&deps.storage).unwrap().is_some());
+
+        // the second is transferred back right away
+        let ok = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        });
+        let result = reply_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            Reply {
+                id: REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+                result: ok,
+            },
+        )
+        .unwrap();
+        let mut expected = MockStorage::new();
+        OPERATION_TIMEOUT.save(&mut expected, &TIMEOUT).unwrap();
+        assert_eq!(
+            result.messages,
+            refund_transfers(&mut expected, &env, 0, &mock_origin(), &coins(200, "uosmo"),)
+                .unwrap()
+        );
+        assert!(HOOK_REFUND.may_load(&deps.storage).unwrap().is_none());
+    }
+}
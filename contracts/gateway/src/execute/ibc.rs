@@ -0,0 +1,306 @@
+use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response};
+use mitosis_interface::gateway::PacketV1;
+
+use crate::{
+    errors::ContractError,
+    execute::{
+        limits::collect_outflows,
+        withdraw::{dispatch_withdraw, validate_withdraw},
+    },
+    state::{
+        assert_owned,
+        channels::{load_open_channel, COUNTERPARTIES, SEND_CHANNEL},
+        limits::record_outflows,
+        requests::consume_request,
+        LIQUIDITY_MANAGER, PAUSED,
+    },
+};
+
+pub fn set_ibc_counterparty(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    connection_id: String,
+    port_id: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    match &port_id {
+        Some(port_id) => COUNTERPARTIES.save(deps.storage, &connection_id, port_id)?,
+        None => COUNTERPARTIES.remove(deps.storage, &connection_id),
+    }
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "set_ibc_counterparty"),
+        attr("executor", info.sender),
+        attr("connection_id", connection_id),
+        attr("port_id", port_id.unwrap_or_else(|| "none".to_string())),
+    ]);
+
+    Ok(response)
+}
+
+pub fn set_send_channel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    match &channel_id {
+        Some(channel_id) => {
+            load_open_channel(deps.storage, channel_id)?;
+            SEND_CHANNEL.save(deps.storage, channel_id)?;
+        }
+        None => SEND_CHANNEL.remove(deps.storage),
+    }
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "set_send_channel"),
+        attr("executor", info.sender),
+        attr(
+            "channel_id",
+            channel_id.unwrap_or_else(|| "none".to_string()),
+        ),
+    ]);
+
+    Ok(response)
+}
+
+// handle_packet runs a received instruction in its own sub-message, so a failing
+// instruction is reverted as a whole and acknowledged with an error.
+pub fn handle_packet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: String,
+    packet: PacketV1,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let now = env.block.time.seconds();
+
+    match packet {
+        PacketV1::Execute { req_op_id, msgs } => {
+            let lmgr = LIQUIDITY_MANAGER.load(deps.storage)?;
+            record_outflows(deps.storage, &collect_outflows(&msgs, &lmgr), now)?;
+            consume_request(deps.storage, req_op_id)?;
+
+            Ok(Response::new().add_messages(msgs).add_attributes(vec![
+                attr("action", "ibc_execute"),
+                attr("channel_id", channel_id),
+                attr("req_op_id", req_op_id.to_string()),
+            ]))
+        }
+        PacketV1::Withdraw {
+            req_op_id,
+            to,
+            amounts,
+        } => {
+            let to = validate_withdraw(deps.api, &to, &amounts)?;
+            let msgs = dispatch_withdraw(deps.storage, &to, &amounts, req_op_id, now)?;
+
+            Ok(Response::new().add_submessages(msgs).add_attributes(vec![
+                attr("action", "ibc_withdraw"),
+                attr("channel_id", channel_id),
+                attr("req_op_id", req_op_id.to_string()),
+                attr("to", to),
+            ]))
+        }
+        PacketV1::Send { .. } => Err(ContractError::UnsupportedPacket {}),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coins,
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, BankMsg, CosmosMsg, Storage,
+    };
+    use mitosis_interface::gateway::IbcChannelInfo;
+
+    use crate::state::{channels::CHANNELS, context::WITHDRAW, requests::EXECUTED_REQUESTS, OWNER};
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+
+    fn release(storage: &mut dyn Storage) {
+        PAUSED.save(storage, &Default::default()).unwrap()
+    }
+
+    fn mock_channel(storage: &mut dyn Storage, channel_id: &str, open: bool) {
+        CHANNELS
+            .save(
+                storage,
+                channel_id,
+                &IbcChannelInfo {
+                    channel_id: channel_id.to_string(),
+                    connection_id: "connection-2".to_string(),
+                    counterparty_port_id: "their_port".to_string(),
+                    counterparty_channel_id: "channel-7".to_string(),
+                    open,
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_set_ibc_counterparty() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        release(deps.as_mut().storage);
+
+        let unauthorized = set_ibc_counterparty(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            "connection-2".to_string(),
+            Some("their_port".to_string()),
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let info = mock_info(owner.as_str(), &[]);
+        set_ibc_counterparty(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "connection-2".to_string(),
+            Some("their_port".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            COUNTERPARTIES.load(&deps.storage, "connection-2").unwrap(),
+            "their_port"
+        );
+
+        set_ibc_counterparty(deps.as_mut(), env, info, "connection-2".to_string(), None).unwrap();
+        assert!(COUNTERPARTIES
+            .may_load(&deps.storage, "connection-2")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_set_send_channel() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        release(deps.as_mut().storage);
+        mock_channel(deps.as_mut().storage, "channel-0", true);
+        mock_channel(deps.as_mut().storage, "channel-1", false);
+
+        let info = mock_info(owner.as_str(), &[]);
+        let closed = set_send_channel(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            Some("channel-1".to_string()),
+        )
+        .unwrap_err();
+        assert!(matches!(closed, ContractError::ChannelNotOpen { .. }));
+
+        set_send_channel(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            Some("channel-0".to_string()),
+        )
+        .unwrap();
+        assert_eq!(SEND_CHANNEL.load(&deps.storage).unwrap(), "channel-0");
+
+        set_send_channel(deps.as_mut(), env, info, None).unwrap();
+        assert!(SEND_CHANNEL.may_load(&deps.storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_handle_packet() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("lmgr"))
+            .unwrap();
+
+        let msgs: Vec<CosmosMsg> = vec![BankMsg::Send {
+            to_address: ADDR2.to_string(),
+            amount: coins(100, "uosmo"),
+        }
+        .into()];
+        let packet = PacketV1::Execute {
+            req_op_id: 1,
+            msgs: msgs.clone(),
+        };
+
+        let unauthorized = handle_packet(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            "channel-0".to_string(),
+            packet.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let info = mock_info(env.contract.address.as_str(), &[]);
+        let result = handle_packet(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "channel-0".to_string(),
+            packet.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert!(EXECUTED_REQUESTS.load(&deps.storage, 1).unwrap());
+
+        let replayed = handle_packet(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "channel-0".to_string(),
+            packet,
+        )
+        .unwrap_err();
+        assert!(matches!(replayed, ContractError::AlreadyExecuted { .. }));
+
+        let result = handle_packet(
+            deps.as_mut(),
+            env,
+            info,
+            "channel-0".to_string(),
+            PacketV1::Withdraw {
+                req_op_id: 2,
+                to: Addr::unchecked(ADDR2),
+                amounts: coins(100, "uosmo"),
+            },
+        )
+        .unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(
+            WITHDRAW.load(&deps.storage).unwrap().to_address,
+            Addr::unchecked(ADDR2)
+        );
+    }
+}
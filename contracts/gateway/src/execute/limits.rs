@@ -0,0 +1,430 @@
+use cosmwasm_std::{
+    attr, from_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Env, IbcMsg, MessageInfo,
+    Response, StdResult, Storage, WasmMsg,
+};
+use mitosis_interface::{gateway::RateLimit, liquidity_manager};
+
+use crate::{
+    errors::ContractError,
+    state::{
+        assert_owned,
+        limits::{
+            set_outflow_weight as save_outflow_weight, set_rate_limit as save_rate_limit,
+            GLOBAL_LIMIT,
+        },
+        ops::is_valid_denom,
+        PauseInfo, PAUSED,
+    },
+};
+
+pub fn set_rate_limit(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+    limit: Option<RateLimit>,
+) -> Result<Response, ContractError> {
+    // allowed while paused, so the owner can raise a cap that tripped the
+    // breaker before releasing the pause
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    if denom != GLOBAL_LIMIT && !is_valid_denom(&denom) {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("invalid denom: {denom}"),
+        });
+    }
+
+    if let Some(limit) = &limit {
+        if limit.max_amount.is_zero() || limit.window == 0 {
+            return Err(ContractError::InvalidArgument {
+                msg: "max_amount and window must be positive".to_string(),
+            });
+        }
+    }
+
+    save_rate_limit(deps.storage, &denom, limit.clone())?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "set_rate_limit"),
+        attr("executor", info.sender),
+        attr("denom", denom),
+        attr(
+            "limit",
+            limit.map_or("none".to_string(), |l| {
+                format!("{}/{}s", l.max_amount, l.window)
+            }),
+        ),
+    ]);
+
+    Ok(response)
+}
+
+pub fn set_outflow_weight(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+    weight: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    if !is_valid_denom(&denom) {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("invalid denom: {denom}"),
+        });
+    }
+
+    if weight.is_some_and(|w| w.is_zero()) {
+        return Err(ContractError::InvalidArgument {
+            msg: "weight must be positive".to_string(),
+        });
+    }
+
+    save_outflow_weight(deps.storage, &denom, weight)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "set_outflow_weight"),
+        attr("executor", info.sender),
+        attr("denom", denom),
+        attr(
+            "weight",
+            weight.map_or("none".to_string(), |w| w.to_string()),
+        ),
+    ]);
+
+    Ok(response)
+}
+
+// collect_outflows returns the funds the messages move out of the gateway or out
+// of the liquidity manager. A withdrawal that is sent on in the same batch is
+// counted twice. Stargate messages are opaque and not counted, so they should be
+// limited through the execute policy.
+pub fn collect_outflows(msgs: &[CosmosMsg], liquidity_manager: &Addr) -> Vec<Coin> {
+    let mut outflows = vec![];
+    for msg in msgs {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. } | BankMsg::Burn { amount }) => {
+                outflows.extend(amount.iter().cloned())
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => {
+                outflows.extend(funds.iter().cloned());
+
+                if contract_addr == liquidity_manager.as_str() {
+                    if let Ok(liquidity_manager::ExecuteMsg::Withdraw { amount, .. }) =
+                        from_binary(msg)
+                    {
+                        outflows.push(amount);
+                    }
+                }
+            }
+            CosmosMsg::Wasm(WasmMsg::Instantiate { funds, .. }) => {
+                outflows.extend(funds.iter().cloned())
+            }
+            CosmosMsg::Ibc(IbcMsg::Transfer { amount, .. }) => outflows.push(amount.clone()),
+            _ => {}
+        }
+    }
+
+    outflows
+}
+
+pub fn pause_until_released(storage: &mut dyn Storage) -> StdResult<()> {
+    PAUSED.save(
+        storage,
+        &PauseInfo {
+            paused: true,
+            expires_at: None,
+        },
+    )
+}
+
+// trip_breaker pauses the gateway until the owner releases it. The caller has to
+// return the response as Ok, otherwise the pause is reverted with the batch.
+pub fn trip_breaker(
+    storage: &mut dyn Storage,
+    info: MessageInfo,
+    req_op_id: u64,
+    limit: String,
+) -> Result<Response, ContractError> {
+    pause_until_released(storage)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "rate_limited"),
+        attr("executor", info.sender),
+        attr("req_op_id", req_op_id.to_string()),
+        attr("limit", limit),
+    ]);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coin, coins,
+        testing::{mock_dependencies, mock_env, mock_info},
+        to_binary, IbcTimeout, Storage, Timestamp, Uint128,
+    };
+
+    use crate::state::{
+        limits::{OUTFLOW_WEIGHTS, RATE_LIMITS},
+        OWNER,
+    };
+
+    use super::*;
+
+    const ADDR1: &str = "ADDR1";
+    const ADDR2: &str = "ADDR2";
+
+    fn release(storage: &mut dyn Storage) {
+        PAUSED.save(storage, &Default::default()).unwrap()
+    }
+
+    fn mock_limit() -> RateLimit {
+        RateLimit {
+            max_amount: Uint128::new(1000),
+            window: 86400,
+        }
+    }
+
+    #[test]
+    fn test_set_rate_limit() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        release(deps.as_mut().storage);
+
+        let unauthorized = set_rate_limit(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            "uosmo".to_string(),
+            Some(mock_limit()),
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let info = mock_info(owner.as_str(), &[]);
+        let zero_window = set_rate_limit(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "uosmo".to_string(),
+            Some(RateLimit {
+                window: 0,
+                ..mock_limit()
+            }),
+        )
+        .unwrap_err();
+        assert!(matches!(zero_window, ContractError::InvalidArgument { .. }));
+
+        let invalid_denom = set_rate_limit(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "1uosmo".to_string(),
+            Some(mock_limit()),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            invalid_denom,
+            ContractError::InvalidArgument { .. }
+        ));
+
+        let result = set_rate_limit(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "uosmo".to_string(),
+            Some(mock_limit()),
+        )
+        .unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "set_rate_limit"),
+                attr("executor", owner),
+                attr("denom", "uosmo"),
+                attr("limit", "1000/86400s"),
+            ]
+        );
+        assert_eq!(
+            RATE_LIMITS.load(&deps.storage, "uosmo").unwrap(),
+            mock_limit()
+        );
+
+        set_rate_limit(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "uosmo".to_string(),
+            None,
+        )
+        .unwrap();
+        assert!(RATE_LIMITS
+            .may_load(&deps.storage, "uosmo")
+            .unwrap()
+            .is_none());
+
+        // the global cap
+        set_rate_limit(
+            deps.as_mut(),
+            env,
+            info,
+            "*".to_string(),
+            Some(mock_limit()),
+        )
+        .unwrap();
+        assert_eq!(RATE_LIMITS.load(&deps.storage, "*").unwrap(), mock_limit());
+    }
+
+    #[test]
+    fn test_set_outflow_weight() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+
+        let unauthorized = set_outflow_weight(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            "uosmo".to_string(),
+            Some(Decimal::one()),
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let info = mock_info(owner.as_str(), &[]);
+        for (denom, weight) in [("*", Decimal::one()), ("uosmo", Decimal::zero())] {
+            let invalid = set_outflow_weight(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                denom.to_string(),
+                Some(weight),
+            )
+            .unwrap_err();
+            assert!(matches!(invalid, ContractError::InvalidArgument { .. }));
+        }
+
+        let weight = Decimal::from_ratio(1u128, 1_000_000u128);
+        let result = set_outflow_weight(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "uosmo".to_string(),
+            Some(weight),
+        )
+        .unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "set_outflow_weight"),
+                attr("executor", owner),
+                attr("denom", "uosmo"),
+                attr("weight", "0.000001"),
+            ]
+        );
+        assert_eq!(
+            OUTFLOW_WEIGHTS.load(&deps.storage, "uosmo").unwrap(),
+            weight
+        );
+
+        set_outflow_weight(deps.as_mut(), env, info, "uosmo".to_string(), None).unwrap();
+        assert!(OUTFLOW_WEIGHTS
+            .may_load(&deps.storage, "uosmo")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_collect_outflows() {
+        let lmgr = Addr::unchecked("lmgr");
+
+        let msgs: Vec<CosmosMsg> = vec![
+            BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: vec![coin(1, "uosmo"), coin(2, "uatom")],
+            }
+            .into(),
+            BankMsg::Burn {
+                amount: coins(3, "uosmo"),
+            }
+            .into(),
+            WasmMsg::Execute {
+                contract_addr: lmgr.to_string(),
+                msg: to_binary(&liquidity_manager::ExecuteMsg::Withdraw {
+                    withdrawer: None,
+                    amount: coin(4, "uosmo"),
+                })
+                .unwrap(),
+                funds: coins(5, "uosmo"),
+            }
+            .into(),
+            // not the liquidity manager
+            WasmMsg::Execute {
+                contract_addr: "other".to_string(),
+                msg: to_binary(&liquidity_manager::ExecuteMsg::Withdraw {
+                    withdrawer: None,
+                    amount: coin(100, "uosmo"),
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id: "channel-0".to_string(),
+                to_address: ADDR2.to_string(),
+                amount: coin(6, "uosmo"),
+                timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(1)),
+            }),
+        ];
+
+        assert_eq!(
+            collect_outflows(&msgs, &lmgr),
+            vec![
+                coin(1, "uosmo"),
+                coin(2, "uatom"),
+                coin(3, "uosmo"),
+                coin(5, "uosmo"),
+                coin(4, "uosmo"),
+                coin(6, "uosmo"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trip_breaker() {
+        let mut deps = mock_dependencies();
+        release(deps.as_mut().storage);
+
+        let result = trip_breaker(
+            deps.as_mut().storage,
+            mock_info(ADDR2, &[]),
+            3,
+            "uosmo".to_string(),
+        )
+        .unwrap();
+        assert!(result.messages.is_empty());
+        assert_eq!(result.attributes[0], attr("action", "rate_limited"));
+
+        // only the owner can lift it
+        let pause_info = PAUSED
+            .load(&deps.storage)
+            .unwrap()
+            .refresh(deps.as_mut().storage, &mock_env())
+            .unwrap();
+        assert!(matches!(
+            pause_info.assert_not_paused(),
+            Err(ContractError::PausedError {})
+        ));
+    }
+}
@@ -2,7 +2,8 @@ use cosmwasm_std::{attr, Addr, DepsMut, Env, MessageInfo, Response};
 
 use crate::{
     errors::ContractError,
-    state::{assert_owned, DENOM_MANAGER, LIQUIDITY_MANAGER, PAUSED},
+    execute::consts::MAX_OPERATION_TIMEOUT,
+    state::{assert_owned, DENOM_MANAGER, LIQUIDITY_MANAGER, OPERATION_TIMEOUT, PAUSED},
 };
 
 pub fn change_liquidity_manager(
@@ -53,6 +54,43 @@ pub fn change_denom_manager(
     Ok(response)
 }
 
+pub fn validate_operation_timeout(operation_timeout: u64) -> Result<(), ContractError> {
+    if operation_timeout == 0 || operation_timeout > MAX_OPERATION_TIMEOUT {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("operation_timeout must be between 1 and {MAX_OPERATION_TIMEOUT}"),
+        });
+    }
+
+    Ok(())
+}
+
+// change_operation_timeout only applies to sends made after the change.
+pub fn change_operation_timeout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operation_timeout: u64,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    validate_operation_timeout(operation_timeout)?;
+
+    OPERATION_TIMEOUT.save(deps.storage, &operation_timeout)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "change_operation_timeout"),
+        attr("executor", info.sender),
+        attr("operation_timeout", operation_timeout.to_string()),
+    ]);
+
+    Ok(response)
+}
+
 #[cfg(test)]
 mod test {
     use cosmwasm_std::{
@@ -104,8 +142,12 @@ mod test {
                 .unwrap_err();
         assert!(matches!(change_liquidity, ContractError::Unauthorized {}));
 
-        let change_denom = change_denom_manager(deps.as_mut(), env, info, contract).unwrap_err();
+        let change_denom =
+            change_denom_manager(deps.as_mut(), env.clone(), info.clone(), contract).unwrap_err();
         assert!(matches!(change_denom, ContractError::Unauthorized {}));
+
+        let change_timeout = change_operation_timeout(deps.as_mut(), env, info, 60).unwrap_err();
+        assert!(matches!(change_timeout, ContractError::Unauthorized {}));
     }
 
     #[test]
@@ -181,4 +223,41 @@ mod test {
         );
         assert_eq!(contract, DENOM_MANAGER.load(&deps.storage).unwrap())
     }
+
+    #[test]
+    fn test_change_operation_timeout() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+
+        mock_owner(deps.as_mut().storage, owner.clone());
+        let info = mock_info(owner.as_str(), &[]);
+
+        release(deps.as_mut().storage);
+
+        let zero =
+            change_operation_timeout(deps.as_mut(), env.clone(), info.clone(), 0).unwrap_err();
+        assert!(matches!(zero, ContractError::InvalidArgument { .. }));
+
+        let too_long = change_operation_timeout(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            MAX_OPERATION_TIMEOUT + 1,
+        )
+        .unwrap_err();
+        assert!(matches!(too_long, ContractError::InvalidArgument { .. }));
+
+        let change_timeout = change_operation_timeout(deps.as_mut(), env, info, 60).unwrap();
+        assert_eq!(
+            change_timeout.attributes,
+            vec![
+                attr("action", "change_operation_timeout"),
+                attr("executor", owner),
+                attr("operation_timeout", "60"),
+            ]
+        );
+        assert_eq!(60, OPERATION_TIMEOUT.load(&deps.storage).unwrap())
+    }
 }
@@ -1,5 +1,14 @@
+pub mod batch;
 pub mod consts;
 pub mod gov;
+pub mod hooks;
+pub mod ibc;
+pub mod limits;
 pub mod managers;
 pub mod operation;
+pub mod ops;
+pub mod policy;
 pub mod rbac;
+pub mod roots;
+pub mod signers;
+pub mod withdraw;
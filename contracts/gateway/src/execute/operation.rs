@@ -1,13 +1,37 @@
 use cosmwasm_std::{
-    attr, to_binary, CosmosMsg, DepsMut, Env, HexBinary, MessageInfo, Response, WasmMsg,
+    attr, to_binary, Addr, Coin, CosmosMsg, Deps, DepsMut, Env, IbcMsg, IbcTimeout, MessageInfo,
+    Response, Storage, Timestamp, WasmMsg,
+};
+use cw_utils::{one_coin, Expiration};
+use mitosis_interface::{
+    denom_manager,
+    gateway::{
+        execute_sign_bytes, GatewayPacket, HookOrigin, OperationStatus, PacketV1, SignerSignature,
+    },
+    liquidity_manager::{self, WithdrawalStatus},
 };
-use cw_utils::one_coin;
-use mitosis_interface::liquidity_manager;
 
 use crate::{
     errors::ContractError,
-    state::{LIQUIDITY_MANAGER, PUBLIC_KEY},
-    verify::sha256_digest,
+    execute::{
+        hooks::refund_transfers,
+        limits::{collect_outflows, trip_breaker},
+        withdraw::withdraw_submsgs,
+    },
+    state::{
+        channels::SEND_CHANNEL,
+        context::set_withdraw_info,
+        hooks::{HookRefund, HOOK_REFUND},
+        limits::{check_outflows, record_outflows},
+        operations::{query_operation, record_operation, status_key, update_status},
+        ops::{load_enabled_op, validate_op_args},
+        policy::{assert_msgs_allowed, EXECUTE_POLICY},
+        refunds::{cancel_refund, take_cancelled_refund, QUEUED_REFUNDS},
+        requests::consume_request,
+        signers::SIGNER_SET,
+        DENOM_MANAGER, LIQUIDITY_MANAGER, OPERATION_TIMEOUT, PAUSED,
+    },
+    verify::verify_signatures,
 };
 
 pub fn send(
@@ -19,52 +43,319 @@ pub fn send(
 ) -> Result<Response, ContractError> {
     let amount = one_coin(&info).map_err(|_| ContractError::MustPayOne {})?;
 
+    deposit_operation(deps, env, info, vec![amount], op_id, op_args, "send", None)
+}
+
+// send_multi forwards every attached coin in a single deposit.
+pub fn send_multi(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    op_id: u64,
+    op_args: Vec<String>,
+) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+
+    if info.funds.iter().any(|coin| coin.amount.is_zero()) {
+        return Err(ContractError::NoFunds {});
+    }
+
+    let amounts = info.funds.clone();
+    deposit_operation(deps, env, info, amounts, op_id, op_args, "send_multi", None)
+}
+
+// deposit_operation records the operation against the remote sender of a hook
+// send, or the caller otherwise. Every denom has to be known to the denom manager
+// so that relayers see its canonical alias instead of the local denom.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_operation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amounts: Vec<Coin>,
+    op_id: u64,
+    op_args: Vec<String>,
+    action: &str,
+    origin: Option<HookOrigin>,
+) -> Result<Response, ContractError> {
+    let spec = load_enabled_op(deps.storage, op_id)?;
+    validate_op_args(&spec, &op_args)?;
+
+    let aliases = resolve_aliases(deps.as_ref(), &amounts)?;
+
     let msg = liquidity_manager::ExecuteMsg::Deposit {
         depositor: Some(env.contract.address),
     };
 
     let lmgr = LIQUIDITY_MANAGER.load(deps.storage)?;
+    let timeout = OPERATION_TIMEOUT.load(deps.storage)?;
 
-    let resp = Response::new()
+    let sender = origin.as_ref().map_or(info.sender.clone(), |origin| {
+        Addr::unchecked(&origin.original_sender)
+    });
+
+    let now = env.block.time.seconds();
+    let deadline = now
+        .checked_add(timeout)
+        .ok_or(ContractError::InvalidArgument {
+            msg: "deadline overflow".to_string(),
+        })?;
+
+    let op = record_operation(
+        deps.storage,
+        sender,
+        amounts,
+        aliases,
+        op_id,
+        op_args.clone(),
+        env.block.height,
+        deadline,
+        origin,
+    )?;
+
+    let mut resp = Response::new()
         .add_message(WasmMsg::Execute {
             contract_addr: lmgr.into_string(),
             msg: to_binary(&msg)?,
             funds: info.funds,
         })
         .add_attributes(vec![
-            attr("action", "send"),
+            attr("action", action),
             attr("executor", info.sender),
-            attr("amount", amount.to_string()),
+            attr("seq", op.seq.to_string()),
+            attr("deadline", op.deadline.to_string()),
+            attr("amount", coins_to_string(&op.amounts)),
+            attr("alias", op.aliases.join(",")),
             attr("op_id", op_id.to_string()),
             attr("op_args", serde_json::to_string(&op_args).unwrap()),
         ]);
+
+    // announce the send to the counterpart gateway, it times out with the operation
+    if let Some(channel_id) = SEND_CHANNEL.may_load(deps.storage)? {
+        let packet = GatewayPacket::V1(PacketV1::Send {
+            seq: op.seq,
+            sender: op.sender,
+            amounts: op.amounts,
+            aliases: op.aliases,
+            op_id,
+            op_args,
+        });
+
+        resp = resp.add_message(IbcMsg::SendPacket {
+            channel_id,
+            data: to_binary(&packet)?,
+            timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(op.deadline)),
+        });
+    }
+
     Ok(resp)
 }
 
-pub fn execute(
+// resolve_aliases returns the denom manager alias of each coin, in order.
+fn resolve_aliases(deps: Deps, amounts: &[Coin]) -> Result<Vec<String>, ContractError> {
+    let dmgr = DENOM_MANAGER.load(deps.storage)?;
+
+    amounts
+        .iter()
+        .map(|coin| {
+            deps.querier
+                .query_wasm_smart::<denom_manager::ConvertResponse>(
+                    dmgr.as_str(),
+                    &denom_manager::QueryMsg::Convert {
+                        token: coin.denom.clone(),
+                    },
+                )
+                .map(|resp| resp.alias)
+                .map_err(|_| ContractError::UnsupportedDenom {
+                    denom: coin.denom.clone(),
+                })
+        })
+        .collect()
+}
+
+// coins_to_string joins coins with commas, e.g. "100uatom,200uosmo".
+pub fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn update_operation_status(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seq: u64,
+    status: OperationStatus,
+) -> Result<Response, ContractError> {
+    // status updates are authorized by the signer set through execute
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // refunds have to pay the sender back, see refund
+    if status == OperationStatus::Refunded {
+        return Err(ContractError::InvalidArgument {
+            msg: "use refund to refund an operation".to_string(),
+        });
+    }
+
+    let op = update_status(deps.storage, seq, status, env.block.time.seconds())?;
+
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "update_operation_status"),
+        attr("seq", op.seq.to_string()),
+        attr("status", status_key(op.status)),
+    ]);
+
+    Ok(resp)
+}
+
+pub fn refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seq: u64,
+) -> Result<Response, ContractError> {
+    // a hook send is refunded to its remote sender, so anyone may trigger it
+    let op = query_operation(deps.storage, seq)?;
+    if op.origin.is_none() && op.sender != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // legs whose queued withdrawal was cancelled are withdrawn again, the
+    // operation itself is refunded only once
+    let (op, amounts) = match take_cancelled_refund(deps.storage, seq)? {
+        Some(amounts) => (op, amounts),
+        None => {
+            let op = update_status(
+                deps.storage,
+                seq,
+                OperationStatus::Refunded,
+                env.block.time.seconds(),
+            )?;
+            let amounts = op.amounts.clone();
+
+            (op, amounts)
+        }
+    };
+
+    // a local sender is paid by the liquidity manager, a hook send is withdrawn
+    // to the gateway and transferred back on reply
+    set_withdraw_info(deps.storage, op.sender.clone(), amounts.clone(), Some(seq))?;
+    let withdrawer = match op.origin {
+        Some(origin) => {
+            HOOK_REFUND.save(deps.storage, &HookRefund { seq, origin })?;
+            None
+        }
+        None => Some(&op.sender),
+    };
+
+    let lmgr = LIQUIDITY_MANAGER.load(deps.storage)?;
+
+    let resp = Response::new()
+        .add_submessages(withdraw_submsgs(&lmgr, withdrawer, &amounts)?)
+        .add_attributes(vec![
+            attr("action", "refund"),
+            attr("executor", info.sender),
+            attr("seq", seq.to_string()),
+            attr("amount", coins_to_string(&amounts)),
+        ]);
+
+    Ok(resp)
+}
+
+// settle_refund resolves a refund leg the liquidity manager queued. Once the
+// withdrawal is finalized a local sender has been paid, and the funds of a hook
+// send have reached the gateway and are transferred back. Once it is cancelled
+// the funds are back in the gateway's deposit and the leg is left for Refund.
+pub fn settle_refund(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
+    info: MessageInfo,
+    withdrawal_id: u64,
+) -> Result<Response, ContractError> {
+    let queued = QUEUED_REFUNDS
+        .may_load(deps.storage, withdrawal_id)?
+        .ok_or(ContractError::NoQueuedRefund { withdrawal_id })?;
+
+    let withdrawal = deps
+        .querier
+        .query_wasm_smart::<liquidity_manager::WithdrawalResponse>(
+            &queued.liquidity_manager,
+            &liquidity_manager::QueryMsg::GetWithdrawal { withdrawal_id },
+        )?;
+    if withdrawal.depositor != env.contract.address || withdrawal.amount != queued.amount {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("withdrawal {withdrawal_id} does not match the queued refund"),
+        });
+    }
+
+    let mut resp = Response::new();
+    let status = match withdrawal.status {
+        WithdrawalStatus::Queued => {
+            return Err(ContractError::WithdrawalQueued { withdrawal_id });
+        }
+        WithdrawalStatus::Finalized => {
+            if let Some(origin) = query_operation(deps.storage, queued.seq)?.origin {
+                resp = resp.add_submessages(refund_transfers(
+                    deps.storage,
+                    &env,
+                    queued.seq,
+                    &origin,
+                    std::slice::from_ref(&queued.amount),
+                )?);
+            }
+            "finalized"
+        }
+        WithdrawalStatus::Cancelled => {
+            cancel_refund(deps.storage, queued.seq, &queued.amount)?;
+            "cancelled"
+        }
+    };
+    QUEUED_REFUNDS.remove(deps.storage, withdrawal_id);
+
+    Ok(resp.add_attributes(vec![
+        attr("action", "settle_refund"),
+        attr("executor", info.sender),
+        attr("withdrawal_id", withdrawal_id.to_string()),
+        attr("seq", queued.seq.to_string()),
+        attr("amount", queued.amount.to_string()),
+        attr("status", status),
+    ]))
+}
+
+pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msgs: Vec<CosmosMsg>,
     req_op_id: u64,
-    signature: HexBinary,
+    expires_at: Option<Expiration>,
+    signatures: Vec<SignerSignature>,
 ) -> Result<Response, ContractError> {
-    let public_key = PUBLIC_KEY
-        .load(deps.storage)
-        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
-
-    let hash = sha256_digest(to_binary(&msgs)?)?;
-    let verify = deps
-        .api
-        .secp256k1_verify(
-            &hash,
-            signature.to_vec().as_slice(),
-            public_key.to_vec().as_slice(),
-        )
-        .map_err(|_| ContractError::InvalidPubKey {})?;
+    let sign_bytes = execute_sign_bytes(
+        env.block.chain_id.clone(),
+        env.contract.address.clone(),
+        req_op_id,
+        expires_at,
+        msgs.clone(),
+    )?;
 
-    if !verify {
-        return Err(ContractError::InvalidPubKey {}); // TODO: more specify contract err
+    if let Some(resp) = authorize_execute(
+        deps.branch(),
+        &env,
+        &info,
+        &msgs,
+        req_op_id,
+        expires_at,
+        &signatures,
+        &sign_bytes,
+    )? {
+        return Ok(resp);
     }
 
     let resp = Response::new().add_messages(msgs).add_attributes(vec![
@@ -76,19 +367,93 @@ pub fn execute(
     Ok(resp)
 }
 
+// authorize_execute runs the checks shared by Execute and ExecuteBatch and
+// consumes the request. If the messages would exceed an outflow cap, it returns
+// the response of the tripped breaker instead and the messages must be dropped.
+#[allow(clippy::too_many_arguments)]
+pub fn authorize_execute(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    msgs: &[CosmosMsg],
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    signatures: &[SignerSignature],
+    sign_bytes: &[u8],
+) -> Result<Option<Response>, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, env)?
+        .assert_not_paused()?;
+
+    if let Some(expiration) = expires_at {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::SignatureExpired {});
+        }
+    }
+
+    let signer_set = SIGNER_SET
+        .load(deps.storage)
+        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
+    verify_signatures(deps.api, &env.block, &signer_set, sign_bytes, signatures)?;
+
+    admit_msgs(deps.storage, env, info, msgs, req_op_id)
+}
+
+// admit_msgs applies the execute policy and the outflow caps to authorized
+// messages and consumes their request.
+pub fn admit_msgs(
+    storage: &mut dyn Storage,
+    env: &Env,
+    info: &MessageInfo,
+    msgs: &[CosmosMsg],
+    req_op_id: u64,
+) -> Result<Option<Response>, ContractError> {
+    let policy = EXECUTE_POLICY.may_load(storage)?.unwrap_or_default();
+    assert_msgs_allowed(&policy, msgs)?;
+
+    let lmgr = LIQUIDITY_MANAGER.load(storage)?;
+    let outflows = collect_outflows(msgs, &lmgr);
+    let now = env.block.time.seconds();
+    if let Some(limit) = check_outflows(storage, &outflows, now)? {
+        return trip_breaker(storage, info.clone(), req_op_id, limit).map(Some);
+    }
+    record_outflows(storage, &outflows, now)?;
+
+    consume_request(storage, req_op_id)?;
+
+    Ok(None)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::state::OWNER;
+    use crate::contracts;
+    use crate::state::{
+        context::{WithdrawInfo, WITHDRAW},
+        limits::{query_rate_limit, set_rate_limit},
+        operations::{operations, OperationInfo},
+        ops::OP_SPECS,
+        refunds::QueuedRefund,
+        requests::EXECUTED_REQUESTS,
+        signers::SignerSet,
+        OWNER,
+    };
 
     use super::*;
+    use crate::execute::consts::REPLY_WITHDRAW_SUBMESSAGE_SUCCESS;
     use cosmwasm_std::{
-        coin, coins,
-        testing::{mock_dependencies, mock_env, mock_info},
-        Addr, BankMsg, SubMsg,
+        coin, coins, from_binary,
+        testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+        Addr, BankMsg, ContractResult, HexBinary, OwnedDeps, Storage, SubMsg, SystemResult,
+        Uint128, WasmQuery,
+    };
+    use mitosis_interface::gateway::{
+        ExecuteMsg, ExecutePolicy, MsgKind, OpArgType, OpSpec, RateLimit, SignatureScheme, Signer,
     };
 
     const ADDR1: &str = "ADDR1";
     const ADDR2: &str = "ADDR2";
+    const TIMEOUT: u64 = 3600;
 
     #[test]
     fn test_not_send_assets() {
@@ -111,29 +476,38 @@ mod test {
         let contract = Addr::unchecked("contract");
         let info = mock_info(addr.as_str(), &coins(200000, "uosmo"));
 
+        mock_denom_manager(&mut deps);
         LIQUIDITY_MANAGER
             .save(deps.as_mut().storage, &contract)
             .unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        mock_op(deps.as_mut().storage, vec![OpArgType::Bytes]);
 
         let result = send(
             deps.as_mut(),
             env.clone(),
             info.clone(),
             1u64,
-            vec![String::from("hello")],
+            vec![String::from("0xbeef")],
         )
         .unwrap();
+        let deadline = env.block.time.seconds() + TIMEOUT;
 
         assert_eq!(
             result.attributes,
             vec![
                 attr("action", "send"),
-                attr("executor", addr),
+                attr("executor", addr.clone()),
+                attr("seq", "0"),
+                attr("deadline", deadline.to_string()),
                 attr("amount", info.funds[0].to_string()),
+                attr("alias", "alias/uosmo"),
                 attr("op_id", "1"),
                 attr(
                     "op_args",
-                    serde_json::to_string(&vec![String::from("hello")]).unwrap()
+                    serde_json::to_string(&vec![String::from("0xbeef")]).unwrap()
                 )
             ]
         );
@@ -146,125 +520,1285 @@ mod test {
             vec![SubMsg::new(WasmMsg::Execute {
                 contract_addr: contract.into_string(),
                 msg: to_binary(&msg).unwrap(),
-                funds: info.funds,
+                funds: info.funds.clone(),
             })]
-        )
+        );
+
+        assert_eq!(
+            query_operation(&deps.storage, 0).unwrap(),
+            OperationInfo {
+                seq: 0,
+                sender: addr,
+                amounts: info.funds.clone(),
+                aliases: vec![String::from("alias/uosmo")],
+                op_id: 1,
+                op_args: vec![String::from("0xbeef")],
+                height: env.block.height,
+                deadline,
+                status: OperationStatus::Pending,
+                origin: None,
+            }
+        );
+    }
+
+    fn mock_op(storage: &mut dyn Storage, args: Vec<OpArgType>) {
+        OP_SPECS
+            .save(
+                storage,
+                1,
+                &OpSpec {
+                    op_id: 1,
+                    name: "transfer".to_string(),
+                    args,
+                    enabled: true,
+                },
+            )
+            .unwrap();
     }
 
     #[test]
-    fn test_send_multiple_assets_failure() {
+    fn test_send_unsupported_denom() {
         let mut deps = mock_dependencies();
         let env = mock_env();
 
-        let sender = Addr::unchecked(ADDR1);
-        let contract = Addr::unchecked("contract");
-        let info = mock_info(
-            sender.as_str(),
-            &[coin(200000, "uosmo"), coin(100000, "uusdc")],
-        );
-
-        LIQUIDITY_MANAGER
-            .save(deps.as_mut().storage, &contract)
-            .unwrap();
+        mock_op(deps.as_mut().storage, vec![]);
+        mock_denom_manager(&mut deps);
 
-        let result = send(deps.as_mut(), env, info, 1u64, vec![]).unwrap_err();
-        assert!(matches!(result, ContractError::MustPayOne {}))
+        let unsupported = send(
+            deps.as_mut(),
+            env,
+            mock_info(ADDR1, &coins(100, "unknown")),
+            1u64,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            unsupported,
+            ContractError::UnsupportedDenom { denom } if denom == "unknown"
+        ));
+        assert!(query_operation(&deps.storage, 0).is_err());
     }
 
     #[test]
-    fn test_execute_failure() {
+    fn test_send_invalid_op() {
         let mut deps = mock_dependencies();
         let env = mock_env();
 
-        let owner = Addr::unchecked(ADDR1);
-        let sender = Addr::unchecked(ADDR2);
+        let info = mock_info(ADDR1, &coins(200000, "uosmo"));
 
-        let info = mock_info(sender.as_str(), &[]);
+        let unknown = send(deps.as_mut(), env.clone(), info.clone(), 1u64, vec![]).unwrap_err();
+        assert!(matches!(unknown, ContractError::UnknownOp { op_id: 1 }));
 
-        PUBLIC_KEY
-            .save(deps.as_mut().storage, &HexBinary::from(vec![1u8, 2u8]))
-            .unwrap();
-        OWNER.save(deps.as_mut().storage, &owner).unwrap();
-        let result = execute(
+        mock_op(
+            deps.as_mut().storage,
+            vec![OpArgType::Address, OpArgType::Uint],
+        );
+        let ill_typed = send(
             deps.as_mut(),
-            env.clone(),
+            env,
             info,
-            vec![],
-            0,
-            HexBinary::from_hex("12").unwrap(),
+            1u64,
+            vec![
+                String::from("osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2"),
+                String::from("one"),
+            ],
         )
         .unwrap_err();
+        assert!(matches!(
+            ill_typed,
+            ContractError::InvalidOpArg {
+                op_id: 1,
+                index: 1,
+                ..
+            }
+        ));
+        assert!(!operations().has(&deps.storage, 0));
+    }
 
-        assert!(matches!(result, ContractError::Unauthorized {}));
+    fn mock_send(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, env: Env, sender: &Addr) {
+        mock_denom_manager(deps);
+        LIQUIDITY_MANAGER
+            .save(&mut deps.storage, &Addr::unchecked("contract"))
+            .unwrap();
+        OPERATION_TIMEOUT.save(&mut deps.storage, &TIMEOUT).unwrap();
+        mock_op(&mut deps.storage, vec![]);
 
-        let info = mock_info(owner.as_str(), &[]);
-        let result = execute(
+        send(
             deps.as_mut(),
             env,
-            info,
+            mock_info(sender.as_str(), &coins(200000, "uosmo")),
+            1u64,
             vec![],
-            0,
-            HexBinary::from_hex("12").unwrap(),
         )
-        .unwrap_err();
-
-        assert!(matches!(result, ContractError::InvalidPubKey {}))
+        .unwrap();
     }
 
     #[test]
-    fn test_execute_successfully() {
+    fn test_send_over_ibc() {
         let mut deps = mock_dependencies();
         let env = mock_env();
 
-        let owner = Addr::unchecked(ADDR1);
-        let info = mock_info(owner.as_str(), &[]);
-        let contract: Addr = Addr::unchecked("contract");
-        let public_key = HexBinary::from(vec![
-            2, 191, 219, 148, 192, 213, 90, 105, 81, 110, 121, 164, 102, 210, 194, 26, 140, 10, 19,
-            2, 139, 176, 7, 14, 221, 13, 10, 7, 195, 19, 186, 83, 238,
-        ]);
-
-        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        let addr = Addr::unchecked(ADDR1);
+        mock_denom_manager(&mut deps);
+        SEND_CHANNEL
+            .save(deps.as_mut().storage, &"channel-0".to_string())
+            .unwrap();
         LIQUIDITY_MANAGER
-            .save(deps.as_mut().storage, &contract)
+            .save(deps.as_mut().storage, &Addr::unchecked("contract"))
             .unwrap();
-        PUBLIC_KEY.save(deps.as_mut().storage, &public_key).unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        mock_op(deps.as_mut().storage, vec![]);
 
-        let msgs: Vec<CosmosMsg> = vec![BankMsg::Send {
-            to_address: owner.to_string(),
-            amount: coins(100000, "uosmo"),
-        }
-        .into()];
+        let result = send(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr.as_str(), &coins(200000, "uosmo")),
+            1u64,
+            vec![],
+        )
+        .unwrap();
 
-        let result = execute(
+        assert_eq!(
+            result.messages[1],
+            SubMsg::new(IbcMsg::SendPacket {
+                channel_id: "channel-0".to_string(),
+                data: to_binary(&GatewayPacket::V1(PacketV1::Send {
+                    seq: 0,
+                    sender: addr,
+                    amounts: coins(200000, "uosmo"),
+                    aliases: vec![String::from("alias/uosmo")],
+                    op_id: 1,
+                    op_args: vec![],
+                }))
+                .unwrap(),
+                timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(
+                    env.block.time.seconds() + TIMEOUT,
+                )),
+            })
+        );
+    }
+
+    #[test]
+    fn test_update_operation_status() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let addr = Addr::unchecked(ADDR1);
+        let gateway = mock_info(env.contract.address.as_str(), &[]);
+        mock_send(&mut deps, env.clone(), &addr);
+
+        let unauthorized = update_operation_status(
             deps.as_mut(),
-            env,
-            info,
-            msgs,
+            env.clone(),
+            mock_info(addr.as_str(), &[]),
+            0,
+            OperationStatus::Relayed,
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let refund = update_operation_status(
+            deps.as_mut(),
+            env.clone(),
+            gateway.clone(),
+            0,
+            OperationStatus::Refunded,
+        )
+        .unwrap_err();
+        assert!(matches!(refund, ContractError::InvalidArgument { .. }));
+
+        let result = update_operation_status(
+            deps.as_mut(),
+            env.clone(),
+            gateway,
             0,
-            HexBinary::from(vec![
-                245, 203, 35, 74, 190, 205, 192, 228, 239, 109, 138, 172, 195, 248, 157, 251, 142,
-                80, 76, 247, 112, 108, 193, 156, 235, 191, 2, 26, 29, 49, 146, 83, 113, 7, 13, 45,
-                130, 54, 230, 228, 81, 193, 132, 88, 49, 202, 195, 198, 46, 13, 152, 54, 178, 68,
-                151, 170, 97, 88, 240, 183, 245, 51, 243, 164,
-            ]),
+            OperationStatus::Relayed,
         )
         .unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "update_operation_status"),
+                attr("seq", "0"),
+                attr("status", "relayed"),
+            ]
+        );
+        assert_eq!(
+            query_operation(&deps.storage, 0).unwrap().status,
+            OperationStatus::Relayed
+        );
+    }
+
+    #[test]
+    fn test_refund() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let sender = Addr::unchecked(ADDR1);
+        let info = mock_info(sender.as_str(), &[]);
+        mock_send(&mut deps, env.clone(), &sender);
+
+        let early = refund(deps.as_mut(), env.clone(), info.clone(), 0).unwrap_err();
+        assert!(matches!(
+            early,
+            ContractError::OperationNotExpired { seq: 0 }
+        ));
+
+        env.block.time = env.block.time.plus_seconds(TIMEOUT);
 
+        let other = refund(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), 0).unwrap_err();
+        assert!(matches!(other, ContractError::Unauthorized {}));
+
+        let result = refund(deps.as_mut(), env.clone(), info.clone(), 0).unwrap();
         assert_eq!(
             result.attributes,
             vec![
-                attr("action", "execute"),
-                attr("executor", owner.clone()),
-                attr("req_op_id", "0")
+                attr("action", "refund"),
+                attr("executor", sender.clone()),
+                attr("seq", "0"),
+                attr("amount", coin(200000, "uosmo").to_string()),
             ]
         );
         assert_eq!(
             result.messages,
-            vec![SubMsg::new(BankMsg::Send {
-                to_address: owner.to_string(),
-                amount: coins(100000, "uosmo"),
-            })]
-        )
+            vec![SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: "contract".to_string(),
+                    msg: to_binary(&liquidity_manager::ExecuteMsg::Withdraw {
+                        withdrawer: Some(sender.clone()),
+                        amount: coin(200000, "uosmo"),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                },
+                REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+            )]
+        );
+        assert_eq!(
+            WITHDRAW.load(&deps.storage).unwrap(),
+            WithdrawInfo {
+                to_address: sender,
+                amount: coins(200000, "uosmo"),
+                refund_seq: Some(0),
+            }
+        );
+        assert_eq!(
+            query_operation(&deps.storage, 0).unwrap().status,
+            OperationStatus::Refunded
+        );
+
+        WITHDRAW.remove(&mut deps.storage);
+        let twice = refund(deps.as_mut(), env, info, 0).unwrap_err();
+        assert!(matches!(
+            twice,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+    }
+
+    fn mock_withdrawal(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        status: WithdrawalStatus,
+    ) {
+        let depositor = env.contract.address.clone();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "contract" => {
+                let liquidity_manager::QueryMsg::GetWithdrawal { withdrawal_id } =
+                    from_binary(msg).unwrap()
+                else {
+                    panic!("unexpected query");
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&liquidity_manager::WithdrawalResponse {
+                        withdrawal_id,
+                        depositor: depositor.clone(),
+                        withdrawer: Addr::unchecked(ADDR1),
+                        amount: coin(200000, "uosmo"),
+                        release_at: 0,
+                        status,
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => panic!("unexpected query"),
+        });
+    }
+
+    #[test]
+    fn test_settle_refund() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let sender = Addr::unchecked(ADDR1);
+        let info = mock_info(sender.as_str(), &[]);
+        mock_send(&mut deps, env.clone(), &sender);
+
+        env.block.time = env.block.time.plus_seconds(TIMEOUT);
+        refund(deps.as_mut(), env.clone(), info.clone(), 0).unwrap();
+        WITHDRAW.remove(&mut deps.storage);
+
+        // as recorded by the withdraw reply
+        let queued = QueuedRefund {
+            seq: 0,
+            liquidity_manager: Addr::unchecked("contract"),
+            amount: coin(200000, "uosmo"),
+        };
+        for id in [7, 8] {
+            QUEUED_REFUNDS.save(&mut deps.storage, id, &queued).unwrap();
+        }
+
+        let unknown = settle_refund(deps.as_mut(), env.clone(), info.clone(), 9).unwrap_err();
+        assert!(matches!(
+            unknown,
+            ContractError::NoQueuedRefund { withdrawal_id: 9 }
+        ));
+
+        mock_withdrawal(&mut deps, &env, WithdrawalStatus::Queued);
+        let pending = settle_refund(deps.as_mut(), env.clone(), info.clone(), 7).unwrap_err();
+        assert!(matches!(
+            pending,
+            ContractError::WithdrawalQueued { withdrawal_id: 7 }
+        ));
+
+        // the liquidity manager paid the sender
+        mock_withdrawal(&mut deps, &env, WithdrawalStatus::Finalized);
+        let finalized = settle_refund(deps.as_mut(), env.clone(), info.clone(), 7).unwrap();
+        assert!(finalized.messages.is_empty());
+        assert_eq!(finalized.attributes[5], attr("status", "finalized"));
+        assert!(!QUEUED_REFUNDS.has(&deps.storage, 7));
+
+        // a cancelled withdrawal was credited back, so the leg can be refunded again
+        mock_withdrawal(&mut deps, &env, WithdrawalStatus::Cancelled);
+        let cancelled = settle_refund(deps.as_mut(), env.clone(), info.clone(), 8).unwrap();
+        assert_eq!(
+            cancelled.attributes,
+            vec![
+                attr("action", "settle_refund"),
+                attr("executor", sender.clone()),
+                attr("withdrawal_id", "8"),
+                attr("seq", "0"),
+                attr("amount", "200000uosmo"),
+                attr("status", "cancelled"),
+            ]
+        );
+        let twice = settle_refund(deps.as_mut(), env.clone(), info.clone(), 8).unwrap_err();
+        assert!(matches!(twice, ContractError::NoQueuedRefund { .. }));
+
+        let result = refund(deps.as_mut(), env.clone(), info.clone(), 0).unwrap();
+        assert_eq!(result.attributes[3], attr("amount", "200000uosmo"));
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(WITHDRAW.load(&deps.storage).unwrap().refund_seq, Some(0));
+        assert_eq!(
+            query_operation(&deps.storage, 0).unwrap().status,
+            OperationStatus::Refunded
+        );
+
+        WITHDRAW.remove(&mut deps.storage);
+        let done = refund(deps.as_mut(), env, info, 0).unwrap_err();
+        assert!(matches!(
+            done,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+    }
+
+    #[test]
+    fn test_refund_acknowledged() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let sender = Addr::unchecked(ADDR1);
+        mock_send(&mut deps, env.clone(), &sender);
+
+        update_operation_status(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(env.contract.address.as_str(), &[]),
+            0,
+            OperationStatus::Relayed,
+        )
+        .unwrap();
+
+        env.block.time = env.block.time.plus_seconds(TIMEOUT);
+        let result = refund(deps.as_mut(), env, mock_info(sender.as_str(), &[]), 0).unwrap_err();
+        assert!(matches!(
+            result,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+    }
+
+    fn mock_denom_manager(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        DENOM_MANAGER
+            .save(&mut deps.storage, &Addr::unchecked("denom_manager"))
+            .unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "denom_manager" => {
+                let denom_manager::QueryMsg::Convert { token } = from_binary(msg).unwrap() else {
+                    panic!("unexpected query");
+                };
+                if token == "unknown" {
+                    SystemResult::Ok(ContractResult::Err("alias not found".to_string()))
+                } else {
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&denom_manager::ConvertResponse {
+                            alias: format!("alias/{token}"),
+                            token,
+                        })
+                        .unwrap(),
+                    ))
+                }
+            }
+            _ => panic!("unexpected query"),
+        });
+    }
+
+    #[test]
+    fn test_send_multi() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let sender = Addr::unchecked(ADDR1);
+        let funds = vec![coin(100, "uatom"), coin(200, "uosmo")];
+        let info = mock_info(sender.as_str(), &funds);
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("contract"))
+            .unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        mock_op(deps.as_mut().storage, vec![]);
+        mock_denom_manager(&mut deps);
+
+        let result = send_multi(deps.as_mut(), env.clone(), info, 1u64, vec![]).unwrap();
+        assert_eq!(result.attributes[0], attr("action", "send_multi"));
+        assert_eq!(result.attributes[4], attr("amount", "100uatom,200uosmo"));
+        assert_eq!(
+            result.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "contract".to_string(),
+                msg: to_binary(&liquidity_manager::ExecuteMsg::Deposit {
+                    depositor: Some(env.contract.address),
+                })
+                .unwrap(),
+                funds: funds.clone(),
+            })]
+        );
+        assert_eq!(query_operation(&deps.storage, 0).unwrap().amounts, funds);
+    }
+
+    #[test]
+    fn test_send_multi_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        mock_op(deps.as_mut().storage, vec![]);
+        mock_denom_manager(&mut deps);
+
+        let no_funds = send_multi(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            1u64,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(no_funds, ContractError::NoFunds {}));
+
+        let unsupported = send_multi(
+            deps.as_mut(),
+            env,
+            mock_info(ADDR1, &[coin(100, "uosmo"), coin(100, "unknown")]),
+            1u64,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            unsupported,
+            ContractError::UnsupportedDenom { denom } if denom == "unknown"
+        ));
+    }
+
+    #[test]
+    fn test_refund_multi() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let sender = Addr::unchecked(ADDR1);
+        let funds = vec![coin(100, "uatom"), coin(200, "uosmo")];
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &Addr::unchecked("contract"))
+            .unwrap();
+        OPERATION_TIMEOUT
+            .save(deps.as_mut().storage, &TIMEOUT)
+            .unwrap();
+        mock_op(deps.as_mut().storage, vec![]);
+        mock_denom_manager(&mut deps);
+        send_multi(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(sender.as_str(), &funds),
+            1u64,
+            vec![],
+        )
+        .unwrap();
+
+        env.block.time = env.block.time.plus_seconds(TIMEOUT);
+        let result = refund(deps.as_mut(), env, mock_info(sender.as_str(), &[]), 0).unwrap();
+
+        let withdraw = |amount: &Coin| WasmMsg::Execute {
+            contract_addr: "contract".to_string(),
+            msg: to_binary(&liquidity_manager::ExecuteMsg::Withdraw {
+                withdrawer: Some(sender.clone()),
+                amount: amount.clone(),
+            })
+            .unwrap(),
+            funds: vec![],
+        };
+        assert_eq!(
+            result.messages,
+            vec![
+                SubMsg::reply_on_success(withdraw(&funds[0]), REPLY_WITHDRAW_SUBMESSAGE_SUCCESS),
+                SubMsg::reply_on_success(withdraw(&funds[1]), REPLY_WITHDRAW_SUBMESSAGE_SUCCESS),
+            ]
+        );
+        assert_eq!(
+            WITHDRAW.load(&deps.storage).unwrap(),
+            WithdrawInfo {
+                to_address: sender,
+                amount: funds,
+                refund_seq: Some(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_send_multiple_assets_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let sender = Addr::unchecked(ADDR1);
+        let contract = Addr::unchecked("contract");
+        let info = mock_info(
+            sender.as_str(),
+            &[coin(200000, "uosmo"), coin(100000, "uusdc")],
+        );
+
+        LIQUIDITY_MANAGER
+            .save(deps.as_mut().storage, &contract)
+            .unwrap();
+
+        let result = send(deps.as_mut(), env, info, 1u64, vec![]).unwrap_err();
+        assert!(matches!(result, ContractError::MustPayOne {}))
+    }
+
+    #[test]
+    fn test_execute_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let relayer = Addr::unchecked(ADDR2);
+        let info = mock_info(relayer.as_str(), &[]);
+
+        PAUSED
+            .save(deps.as_mut().storage, &Default::default())
+            .unwrap();
+        SIGNER_SET
+            .save(
+                deps.as_mut().storage,
+                &SignerSet::new(
+                    vec![Signer {
+                        public_key: HexBinary::from(vec![2u8; 33]),
+                        weight: 1,
+                        scheme: SignatureScheme::Secp256k1,
+                    }],
+                    1,
+                    0,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        let signatures = vec![SignerSignature {
+            public_key: HexBinary::from(vec![2u8; 33]),
+            signature: HexBinary::from_hex("12").unwrap(),
+        }];
+        let result = execute(deps.as_mut(), env, info, vec![], 0, None, signatures).unwrap_err();
+
+        assert!(matches!(result, ContractError::InvalidPubKey {}))
+    }
+
+    #[test]
+    fn test_execute_not_allowed() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+        EXECUTE_POLICY
+            .save(deps.as_mut().storage, &ExecutePolicy::default())
+            .unwrap();
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            result,
+            ContractError::MsgNotAllowed { index: 0, .. }
+        ));
+
+        // the request can still be executed once the policy allows it
+        assert!(EXECUTED_REQUESTS
+            .may_load(&deps.storage, 0)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_execute_rate_limited() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+        set_rate_limit(
+            deps.as_mut().storage,
+            "uosmo",
+            Some(RateLimit {
+                max_amount: Uint128::new(99999),
+                window: 86400,
+            }),
+        )
+        .unwrap();
+
+        // the batch is dropped and the gateway pauses itself
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap();
+        assert!(result.messages.is_empty());
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "rate_limited"),
+                attr("executor", owner.clone()),
+                attr("req_op_id", "0"),
+                attr("limit", "uosmo"),
+            ]
+        );
+        assert!(EXECUTED_REQUESTS
+            .may_load(&deps.storage, 0)
+            .unwrap()
+            .is_none());
+
+        let paused = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(paused, ContractError::PausedError {}));
+
+        // raised by the owner while paused and released, the same request goes
+        // through
+        contracts::execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::SetRateLimit {
+                denom: "uosmo".to_string(),
+                limit: Some(RateLimit {
+                    max_amount: Uint128::new(100000),
+                    window: 86400,
+                }),
+            },
+        )
+        .unwrap();
+        contracts::execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Release {},
+        )
+        .unwrap();
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(
+            query_rate_limit(&deps.storage, "uosmo".to_string(), 0)
+                .unwrap()
+                .remaining,
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn test_execute_by_relayer() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let relayer = Addr::unchecked(ADDR2);
+        let info = mock_info(relayer.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        // signatures authorize the request, not the sender
+        let result = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap();
+        assert_eq!(result.attributes[1], attr("executor", relayer));
+    }
+
+    const PUBLIC_KEYS: [&str; 3] = [
+        "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1",
+        "02ec18c82501c5088119251679b538e9cf8eae502956cc862c7778aa148365e886",
+        "0361b91a764b947cb1409508e8b5b6c30d659c5101cbedc20a5f0bb0ad8e63a4e3",
+    ];
+
+    // signatures of mock_send_msgs with req_op_id 0 on mock_env's chain and contract
+    const SIGNATURES: [&str; 3] = [
+        "dead103b7e260f3cefbe047c571704dc8c50add7ff300ddde30ab97d896fc5d00ab5dcbc2399f581e37ec886c3442a5852b4d1e41a074d8a1e623eea9913c926",
+        "008ba4b377c3854af1ce489e714dc8ba236e22b3849da3b102f63504d95598a251aff7efbfb78f47286011d3baa3686892b4561dc7983b87fc3b6643ea89db73",
+        "29d3faae4ec23d3c57abbeb049a863c4919cd33859528e43cc79aaae255b06b836dabdef3bb2405faa6c1117a341f8b56bf9bcbccd3bfc266b04fa9e262d023c",
+    ];
+
+    // same as SIGNATURES, but expires at height 12346
+    const EXPIRING_SIGNATURES: [&str; 2] = [
+        "7f0fd73c34a4894459dd406c22bb0a88e9d59fa5e607a451bbbde919fd9ed4e4009afd10f8b44e78ee39ab68f4611002004c33cd19c68d5423544640109dfa5d",
+        "2b695d733bd0a4cdf6ad0009f405ef3e2fc49d3babb2775b6532e5c3cf509a5023091251692c08d0c12ac098a92138013c66c136f79b6438166623313220d812",
+    ];
+
+    // 2-of-3 signer set
+    fn mock_signer(storage: &mut dyn Storage, owner: &Addr) {
+        let signers = PUBLIC_KEYS
+            .iter()
+            .map(|key| Signer {
+                public_key: HexBinary::from_hex(key).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            })
+            .collect();
+
+        OWNER.save(storage, owner).unwrap();
+        PAUSED.save(storage, &Default::default()).unwrap();
+        LIQUIDITY_MANAGER
+            .save(storage, &Addr::unchecked("contract"))
+            .unwrap();
+        SIGNER_SET
+            .save(storage, &SignerSet::new(signers, 2, 0).unwrap())
+            .unwrap();
+        EXECUTE_POLICY
+            .save(
+                storage,
+                &ExecutePolicy {
+                    kinds: vec![MsgKind::Bank],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+
+    fn mock_signatures(signatures: &[&str], signers: &[usize]) -> Vec<SignerSignature> {
+        signers
+            .iter()
+            .map(|&i| SignerSignature {
+                public_key: HexBinary::from_hex(PUBLIC_KEYS[i]).unwrap(),
+                signature: HexBinary::from_hex(signatures[i]).unwrap(),
+            })
+            .collect()
+    }
+
+    fn mock_send_msgs(to: &Addr) -> Vec<CosmosMsg> {
+        vec![BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(100000, "uosmo"),
+        }
+        .into()]
+    }
+
+    #[test]
+    fn test_execute_successfully() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        let result = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 2]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "execute"),
+                attr("executor", owner.clone()),
+                attr("req_op_id", "0")
+            ]
+        );
+        assert_eq!(
+            result.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: owner.to_string(),
+                amount: coins(100000, "uosmo"),
+            })]
+        );
+        assert!(EXECUTED_REQUESTS.load(&deps.storage, 0).unwrap());
+    }
+
+    #[test]
+    fn test_execute_threshold() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        let not_enough = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[1]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            not_enough,
+            ContractError::ThresholdNotMet {
+                weight: 1,
+                threshold: 2
+            }
+        ));
+
+        let duplicated = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[1, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(duplicated, ContractError::DuplicateSigner { .. }));
+
+        let mut unknown = mock_signatures(&SIGNATURES, &[0, 1]);
+        unknown[1].public_key = HexBinary::from(vec![1u8, 2u8]);
+        let unknown = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            unknown,
+        )
+        .unwrap_err();
+        assert!(matches!(unknown, ContractError::UnknownSigner { .. }));
+
+        // signature of other signer
+        let mut forged = mock_signatures(&SIGNATURES, &[0, 1]);
+        forged[1].signature = forged[0].signature.clone();
+        let forged = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            forged,
+        )
+        .unwrap_err();
+        assert!(matches!(forged, ContractError::InvalidPubKey {}));
+    }
+
+    #[test]
+    fn test_execute_replay() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap();
+
+        let replayed = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[1, 2]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            replayed,
+            ContractError::AlreadyExecuted { req_op_id: 0 }
+        ));
+    }
+
+    #[test]
+    fn test_execute_other_req_op_id() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        // signature is bound to req_op_id 0
+        let result = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            1,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(result, ContractError::InvalidPubKey {}));
+        assert!(!EXECUTED_REQUESTS.has(&deps.storage, 1));
+    }
+
+    #[test]
+    fn test_execute_expiration() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+        let expires_at = Some(Expiration::AtHeight(12346));
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        // expiration is part of the signed payload
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            Some(Expiration::AtHeight(12347)),
+            mock_signatures(&EXPIRING_SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(result, ContractError::InvalidPubKey {}));
+
+        env.block.height = 12346;
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            expires_at,
+            mock_signatures(&EXPIRING_SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(result, ContractError::SignatureExpired {}));
+
+        env.block.height = 12345;
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            expires_at,
+            mock_signatures(&EXPIRING_SIGNATURES, &[0, 1]),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_execute_other_domain() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        env.block.chain_id = "osmosis-1".to_string();
+        let other_chain = execute(
+            deps.as_mut(),
+            env,
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(other_chain, ContractError::InvalidPubKey {}));
+
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked("other_gateway");
+        let other_contract = execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(other_contract, ContractError::InvalidPubKey {}));
+    }
+
+    #[test]
+    fn test_execute_rotated_signer() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        // rotate the second signer into the third one's key
+        let mut signer_set = SIGNER_SET.load(&deps.storage).unwrap();
+        signer_set.signers.remove(2);
+        signer_set.signers.push(Signer {
+            public_key: HexBinary::from(vec![2u8; 33]),
+            weight: 1,
+            scheme: SignatureScheme::Secp256k1,
+        });
+        signer_set
+            .rotate(
+                &HexBinary::from_hex(PUBLIC_KEYS[1]).unwrap(),
+                &HexBinary::from_hex(PUBLIC_KEYS[2]).unwrap(),
+                SignatureScheme::Secp256k1,
+                env.block.time.seconds() + 100,
+                env.block.time.seconds(),
+            )
+            .unwrap();
+        SIGNER_SET.save(&mut deps.storage, &signer_set).unwrap();
+
+        // old and new key share one slot
+        let same_slot = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[1, 2]),
+        )
+        .unwrap_err();
+        assert!(matches!(same_slot, ContractError::DuplicateSigner { .. }));
+
+        // old key is accepted within the grace window
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[0, 1]),
+        )
+        .unwrap();
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let expired = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            mock_signatures(&SIGNATURES, &[1, 0]),
+        )
+        .unwrap_err();
+        assert!(matches!(expired, ContractError::UnknownSigner { .. }));
+    }
+
+    // ed25519 key from seed [7; 32] and signature over the same payload as SIGNATURES
+    const ED25519_PUBLIC_KEY: &str =
+        "ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c";
+    const ED25519_SIGNATURE: &str = "3692ea693894fcba86d73367bc55ab42b39c2a072b2f30ed1477ffc1bd0b2d211890fc77f936303936a16a5f1227ba490f21e7ed6fa78c85ecd9f4806a461104";
+
+    // eth address of the first secp256k1 key and its personal_sign signature
+    const EIP191_ADDRESS: &str = "ae7d1f30e324d4e348ef04d9a9e867f863f23067";
+    const EIP191_SIGNATURE: &str = "8a66cbcba4464dc484a0999548b539cfab198532063e2ef31522ce77f52cd24b08a1888173571d3e0624653d19e625f39c2ecb97fdb971bc7a2dd3db878feb331b";
+
+    #[test]
+    fn test_execute_signature_schemes() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        let signers = vec![
+            Signer {
+                public_key: HexBinary::from_hex(PUBLIC_KEYS[1]).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            },
+            Signer {
+                public_key: HexBinary::from_hex(ED25519_PUBLIC_KEY).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Ed25519,
+            },
+            Signer {
+                public_key: HexBinary::from_hex(EIP191_ADDRESS).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Eip191,
+            },
+        ];
+        SIGNER_SET
+            .save(&mut deps.storage, &SignerSet::new(signers, 3, 0).unwrap())
+            .unwrap();
+        PAUSED.save(&mut deps.storage, &Default::default()).unwrap();
+        LIQUIDITY_MANAGER
+            .save(&mut deps.storage, &Addr::unchecked("contract"))
+            .unwrap();
+        EXECUTE_POLICY
+            .save(
+                &mut deps.storage,
+                &ExecutePolicy {
+                    kinds: vec![MsgKind::Bank],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let signature = |public_key: &str, signature: &str| SignerSignature {
+            public_key: HexBinary::from_hex(public_key).unwrap(),
+            signature: HexBinary::from_hex(signature).unwrap(),
+        };
+        let secp256k1 = signature(PUBLIC_KEYS[1], SIGNATURES[1]);
+        let ed25519 = signature(ED25519_PUBLIC_KEY, ED25519_SIGNATURE);
+        let eip191 = signature(EIP191_ADDRESS, EIP191_SIGNATURE);
+
+        // a secp256k1 signature does not pass as an eip191 one
+        let wrong_scheme = signature(EIP191_ADDRESS, &format!("{}1b", SIGNATURES[0]));
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            vec![secp256k1.clone(), ed25519.clone(), wrong_scheme],
+        )
+        .unwrap_err();
+        assert!(matches!(result, ContractError::InvalidPubKey {}));
+
+        // a tampered eip191 signature recovers another address
+        let mut tampered = eip191.clone();
+        let mut tampered_signature = tampered.signature.to_vec();
+        tampered_signature[0] ^= 1;
+        tampered.signature = HexBinary::from(tampered_signature);
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            vec![secp256k1.clone(), ed25519.clone(), tampered],
+        )
+        .unwrap_err();
+        assert!(matches!(result, ContractError::InvalidPubKey {}));
+
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_send_msgs(&owner),
+            0,
+            None,
+            vec![secp256k1.clone(), eip191.clone()],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            result,
+            ContractError::ThresholdNotMet {
+                weight: 2,
+                threshold: 3
+            }
+        ));
+
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            mock_send_msgs(&owner),
+            0,
+            None,
+            vec![secp256k1, ed25519, eip191],
+        )
+        .unwrap();
     }
 }
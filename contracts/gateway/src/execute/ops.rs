@@ -0,0 +1,187 @@
+use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response};
+use mitosis_interface::gateway::{OpArgType, OpSpec};
+
+use crate::{
+    errors::ContractError,
+    state::{assert_owned, ops::OP_SPECS, PAUSED},
+};
+
+pub fn register_op(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    op_id: u64,
+    name: String,
+    args: Vec<OpArgType>,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    if name.is_empty() {
+        return Err(ContractError::InvalidArgument {
+            msg: "op name must not be empty".to_string(),
+        });
+    }
+
+    OP_SPECS.save(
+        deps.storage,
+        op_id,
+        &OpSpec {
+            op_id,
+            name: name.clone(),
+            args,
+            enabled,
+        },
+    )?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "register_op"),
+        attr("executor", info.sender),
+        attr("op_id", op_id.to_string()),
+        attr("name", name),
+        attr("enabled", enabled.to_string()),
+    ]);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, Storage,
+    };
+
+    use crate::state::{PauseInfo, OWNER};
+
+    use super::*;
+
+    const ADDR1: &str = "ADDR1";
+    const ADDR2: &str = "ADDR2";
+
+    fn release(storage: &mut dyn Storage) {
+        PAUSED.save(storage, &Default::default()).unwrap()
+    }
+
+    fn stop(storage: &mut dyn Storage, now: u64) {
+        PAUSED
+            .save(
+                storage,
+                &PauseInfo {
+                    paused: true,
+                    expires_at: Some(now + 1000),
+                },
+            )
+            .unwrap()
+    }
+
+    fn mock_owner(storage: &mut dyn Storage, owner: Addr) {
+        OWNER.save(storage, &owner).unwrap();
+    }
+
+    #[test]
+    fn test_register_op_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        mock_owner(deps.as_mut().storage, owner.clone());
+        release(deps.as_mut().storage);
+
+        let unauthorized = register_op(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            1,
+            "transfer".to_string(),
+            vec![],
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let info = mock_info(owner.as_str(), &[]);
+        let empty_name = register_op(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            1,
+            "".to_string(),
+            vec![],
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(empty_name, ContractError::InvalidArgument { .. }));
+
+        stop(deps.as_mut().storage, env.block.time.seconds());
+        let paused = register_op(
+            deps.as_mut(),
+            env,
+            info,
+            1,
+            "transfer".to_string(),
+            vec![],
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(paused, ContractError::PausedError {}));
+    }
+
+    #[test]
+    fn test_register_op() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+        mock_owner(deps.as_mut().storage, owner.clone());
+        release(deps.as_mut().storage);
+
+        let result = register_op(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            1,
+            "transfer".to_string(),
+            vec![OpArgType::Address, OpArgType::Uint],
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "register_op"),
+                attr("executor", owner),
+                attr("op_id", "1"),
+                attr("name", "transfer"),
+                attr("enabled", "true"),
+            ]
+        );
+
+        // re-registering replaces the spec
+        register_op(
+            deps.as_mut(),
+            env,
+            info,
+            1,
+            "transfer".to_string(),
+            vec![OpArgType::Address],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            OP_SPECS.load(&deps.storage, 1).unwrap(),
+            OpSpec {
+                op_id: 1,
+                name: "transfer".to_string(),
+                args: vec![OpArgType::Address],
+                enabled: false,
+            }
+        );
+    }
+}
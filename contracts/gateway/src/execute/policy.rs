@@ -0,0 +1,132 @@
+use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response};
+use mitosis_interface::gateway::ExecutePolicy;
+
+use crate::{
+    errors::ContractError,
+    state::{
+        assert_owned,
+        policy::{validate_policy, EXECUTE_POLICY},
+        PAUSED,
+    },
+};
+
+// set_execute_policy replaces the allow-list checked by Execute before dispatch.
+pub fn set_execute_policy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    policy: ExecutePolicy,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    validate_policy(deps.api, &policy)?;
+    EXECUTE_POLICY.save(deps.storage, &policy)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "set_execute_policy"),
+        attr("executor", info.sender),
+    ]);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, Storage,
+    };
+    use mitosis_interface::gateway::MsgKind;
+
+    use crate::state::{PauseInfo, OWNER};
+
+    use super::*;
+
+    const ADDR1: &str = "ADDR1";
+    const ADDR2: &str = "ADDR2";
+
+    fn release(storage: &mut dyn Storage) {
+        PAUSED.save(storage, &Default::default()).unwrap()
+    }
+
+    fn stop(storage: &mut dyn Storage, now: u64) {
+        PAUSED
+            .save(
+                storage,
+                &PauseInfo {
+                    paused: true,
+                    expires_at: Some(now + 1000),
+                },
+            )
+            .unwrap()
+    }
+
+    fn mock_policy() -> ExecutePolicy {
+        ExecutePolicy {
+            kinds: vec![MsgKind::Bank],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_set_execute_policy_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        release(deps.as_mut().storage);
+
+        let unauthorized = set_execute_policy(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            mock_policy(),
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let info = mock_info(owner.as_str(), &[]);
+        let invalid = set_execute_policy(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecutePolicy {
+                stargate_type_urls: vec!["".to_string()],
+                ..mock_policy()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(invalid, ContractError::InvalidArgument { .. }));
+
+        stop(deps.as_mut().storage, env.block.time.seconds());
+        let paused = set_execute_policy(deps.as_mut(), env, info, mock_policy()).unwrap_err();
+        assert!(matches!(paused, ContractError::PausedError {}));
+    }
+
+    #[test]
+    fn test_set_execute_policy() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        release(deps.as_mut().storage);
+
+        let info = mock_info(owner.as_str(), &[]);
+        let result = set_execute_policy(deps.as_mut(), env, info, mock_policy()).unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "set_execute_policy"),
+                attr("executor", owner),
+            ]
+        );
+        assert_eq!(EXECUTE_POLICY.load(&deps.storage).unwrap(), mock_policy());
+    }
+}
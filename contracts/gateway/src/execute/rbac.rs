@@ -1,17 +1,17 @@
-use cosmwasm_std::{attr, Addr, DepsMut, Env, HexBinary, MessageInfo, Response};
+use cosmwasm_std::{attr, Addr, DepsMut, Env, MessageInfo, Response};
 
 use crate::{
     errors::ContractError,
-    state::{assert_owned, OWNER, PAUSED, PUBLIC_KEY},
-    verify::pub_to_addr,
+    state::{assert_owned, rbac, OWNER, PAUSED},
 };
 
+// ChangeOwner only records new_owner as pending, it takes over with AcceptOwner.
 pub fn change_owner(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     new_owner: Addr,
-    new_pubkey: HexBinary,
+    expires_at: Option<u64>,
 ) -> Result<Response, ContractError> {
     PAUSED
         .load(deps.storage)?
@@ -20,20 +20,111 @@ pub fn change_owner(
 
     assert_owned(deps.storage, info.sender.clone())?;
 
-    let public_key_addr = pub_to_addr(new_pubkey.clone().into(), "osmo")?;
-
-    if public_key_addr != new_owner {
-        return Err(ContractError::InvalidPubKey {});
+    if matches!(expires_at, Some(expires_at) if expires_at <= env.block.time.seconds()) {
+        return Err(ContractError::InvalidArgument {
+            msg: "expires_at must be in the future".to_string(),
+        });
     }
 
-    OWNER.save(deps.storage, &new_owner)?;
-    PUBLIC_KEY.save(deps.storage, &new_pubkey)?;
+    rbac::propose_owner(deps.storage, new_owner.clone(), expires_at)?;
 
     let response = Response::new().add_attributes(vec![
         attr("action", "change_owner"),
         attr("executor", info.sender),
         attr("new_owner", new_owner),
-        attr("new_public_key", new_pubkey.to_hex()),
+        attr(
+            "expires_at",
+            expires_at.map_or("never".to_string(), |e| e.to_string()),
+        ),
+    ]);
+
+    Ok(response)
+}
+
+pub fn accept_owner(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    let previous_owner = OWNER.load(deps.storage)?;
+    rbac::accept_owner(deps.storage, info.sender.clone(), env.block.time.seconds())?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "accept_owner"),
+        attr("executor", info.sender),
+        attr("previous_owner", previous_owner),
+    ]);
+
+    Ok(response)
+}
+
+pub fn cancel_owner(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    let pending_owner = rbac::cancel_owner(deps.storage)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "cancel_owner"),
+        attr("executor", info.sender),
+        attr("pending_owner", pending_owner),
+    ]);
+
+    Ok(response)
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: String,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    rbac::grant_role(deps.storage, role.clone(), addr.clone())?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "grant_role"),
+        attr("executor", info.sender),
+        attr("role", role),
+        attr("addr", addr),
+    ]);
+
+    Ok(response)
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: String,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    rbac::revoke_role(deps.storage, role.clone(), addr.clone())?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "revoke_role"),
+        attr("executor", info.sender),
+        attr("role", role),
+        attr("addr", addr),
     ]);
 
     Ok(response)
@@ -88,12 +179,10 @@ mod test {
 
         let addr = Addr::unchecked(ADDR1);
         let info = mock_info(addr.as_str(), &[]);
-        let dummy_pubkey = HexBinary::from_hex("12").unwrap();
 
         stop(deps.as_mut().storage, env.block.time.seconds());
 
-        let change_owner_err =
-            change_owner(deps.as_mut(), env, info, addr, dummy_pubkey).unwrap_err();
+        let change_owner_err = change_owner(deps.as_mut(), env, info, addr, None).unwrap_err();
         assert!(matches!(change_owner_err, ContractError::PausedError {}));
     }
 
@@ -105,13 +194,11 @@ mod test {
         let owner = Addr::unchecked(ADDR1);
         let abuser = Addr::unchecked(ADDR2);
         let info = mock_info(abuser.as_str(), &[]);
-        let dummy_pubkey = HexBinary::from_hex("12").unwrap();
 
         resume(deps.as_mut().storage, env.block.time.seconds());
         mock_owner(deps.as_mut().storage, owner);
 
-        let unauthorized_err =
-            change_owner(deps.as_mut(), env, info, abuser, dummy_pubkey).unwrap_err();
+        let unauthorized_err = change_owner(deps.as_mut(), env, info, abuser, None).unwrap_err();
         assert!(matches!(unauthorized_err, ContractError::Unauthorized {}))
     }
 
@@ -121,53 +208,91 @@ mod test {
         let env = mock_env();
 
         let owner = Addr::unchecked(ADDR1);
-        let new_owner = Addr::unchecked("osmo134s3q9c56t93v96aksveuk9lp8ngljlnlupphd");
+        let new_owner = Addr::unchecked(ADDR2);
         let info = mock_info(owner.as_str(), &[]);
-        let public_key = HexBinary::from(vec![
-            2, 191, 219, 148, 192, 213, 90, 105, 81, 110, 121, 164, 102, 210, 194, 26, 140, 10, 19,
-            2, 139, 176, 7, 14, 221, 13, 10, 7, 195, 19, 186, 83, 238,
-        ]);
 
         resume(deps.as_mut().storage, env.block.time.seconds());
         mock_owner(deps.as_mut().storage, owner.clone());
 
-        let changed_owner = change_owner(
-            deps.as_mut(),
-            env,
-            info,
-            new_owner.clone(),
-            public_key.clone(),
-        )
-        .unwrap();
+        let changed_owner =
+            change_owner(deps.as_mut(), env.clone(), info, new_owner.clone(), None).unwrap();
         assert_eq!(
             changed_owner.attributes,
             vec![
                 attr("action", "change_owner"),
                 attr("executor", owner.as_str()),
                 attr("new_owner", new_owner.as_str()),
-                attr("new_public_key", public_key.to_hex()),
+                attr("expires_at", "never"),
+            ]
+        );
+        assert_eq!(OWNER.load(&deps.storage).unwrap(), owner);
+
+        let accepted =
+            accept_owner(deps.as_mut(), env, mock_info(new_owner.as_str(), &[])).unwrap();
+        assert_eq!(
+            accepted.attributes,
+            vec![
+                attr("action", "accept_owner"),
+                attr("executor", new_owner.as_str()),
+                attr("previous_owner", owner.as_str()),
             ]
         );
+        assert_eq!(OWNER.load(&deps.storage).unwrap(), new_owner);
     }
 
     #[test]
-    fn test_fail_pubkey_change_owner() {
+    fn test_cancel_owner() {
         let mut deps = mock_dependencies();
         let env = mock_env();
+        let now = env.block.time.seconds();
 
         let owner = Addr::unchecked(ADDR1);
         let new_owner = Addr::unchecked(ADDR2);
         let info = mock_info(owner.as_str(), &[]);
-        let public_key = HexBinary::from(vec![
-            2, 191, 219, 148, 192, 213, 90, 105, 81, 110, 121, 164, 102, 210, 194, 26, 140, 10, 19,
-            2, 139, 176, 7, 14, 221, 13, 10, 7, 195, 19, 186, 83, 238,
-        ]);
 
-        resume(deps.as_mut().storage, env.block.time.seconds());
-        mock_owner(deps.as_mut().storage, owner);
+        resume(deps.as_mut().storage, now);
+        mock_owner(deps.as_mut().storage, owner.clone());
 
-        let changed_owner =
-            change_owner(deps.as_mut(), env, info, new_owner, public_key).unwrap_err();
-        assert!(matches!(changed_owner, ContractError::InvalidPubKey {}))
+        let past_err = change_owner(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            new_owner.clone(),
+            Some(now),
+        )
+        .unwrap_err();
+        assert!(matches!(past_err, ContractError::InvalidArgument { .. }));
+
+        change_owner(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            new_owner.clone(),
+            Some(now + 100),
+        )
+        .unwrap();
+
+        let abuser_err = cancel_owner(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(new_owner.as_str(), &[]),
+        )
+        .unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+
+        let cancelled = cancel_owner(deps.as_mut(), env.clone(), info).unwrap();
+        assert_eq!(
+            cancelled.attributes,
+            vec![
+                attr("action", "cancel_owner"),
+                attr("executor", owner.as_str()),
+                attr("pending_owner", new_owner.as_str()),
+            ]
+        );
+
+        let accept_err =
+            accept_owner(deps.as_mut(), env, mock_info(new_owner.as_str(), &[])).unwrap_err();
+        assert!(matches!(accept_err, ContractError::NoPendingOwner {}));
+        assert_eq!(OWNER.load(&deps.storage).unwrap(), owner);
     }
 }
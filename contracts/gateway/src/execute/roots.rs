@@ -0,0 +1,544 @@
+use cosmwasm_std::{attr, DepsMut, Env, HexBinary, MessageInfo, Response};
+use cw_utils::Expiration;
+use mitosis_interface::gateway::{commit_root_sign_bytes, MerkleLeaf, SignerSignature};
+
+use crate::{
+    errors::ContractError,
+    execute::operation::admit_msgs,
+    state::{
+        rbac::assert_pauser,
+        requests::consume_request,
+        roots::{load_root, RootInfo, ROOTS},
+        signers::SIGNER_SET,
+        PAUSED,
+    },
+    verify::{merkle_leaf_hash, verify_merkle_proof, verify_signatures},
+};
+
+// commit_root lets the signer set authorize many executes with one signature
+// each. The root has to expire, its leaves can be executed until then.
+pub fn commit_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    root: HexBinary,
+    req_op_id: u64,
+    expires_at: Expiration,
+    signatures: Vec<SignerSignature>,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    if root.len() != 32 {
+        return Err(ContractError::InvalidArgument {
+            msg: "root must be a sha256 hash".to_string(),
+        });
+    }
+
+    if matches!(expires_at, Expiration::Never {}) {
+        return Err(ContractError::InvalidArgument {
+            msg: "root must expire".to_string(),
+        });
+    }
+
+    if expires_at.is_expired(&env.block) {
+        return Err(ContractError::SignatureExpired {});
+    }
+
+    let signer_set = SIGNER_SET
+        .load(deps.storage)
+        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
+
+    let sign_bytes = commit_root_sign_bytes(
+        env.block.chain_id.clone(),
+        env.contract.address,
+        req_op_id,
+        expires_at,
+        root.clone(),
+    )?;
+    verify_signatures(deps.api, &env.block, &signer_set, &sign_bytes, &signatures)?;
+
+    consume_request(deps.storage, req_op_id)?;
+
+    ROOTS.save(
+        deps.storage,
+        root.as_slice(),
+        &RootInfo {
+            req_op_id,
+            expires_at,
+            signer_set_version: signer_set.version,
+        },
+    )?;
+
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "commit_root"),
+        attr("executor", info.sender),
+        attr("req_op_id", req_op_id.to_string()),
+        attr("root", root.to_hex()),
+        attr("expires_at", expires_at.to_string()),
+    ]);
+
+    Ok(resp)
+}
+
+// execute_leaf is permissionless, the leaf is authorized by the committed root.
+// Its req_op_id is consumed like the one of a signed Execute. A stale root is
+// removed on touch, which has to succeed for the removal to stick.
+pub fn execute_leaf(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    root: HexBinary,
+    leaf: MerkleLeaf,
+    proof: Vec<HexBinary>,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    let committed = load_root(deps.storage, &root)?;
+    let signer_set = SIGNER_SET
+        .load(deps.storage)
+        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
+
+    if let Some(reason) = committed.stale_reason(&env.block, signer_set.version) {
+        ROOTS.remove(deps.storage, root.as_slice());
+
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "remove_root"),
+            attr("executor", info.sender),
+            attr("root", root.to_hex()),
+            attr("reason", reason),
+        ]));
+    }
+
+    verify_merkle_proof(root.as_slice(), merkle_leaf_hash(&leaf)?, &proof)?;
+
+    if let Some(resp) = admit_msgs(deps.storage, &env, &info, &leaf.msgs, leaf.req_op_id)? {
+        return Ok(resp);
+    }
+
+    let resp = Response::new().add_messages(leaf.msgs).add_attributes(vec![
+        attr("action", "execute_leaf"),
+        attr("executor", info.sender),
+        attr("root", root.to_hex()),
+        attr("req_op_id", leaf.req_op_id.to_string()),
+    ]);
+
+    Ok(resp)
+}
+
+// revoke_root lets the owner or a guardian drop a root whose leaves must not
+// run, e.g. after a signer key leaked. It works while paused.
+pub fn revoke_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    root: HexBinary,
+) -> Result<Response, ContractError> {
+    let role = assert_pauser(deps.storage, info.sender.clone())?;
+
+    load_root(deps.storage, &root)?;
+    ROOTS.remove(deps.storage, root.as_slice());
+
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "revoke_root"),
+        attr("executor", info.sender),
+        attr("role", role),
+        attr("root", root.to_hex()),
+    ]);
+
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coins,
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, BankMsg, CosmosMsg, Storage, SubMsg,
+    };
+    use mitosis_interface::gateway::{ExecutePolicy, MsgKind, SignatureScheme, Signer};
+
+    use crate::{
+        state::{
+            policy::EXECUTE_POLICY,
+            rbac::{grant_role, GUARDIAN_ROLE},
+            requests::is_executed,
+            signers::SignerSet,
+            LIQUIDITY_MANAGER, OWNER,
+        },
+        verify::merkle_node_hash,
+    };
+
+    use super::*;
+
+    const PUBLIC_KEYS: [&str; 2] = [
+        "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1",
+        "02ec18c82501c5088119251679b538e9cf8eae502956cc862c7778aa148365e886",
+    ];
+
+    // root of (mock_leaf(0), mock_leaf(1)), mock_leaf(2)
+    const ROOT: &str = "161932e575e52a7ff381fb713d690b16aad9f482138edcfd762691b55082d5d8";
+
+    // signatures of ROOT with req_op_id 1, expiring at height 20000
+    const SIGNATURES: [&str; 2] = [
+        "7d6a797762115a59eeaea593d42940543ef9794cf4d79be43be6455f801b697840e119cf14e92ee20eb6fe1d0bee83104243b39dd3a97b6f99c3ddd060e6891d",
+        "90960dfbaa7e5766a280276313cf80ea37020e1baa80e93d3d6580043846fa294c88613c5890fa0da5dfcab7addd3b08e2bdfa94f8fcd35464bf8bd4ac0fa6dd",
+    ];
+    const EXPIRES_AT: Expiration = Expiration::AtHeight(20000);
+
+    // 2-of-2 signer set
+    fn mock_signer(storage: &mut dyn Storage) {
+        let signers = PUBLIC_KEYS
+            .iter()
+            .map(|key| Signer {
+                public_key: HexBinary::from_hex(key).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            })
+            .collect();
+
+        PAUSED.save(storage, &Default::default()).unwrap();
+        LIQUIDITY_MANAGER
+            .save(storage, &Addr::unchecked("contract"))
+            .unwrap();
+        SIGNER_SET
+            .save(storage, &SignerSet::new(signers, 2, 0).unwrap())
+            .unwrap();
+        EXECUTE_POLICY
+            .save(
+                storage,
+                &ExecutePolicy {
+                    kinds: vec![MsgKind::Bank],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+
+    fn mock_signatures() -> Vec<SignerSignature> {
+        PUBLIC_KEYS
+            .iter()
+            .zip(SIGNATURES)
+            .map(|(public_key, signature)| SignerSignature {
+                public_key: HexBinary::from_hex(public_key).unwrap(),
+                signature: HexBinary::from_hex(signature).unwrap(),
+            })
+            .collect()
+    }
+
+    fn mock_leaf(i: u64) -> MerkleLeaf {
+        let (to, amount) = [("ADDR1", 100), ("ADDR2", 200), ("ADDR1", 300)][i as usize];
+
+        MerkleLeaf {
+            req_op_id: 10 + i,
+            msgs: vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: to.to_string(),
+                amount: coins(amount, "uosmo"),
+            })],
+        }
+    }
+
+    fn mock_proof(i: u64) -> Vec<HexBinary> {
+        let leaves: Vec<_> = (0..3)
+            .map(|i| merkle_leaf_hash(&mock_leaf(i)).unwrap())
+            .collect();
+        let node = merkle_node_hash(&leaves[0], &leaves[1]).unwrap();
+
+        let proof = match i {
+            0 => vec![leaves[1], leaves[2]],
+            1 => vec![leaves[0], leaves[2]],
+            _ => vec![node],
+        };
+        proof.into_iter().map(HexBinary::from).collect()
+    }
+
+    fn root() -> HexBinary {
+        HexBinary::from_hex(ROOT).unwrap()
+    }
+
+    fn mock_commit(deps: DepsMut) -> Result<Response, ContractError> {
+        commit_root(
+            deps,
+            mock_env(),
+            mock_info("relayer", &[]),
+            root(),
+            1,
+            EXPIRES_AT,
+            mock_signatures(),
+        )
+    }
+
+    #[test]
+    fn test_commit_root() {
+        let mut deps = mock_dependencies();
+
+        mock_signer(deps.as_mut().storage);
+
+        let result = mock_commit(deps.as_mut()).unwrap();
+        assert_eq!(result.attributes[3], attr("root", ROOT));
+        assert_eq!(
+            ROOTS.load(&deps.storage, root().as_slice()).unwrap(),
+            RootInfo {
+                req_op_id: 1,
+                expires_at: EXPIRES_AT,
+                signer_set_version: 0,
+            }
+        );
+
+        let replay = mock_commit(deps.as_mut()).unwrap_err();
+        assert!(matches!(
+            replay,
+            ContractError::AlreadyExecuted { req_op_id: 1 }
+        ));
+    }
+
+    #[test]
+    fn test_commit_root_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("relayer", &[]);
+
+        mock_signer(deps.as_mut().storage);
+
+        let never = commit_root(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            root(),
+            1,
+            Expiration::Never {},
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(never, ContractError::InvalidArgument { .. }));
+
+        // the signatures cover the expiry
+        let extended = commit_root(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            root(),
+            1,
+            Expiration::AtHeight(30000),
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(extended, ContractError::InvalidPubKey {}));
+
+        let mut late = env;
+        late.block.height = 20000;
+        let expired = commit_root(
+            deps.as_mut(),
+            late,
+            info,
+            root(),
+            1,
+            EXPIRES_AT,
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(expired, ContractError::SignatureExpired {}));
+    }
+
+    #[test]
+    fn test_execute_leaf() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("anyone", &[]);
+
+        mock_signer(deps.as_mut().storage);
+
+        let not_committed = execute_leaf(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            root(),
+            mock_leaf(0),
+            mock_proof(0),
+        )
+        .unwrap_err();
+        assert!(matches!(not_committed, ContractError::RootNotFound { .. }));
+
+        mock_commit(deps.as_mut()).unwrap();
+
+        for i in 0..3 {
+            let result = execute_leaf(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                root(),
+                mock_leaf(i),
+                mock_proof(i),
+            )
+            .unwrap();
+            assert_eq!(
+                result.messages,
+                vec![SubMsg::new(mock_leaf(i).msgs[0].clone())]
+            );
+            assert!(is_executed(&deps.storage, 10 + i).unwrap());
+        }
+
+        let replay = execute_leaf(
+            deps.as_mut(),
+            env,
+            info,
+            root(),
+            mock_leaf(0),
+            mock_proof(0),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            replay,
+            ContractError::AlreadyExecuted { req_op_id: 10 }
+        ));
+    }
+
+    #[test]
+    fn test_execute_leaf_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("anyone", &[]);
+
+        mock_signer(deps.as_mut().storage);
+        mock_commit(deps.as_mut()).unwrap();
+
+        // a leaf not in the tree
+        let mut forged = mock_leaf(0);
+        forged.msgs = mock_leaf(1).msgs;
+        let invalid = execute_leaf(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            root(),
+            forged,
+            mock_proof(0),
+        )
+        .unwrap_err();
+        assert!(matches!(invalid, ContractError::InvalidProof {}));
+
+        // siblings are sha256 hashes
+        let short = execute_leaf(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            root(),
+            mock_leaf(0),
+            vec![HexBinary::from(vec![0u8; 31])],
+        )
+        .unwrap_err();
+        assert!(matches!(short, ContractError::InvalidProof {}));
+
+        // an expired root is removed on touch
+        let mut late = env;
+        late.block.height = 20000;
+        let expired = execute_leaf(
+            deps.as_mut(),
+            late.clone(),
+            info.clone(),
+            root(),
+            mock_leaf(0),
+            mock_proof(0),
+        )
+        .unwrap();
+        assert!(expired.messages.is_empty());
+        assert_eq!(expired.attributes[0], attr("action", "remove_root"));
+        assert_eq!(expired.attributes[3], attr("reason", "expired"));
+        assert!(!is_executed(&deps.storage, 10).unwrap());
+
+        let removed = execute_leaf(
+            deps.as_mut(),
+            late,
+            info,
+            root(),
+            mock_leaf(0),
+            mock_proof(0),
+        )
+        .unwrap_err();
+        assert!(matches!(removed, ContractError::RootNotFound { .. }));
+    }
+
+    #[test]
+    fn test_execute_leaf_outdated_signer_set() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("anyone", &[]);
+
+        mock_signer(deps.as_mut().storage);
+        mock_commit(deps.as_mut()).unwrap();
+
+        // a replaced or rotated signer set bumps the version
+        let mut signer_set = SIGNER_SET.load(&deps.storage).unwrap();
+        signer_set.version += 1;
+        SIGNER_SET.save(deps.as_mut().storage, &signer_set).unwrap();
+
+        let outdated = execute_leaf(
+            deps.as_mut(),
+            env,
+            info,
+            root(),
+            mock_leaf(0),
+            mock_proof(0),
+        )
+        .unwrap();
+        assert!(outdated.messages.is_empty());
+        assert_eq!(
+            outdated.attributes[3],
+            attr("reason", "outdated_signer_set")
+        );
+        assert!(ROOTS
+            .may_load(&deps.storage, root().as_slice())
+            .unwrap()
+            .is_none());
+        assert!(!is_executed(&deps.storage, 10).unwrap());
+    }
+
+    #[test]
+    fn test_revoke_root() {
+        let mut deps = mock_dependencies();
+
+        mock_signer(deps.as_mut().storage);
+        OWNER
+            .save(deps.as_mut().storage, &Addr::unchecked("owner"))
+            .unwrap();
+        grant_role(
+            deps.as_mut().storage,
+            GUARDIAN_ROLE.to_string(),
+            Addr::unchecked("guardian"),
+        )
+        .unwrap();
+        mock_commit(deps.as_mut()).unwrap();
+
+        let unauthorized =
+            revoke_root(deps.as_mut(), mock_info("relayer", &[]), root()).unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let result = revoke_root(deps.as_mut(), mock_info("guardian", &[]), root()).unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "revoke_root"),
+                attr("executor", "guardian"),
+                attr("role", "guardian"),
+                attr("root", ROOT),
+            ]
+        );
+
+        let revoked = execute_leaf(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            root(),
+            mock_leaf(0),
+            mock_proof(0),
+        )
+        .unwrap_err();
+        assert!(matches!(revoked, ContractError::RootNotFound { .. }));
+
+        let not_found = revoke_root(deps.as_mut(), mock_info("owner", &[]), root()).unwrap_err();
+        assert!(matches!(not_found, ContractError::RootNotFound { .. }));
+    }
+}
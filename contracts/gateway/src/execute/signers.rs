@@ -0,0 +1,472 @@
+use cosmwasm_std::{attr, DepsMut, Env, HexBinary, MessageInfo, Response};
+use cw_utils::Expiration;
+use mitosis_interface::gateway::{
+    SignAction, SignPayload, SignatureScheme, Signer, SignerSignature,
+};
+
+use crate::{
+    errors::ContractError,
+    state::{
+        requests::consume_request,
+        signers::{SignerSet, SIGNER_SET},
+    },
+    verify::verify_signatures,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_signer_set(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    signers: Vec<Signer>,
+    threshold: u64,
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    signatures: Vec<SignerSignature>,
+) -> Result<Response, ContractError> {
+    if let Some(expiration) = expires_at {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::SignatureExpired {});
+        }
+    }
+
+    let current = SIGNER_SET
+        .load(deps.storage)
+        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
+
+    let sign_bytes = SignPayload::new(
+        env.block.chain_id.clone(),
+        env.contract.address,
+        req_op_id,
+        expires_at,
+        SignAction::UpdateSignerSet {
+            signers: signers.clone(),
+            threshold,
+            version: current.version,
+        },
+    )
+    .to_sign_bytes()?;
+    verify_signatures(deps.api, &env.block, &current, &sign_bytes, &signatures)?;
+
+    let next = SignerSet::new(signers, threshold, current.version + 1)?;
+
+    consume_request(deps.storage, req_op_id)?;
+    SIGNER_SET.save(deps.storage, &next)?;
+
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "update_signer_set"),
+        attr("executor", info.sender),
+        attr("req_op_id", req_op_id.to_string()),
+        attr("version", next.version.to_string()),
+        attr("threshold", next.threshold.to_string()),
+    ]);
+
+    Ok(resp)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn rotate_signer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    old_public_key: HexBinary,
+    new_public_key: HexBinary,
+    scheme: SignatureScheme,
+    grace_period: u64,
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    signatures: Vec<SignerSignature>,
+) -> Result<Response, ContractError> {
+    if let Some(expiration) = expires_at {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::SignatureExpired {});
+        }
+    }
+
+    let mut signer_set = SIGNER_SET
+        .load(deps.storage)
+        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
+
+    let sign_bytes = SignPayload::new(
+        env.block.chain_id.clone(),
+        env.contract.address.clone(),
+        req_op_id,
+        expires_at,
+        SignAction::RotateSigner {
+            old_public_key: old_public_key.clone(),
+            new_public_key: new_public_key.clone(),
+            scheme,
+            grace_period,
+            version: signer_set.version,
+        },
+    )
+    .to_sign_bytes()?;
+    verify_signatures(deps.api, &env.block, &signer_set, &sign_bytes, &signatures)?;
+
+    let now = env.block.time.seconds();
+    let grace_expires_at = now
+        .checked_add(grace_period)
+        .ok_or(ContractError::InvalidArgument {
+            msg: "grace_period overflow".to_string(),
+        })?;
+
+    signer_set.rotate(
+        &old_public_key,
+        &new_public_key,
+        scheme,
+        grace_expires_at,
+        now,
+    )?;
+
+    consume_request(deps.storage, req_op_id)?;
+    SIGNER_SET.save(deps.storage, &signer_set)?;
+
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "rotate_signer"),
+        attr("executor", info.sender),
+        attr("req_op_id", req_op_id.to_string()),
+        attr("old_public_key", old_public_key.to_hex()),
+        attr("new_public_key", new_public_key.to_hex()),
+        attr("grace_expires_at", grace_expires_at.to_string()),
+        attr("version", signer_set.version.to_string()),
+    ]);
+
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, Storage,
+    };
+    use mitosis_interface::gateway::{RetiringSigner, SignatureScheme};
+
+    use crate::state::{requests::EXECUTED_REQUESTS, OWNER};
+
+    use super::*;
+
+    const ADDR1: &str = "ADDR1";
+    const ADDR2: &str = "ADDR2";
+
+    const PUBLIC_KEYS: [&str; 3] = [
+        "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1",
+        "02ec18c82501c5088119251679b538e9cf8eae502956cc862c7778aa148365e886",
+        "0361b91a764b947cb1409508e8b5b6c30d659c5101cbedc20a5f0bb0ad8e63a4e3",
+    ];
+
+    // signatures of mock_next_signers with req_op_id 1 over signer set version 0
+    const SIGNATURES: [&str; 3] = [
+        "9cb597862538e57fc6cda902e8bf42b635d4dd5dd7a79cd328fbc28042ebce50051c8f1d412e3378bd6650e02c91ceab3765b250d3e3b64118d69aeecc24cde2",
+        "262352636ad7503af13670cb57419c0e764572d9f14a0eabaead0cb9ed8f18de331470645ccd37b3083a77d509394796592bd759b2a20191a2d80e957409818c",
+        "eb677176b4c4f1703ee3044bca58ea6241068380d9e3c1b41ce1b3ba99c06c84233e80951922f0479a9d812d9a229923ad839dc9864c0bf55fc52182762f9a23",
+    ];
+
+    fn mock_signer(storage: &mut dyn Storage, owner: &Addr) {
+        let signers = PUBLIC_KEYS
+            .iter()
+            .map(|key| Signer {
+                public_key: HexBinary::from_hex(key).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            })
+            .collect();
+
+        OWNER.save(storage, owner).unwrap();
+        SIGNER_SET
+            .save(storage, &SignerSet::new(signers, 2, 0).unwrap())
+            .unwrap();
+    }
+
+    fn mock_next_signers() -> Vec<Signer> {
+        vec![
+            Signer {
+                public_key: HexBinary::from_hex(PUBLIC_KEYS[0]).unwrap(),
+                weight: 2,
+                scheme: SignatureScheme::Secp256k1,
+            },
+            Signer {
+                public_key: HexBinary::from_hex(PUBLIC_KEYS[2]).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            },
+        ]
+    }
+
+    fn mock_signatures(signers: &[usize]) -> Vec<SignerSignature> {
+        signers
+            .iter()
+            .map(|&i| SignerSignature {
+                public_key: HexBinary::from_hex(PUBLIC_KEYS[i]).unwrap(),
+                signature: HexBinary::from_hex(SIGNATURES[i]).unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_update_signer_set() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        let result = update_signer_set(
+            deps.as_mut(),
+            env,
+            info,
+            mock_next_signers(),
+            2,
+            1,
+            None,
+            mock_signatures(&[0, 1]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "update_signer_set"),
+                attr("executor", owner),
+                attr("req_op_id", "1"),
+                attr("version", "1"),
+                attr("threshold", "2"),
+            ]
+        );
+        assert_eq!(
+            SIGNER_SET.load(&deps.storage).unwrap(),
+            SignerSet {
+                signers: mock_next_signers(),
+                retiring: vec![],
+                threshold: 2,
+                version: 1,
+            }
+        );
+        assert!(EXECUTED_REQUESTS.load(&deps.storage, 1).unwrap());
+    }
+
+    #[test]
+    fn test_update_signer_set_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(owner.as_str(), &[]);
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        // quorum of the current set is required
+        let not_enough = update_signer_set(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_next_signers(),
+            2,
+            1,
+            None,
+            mock_signatures(&[2]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            not_enough,
+            ContractError::ThresholdNotMet {
+                weight: 1,
+                threshold: 2
+            }
+        ));
+
+        // signed threshold differs
+        let tampered = update_signer_set(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_next_signers(),
+            1,
+            1,
+            None,
+            mock_signatures(&[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(tampered, ContractError::InvalidPubKey {}));
+
+        update_signer_set(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            mock_next_signers(),
+            2,
+            1,
+            None,
+            mock_signatures(&[0, 1]),
+        )
+        .unwrap();
+
+        // signatures are bound to the replaced version
+        let replayed = update_signer_set(
+            deps.as_mut(),
+            env,
+            info,
+            mock_next_signers(),
+            2,
+            1,
+            None,
+            mock_signatures(&[0, 2]),
+        )
+        .unwrap_err();
+        assert!(matches!(replayed, ContractError::InvalidPubKey {}));
+    }
+
+    // ed25519 key from seed [7; 32]
+    const ED25519_PUBLIC_KEY: &str =
+        "ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c";
+
+    // signatures of the rotation of the third key to ED25519_PUBLIC_KEY with
+    // req_op_id 2 and a grace period of 100 over signer set version 0
+    const ROTATE_SIGNATURES: [&str; 2] = [
+        "bfa8b1f7cef505be606314af0f29a38933434fd735d75540efce8c237ce9ca9223a47fa34579a6f4a0ae97eb240ed3880fea7da748addab6ec30895fd7d8486b",
+        "137aafe8923e654a67ba7d77d29679c1f99c18307a7eb2318c93ff6e081b47b04437755e5f5b8154b56ca70467b1e1f55fe74a2f45c875561f2338e5865607e3",
+    ];
+
+    fn mock_rotate_signatures(signers: &[usize]) -> Vec<SignerSignature> {
+        signers
+            .iter()
+            .map(|&i| SignerSignature {
+                public_key: HexBinary::from_hex(PUBLIC_KEYS[i]).unwrap(),
+                signature: HexBinary::from_hex(ROTATE_SIGNATURES[i]).unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rotate_signer() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let info = mock_info(ADDR2, &[]);
+        let old_public_key = HexBinary::from_hex(PUBLIC_KEYS[2]).unwrap();
+        let new_public_key = HexBinary::from_hex(ED25519_PUBLIC_KEY).unwrap();
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        let result = rotate_signer(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            old_public_key.clone(),
+            new_public_key.clone(),
+            SignatureScheme::Ed25519,
+            100,
+            2,
+            None,
+            mock_rotate_signatures(&[0, 1]),
+        )
+        .unwrap();
+
+        let expires_at = env.block.time.seconds() + 100;
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "rotate_signer"),
+                attr("executor", ADDR2),
+                attr("req_op_id", "2"),
+                attr("old_public_key", old_public_key.to_hex()),
+                attr("new_public_key", new_public_key.to_hex()),
+                attr("grace_expires_at", expires_at.to_string()),
+                attr("version", "1"),
+            ]
+        );
+
+        let signer_set = SIGNER_SET.load(&deps.storage).unwrap();
+        assert_eq!(
+            signer_set.signers[2],
+            Signer {
+                public_key: new_public_key.clone(),
+                weight: 1,
+                scheme: SignatureScheme::Ed25519,
+            }
+        );
+        assert_eq!(
+            signer_set.retiring,
+            vec![RetiringSigner {
+                public_key: old_public_key,
+                scheme: SignatureScheme::Secp256k1,
+                successor: new_public_key,
+                expires_at,
+            }]
+        );
+        assert!(EXECUTED_REQUESTS.load(&deps.storage, 2).unwrap());
+
+        // rotation bumps the version, so pending updates over the old set are void
+        let stale = update_signer_set(
+            deps.as_mut(),
+            env,
+            info,
+            mock_next_signers(),
+            2,
+            1,
+            None,
+            mock_signatures(&[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(stale, ContractError::InvalidPubKey {}));
+    }
+
+    #[test]
+    fn test_rotate_signer_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let old_public_key = HexBinary::from_hex(PUBLIC_KEYS[2]).unwrap();
+        let new_public_key = HexBinary::from_hex(ED25519_PUBLIC_KEY).unwrap();
+
+        mock_signer(deps.as_mut().storage, &owner);
+
+        // the owner alone can't rotate, a quorum of the current set is required
+        let not_enough = rotate_signer(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            old_public_key.clone(),
+            new_public_key.clone(),
+            SignatureScheme::Ed25519,
+            100,
+            2,
+            None,
+            mock_rotate_signatures(&[0]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            not_enough,
+            ContractError::ThresholdNotMet {
+                weight: 1,
+                threshold: 2
+            }
+        ));
+
+        // signed scheme differs
+        let tampered = rotate_signer(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            old_public_key,
+            new_public_key,
+            SignatureScheme::Secp256k1,
+            100,
+            2,
+            None,
+            mock_rotate_signatures(&[0, 1]),
+        )
+        .unwrap_err();
+        assert!(matches!(tampered, ContractError::InvalidPubKey {}));
+
+        assert_eq!(SIGNER_SET.load(&deps.storage).unwrap().version, 0);
+        assert!(EXECUTED_REQUESTS
+            .may_load(&deps.storage, 2)
+            .unwrap()
+            .is_none());
+    }
+}
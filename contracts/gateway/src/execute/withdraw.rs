@@ -0,0 +1,535 @@
+use cosmwasm_std::{
+    attr, to_binary, Addr, Api, Coin, DepsMut, Env, Event, MessageInfo, Reply, Response, StdResult,
+    Storage, SubMsg, WasmMsg,
+};
+use cw_utils::Expiration;
+use mitosis_interface::{
+    gateway::{SignAction, SignPayload, SignerSignature},
+    liquidity_manager,
+};
+
+use crate::{
+    errors::ContractError,
+    execute::{
+        consts::REPLY_WITHDRAW_SUBMESSAGE_SUCCESS, hooks::refund_transfers, limits::trip_breaker,
+        operation::coins_to_string,
+    },
+    state::{
+        context::{next_withdraw_leg, set_withdraw_info},
+        hooks::{take_hook_refund, HookRefund, HOOK_REFUND},
+        limits::{check_outflows, record_outflows},
+        refunds::{QueuedRefund, QUEUED_REFUNDS},
+        requests::consume_request,
+        signers::SIGNER_SET,
+        LIQUIDITY_MANAGER, PAUSED,
+    },
+    verify::verify_signatures,
+};
+
+// withdraw_submsgs withdraws amounts from the liquidity manager, one coin at a
+// time, to withdrawer or else to the gateway. Every leg replies, a leg above a
+// withdraw tier is queued by the liquidity manager instead of paid out.
+pub fn withdraw_submsgs(
+    liquidity_manager: &Addr,
+    withdrawer: Option<&Addr>,
+    amounts: &[Coin],
+) -> StdResult<Vec<SubMsg>> {
+    amounts
+        .iter()
+        .map(|amount| {
+            let msg = WasmMsg::Execute {
+                contract_addr: liquidity_manager.to_string(),
+                msg: to_binary(&liquidity_manager::ExecuteMsg::Withdraw {
+                    withdrawer: withdrawer.cloned(),
+                    amount: amount.clone(),
+                })?,
+                funds: vec![],
+            };
+
+            Ok(SubMsg::reply_on_success(
+                msg,
+                REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+            ))
+        })
+        .collect()
+}
+
+// queued_withdrawal returns the id of the withdrawal the liquidity manager
+// queued in a withdraw leg, if it did. Only events of the liquidity manager
+// count, the leg may have run other contracts on the way. The emitter is set by
+// the chain under a reserved key, "_contract_address" on wasmd and
+// "_contract_addr" on cw-multi-test, which contracts can't emit themselves.
+fn queued_withdrawal(events: &[Event], liquidity_manager: &Addr) -> Option<u64> {
+    let queued = events.iter().find(|event| {
+        event.ty == "wasm"
+            && event.attributes.iter().any(|a| {
+                (a.key == "_contract_address" || a.key == "_contract_addr")
+                    && a.value == liquidity_manager.as_str()
+            })
+            && event
+                .attributes
+                .iter()
+                .any(|a| a.key == "action" && a.value == "queue_withdraw")
+    })?;
+
+    queued
+        .attributes
+        .iter()
+        .find(|a| a.key == "withdrawal_id")
+        .and_then(|a| a.value.parse().ok())
+}
+
+pub fn validate_withdraw(
+    api: &dyn Api,
+    to: &Addr,
+    amounts: &[Coin],
+) -> Result<Addr, ContractError> {
+    let to = api.addr_validate(to.as_str())?;
+    if amounts.is_empty() || amounts.iter().any(|c| c.amount.is_zero()) {
+        return Err(ContractError::NoFunds {});
+    }
+
+    Ok(to)
+}
+
+// dispatch_withdraw consumes the request and withdraws from the liquidity
+// manager straight to the recipient.
+pub fn dispatch_withdraw(
+    storage: &mut dyn Storage,
+    to: &Addr,
+    amounts: &[Coin],
+    req_op_id: u64,
+    now: u64,
+) -> Result<Vec<SubMsg>, ContractError> {
+    record_outflows(storage, amounts, now)?;
+    consume_request(storage, req_op_id)?;
+    set_withdraw_info(storage, to.clone(), amounts.to_vec(), None)?;
+
+    let lmgr = LIQUIDITY_MANAGER.load(storage)?;
+
+    Ok(withdraw_submsgs(&lmgr, Some(to), amounts)?)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Addr,
+    amounts: Vec<Coin>,
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    signatures: Vec<SignerSignature>,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    if let Some(expiration) = expires_at {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::SignatureExpired {});
+        }
+    }
+
+    let signer_set = SIGNER_SET
+        .load(deps.storage)
+        .map_err(|_| ContractError::PublicKeyNotRegistered {})?;
+
+    let sign_bytes = SignPayload::new(
+        env.block.chain_id.clone(),
+        env.contract.address,
+        req_op_id,
+        expires_at,
+        SignAction::Withdraw {
+            to: to.clone(),
+            amounts: amounts.clone(),
+        },
+    )
+    .to_sign_bytes()?;
+    verify_signatures(deps.api, &env.block, &signer_set, &sign_bytes, &signatures)?;
+
+    let to = validate_withdraw(deps.api, &to, &amounts)?;
+
+    let now = env.block.time.seconds();
+    if let Some(limit) = check_outflows(deps.storage, &amounts, now)? {
+        return trip_breaker(deps.storage, info, req_op_id, limit);
+    }
+
+    let msgs = dispatch_withdraw(deps.storage, &to, &amounts, req_op_id, now)?;
+
+    let resp = Response::new().add_submessages(msgs).add_attributes(vec![
+        attr("action", "withdraw"),
+        attr("executor", info.sender),
+        attr("req_op_id", req_op_id.to_string()),
+        attr("to", to),
+        attr("amount", coins_to_string(&amounts)),
+    ]);
+
+    Ok(resp)
+}
+
+// reply_withdraw handles one withdraw leg. The liquidity manager pays a local
+// recipient itself, now or when a queued withdrawal is finalized, so only a
+// hook refund has funds to forward. A queued refund leg is kept until
+// SettleRefund finds out how its withdrawal ended.
+pub fn reply_withdraw(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let result = msg
+        .result
+        .into_result()
+        .map_err(|reason| ContractError::WithdrawFailed { reason })?;
+
+    let leg =
+        next_withdraw_leg(deps.storage).map_err(|_| ContractError::WithdrawContextNotFound {})?;
+    let lmgr = LIQUIDITY_MANAGER.load(deps.storage)?;
+    let queued = queued_withdrawal(&result.events, &lmgr);
+
+    let hook_refund = match leg.last {
+        true => take_hook_refund(deps.storage)?,
+        false => HOOK_REFUND.may_load(deps.storage)?,
+    };
+
+    let mut attrs = vec![attr("action", "reply_withdraw")];
+    match &hook_refund {
+        Some(HookRefund { origin, .. }) => attrs.extend([
+            attr("to", origin.original_sender.clone()),
+            attr("channel", origin.channel.clone()),
+        ]),
+        None => attrs.push(attr("to", leg.to_address.clone())),
+    }
+    attrs.push(attr("amount", leg.amount.to_string()));
+    let resp = Response::new().add_attributes(attrs);
+
+    if let Some(withdrawal_id) = queued {
+        if let Some(seq) = leg.refund_seq {
+            QUEUED_REFUNDS.save(
+                deps.storage,
+                withdrawal_id,
+                &QueuedRefund {
+                    seq,
+                    liquidity_manager: lmgr,
+                    amount: leg.amount,
+                },
+            )?;
+        }
+
+        return Ok(resp.add_attribute("withdrawal_id", withdrawal_id.to_string()));
+    }
+
+    // a hook send goes back over the channel it came from
+    match hook_refund {
+        Some(HookRefund { seq, origin }) => {
+            let msgs = refund_transfers(deps.storage, &env, seq, &origin, &[leg.amount])?;
+
+            Ok(resp.add_submessages(msgs))
+        }
+        None => Ok(resp),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coin,
+        testing::{mock_dependencies, mock_env, mock_info},
+        HexBinary, SubMsgResponse, SubMsgResult,
+    };
+    use mitosis_interface::gateway::{SignatureScheme, Signer};
+
+    use crate::state::{
+        context::{WithdrawInfo, WITHDRAW},
+        requests::EXECUTED_REQUESTS,
+        signers::SignerSet,
+    };
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+
+    const PUBLIC_KEYS: [&str; 2] = [
+        "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1",
+        "02ec18c82501c5088119251679b538e9cf8eae502956cc862c7778aa148365e886",
+    ];
+
+    // signatures of mock_amounts to addr1 with req_op_id 0
+    const SIGNATURES: [&str; 2] = [
+        "deec633f39b2938111e101cf5b43e6c81a24b012630e47db54e9e10fc23db99467447f4274672e1dd9adc2513f644fdaf93243a406980b50db1d8d90f757dc2e",
+        "817da7c920db53aaddd18d77acb87db72ff790e72067efae9d15133806e95b9d7274f508c4f3b92a76915001bb79da8f4aa5131537d3eb26a8fa2bc41042e80a",
+    ];
+
+    fn mock_amounts() -> Vec<Coin> {
+        vec![coin(100, "uatom"), coin(200, "uosmo")]
+    }
+
+    fn mock_signatures() -> Vec<SignerSignature> {
+        PUBLIC_KEYS
+            .iter()
+            .zip(SIGNATURES)
+            .map(|(public_key, signature)| SignerSignature {
+                public_key: HexBinary::from_hex(public_key).unwrap(),
+                signature: HexBinary::from_hex(signature).unwrap(),
+            })
+            .collect()
+    }
+
+    fn mock_gateway(deps: DepsMut) {
+        let signers = PUBLIC_KEYS
+            .iter()
+            .map(|key| Signer {
+                public_key: HexBinary::from_hex(key).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            })
+            .collect();
+
+        SIGNER_SET
+            .save(deps.storage, &SignerSet::new(signers, 2, 0).unwrap())
+            .unwrap();
+        LIQUIDITY_MANAGER
+            .save(deps.storage, &Addr::unchecked("contract"))
+            .unwrap();
+        PAUSED.save(deps.storage, &Default::default()).unwrap();
+    }
+
+    #[test]
+    fn test_withdraw() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let to = Addr::unchecked(ADDR1);
+        let info = mock_info(ADDR2, &[]);
+        mock_gateway(deps.as_mut());
+
+        let result = withdraw(
+            deps.as_mut(),
+            env,
+            info,
+            to.clone(),
+            mock_amounts(),
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "withdraw"),
+                attr("executor", ADDR2),
+                attr("req_op_id", "0"),
+                attr("to", to.clone()),
+                attr("amount", "100uatom,200uosmo"),
+            ]
+        );
+        assert_eq!(
+            result.messages,
+            withdraw_submsgs(&Addr::unchecked("contract"), Some(&to), &mock_amounts()).unwrap()
+        );
+        assert_eq!(
+            WITHDRAW.load(&deps.storage).unwrap(),
+            WithdrawInfo {
+                to_address: to,
+                amount: mock_amounts(),
+                refund_seq: None,
+            }
+        );
+        assert!(EXECUTED_REQUESTS.load(&deps.storage, 0).unwrap());
+    }
+
+    #[test]
+    fn test_withdraw_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let info = mock_info(ADDR2, &[]);
+        mock_gateway(deps.as_mut());
+
+        // signed for another recipient
+        let other_recipient = withdraw(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            Addr::unchecked(ADDR2),
+            mock_amounts(),
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(other_recipient, ContractError::InvalidPubKey {}));
+
+        // previous withdraw is not replied yet
+        WITHDRAW
+            .save(
+                &mut deps.storage,
+                &WithdrawInfo {
+                    to_address: Addr::unchecked(ADDR2),
+                    amount: mock_amounts(),
+                    refund_seq: None,
+                },
+            )
+            .unwrap();
+        let not_flushed = withdraw(
+            deps.as_mut(),
+            env,
+            info,
+            Addr::unchecked(ADDR1),
+            mock_amounts(),
+            0,
+            None,
+            mock_signatures(),
+        )
+        .unwrap_err();
+        assert!(matches!(not_flushed, ContractError::WithdrawNotFlushed {}));
+    }
+
+    #[test]
+    fn test_withdraw_submsgs() {
+        let lmgr = Addr::unchecked("contract");
+        let to = Addr::unchecked(ADDR1);
+
+        let msgs = withdraw_submsgs(&lmgr, Some(&to), &mock_amounts()).unwrap();
+        assert_eq!(msgs.len(), 2);
+        for (msg, amount) in msgs.iter().zip(mock_amounts()) {
+            assert_eq!(
+                msg,
+                &SubMsg::reply_on_success(
+                    WasmMsg::Execute {
+                        contract_addr: lmgr.to_string(),
+                        msg: to_binary(&liquidity_manager::ExecuteMsg::Withdraw {
+                            withdrawer: Some(to.clone()),
+                            amount,
+                        })
+                        .unwrap(),
+                        funds: vec![],
+                    },
+                    REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+                )
+            );
+        }
+    }
+
+    fn mock_reply(result: SubMsgResult) -> Reply {
+        Reply {
+            id: REPLY_WITHDRAW_SUBMESSAGE_SUCCESS,
+            result,
+        }
+    }
+
+    fn mock_leg(queued: Option<&str>) -> SubMsgResult {
+        let event = match queued {
+            Some(id) => Event::new("wasm")
+                .add_attribute("_contract_address", "contract")
+                .add_attribute("action", "queue_withdraw")
+                .add_attribute("withdrawal_id", id),
+            None => Event::new("wasm")
+                .add_attribute("_contract_address", "contract")
+                .add_attribute("action", "withdraw"),
+        };
+
+        SubMsgResult::Ok(SubMsgResponse {
+            events: vec![event],
+            data: None,
+        })
+    }
+
+    #[test]
+    fn test_reply_withdraw() {
+        let mut deps = mock_dependencies();
+        mock_gateway(deps.as_mut());
+
+        let no_context =
+            reply_withdraw(deps.as_mut(), mock_env(), mock_reply(mock_leg(None))).unwrap_err();
+        assert!(matches!(
+            no_context,
+            ContractError::WithdrawContextNotFound {}
+        ));
+
+        set_withdraw_info(
+            &mut deps.storage,
+            Addr::unchecked(ADDR1),
+            mock_amounts(),
+            None,
+        )
+        .unwrap();
+
+        let failed = reply_withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_reply(SubMsgResult::Err("oops".to_string())),
+        )
+        .unwrap_err();
+        assert!(matches!(failed, ContractError::WithdrawFailed { .. }));
+
+        // the liquidity manager paid the recipient, nothing is forwarded
+        let paid = reply_withdraw(deps.as_mut(), mock_env(), mock_reply(mock_leg(None))).unwrap();
+        assert!(paid.messages.is_empty());
+        assert_eq!(
+            paid.attributes,
+            vec![
+                attr("action", "reply_withdraw"),
+                attr("to", ADDR1),
+                attr("amount", "100uatom"),
+            ]
+        );
+        assert!(WITHDRAW.may_load(&deps.storage).unwrap().is_some());
+
+        let queued =
+            reply_withdraw(deps.as_mut(), mock_env(), mock_reply(mock_leg(Some("7")))).unwrap();
+        assert!(queued.messages.is_empty());
+        assert_eq!(
+            queued.attributes,
+            vec![
+                attr("action", "reply_withdraw"),
+                attr("to", ADDR1),
+                attr("amount", "200uosmo"),
+                attr("withdrawal_id", "7"),
+            ]
+        );
+
+        // flushed with the last leg, and only a refund leg is kept for settling
+        assert!(WITHDRAW.may_load(&deps.storage).unwrap().is_none());
+        assert!(QUEUED_REFUNDS.is_empty(&deps.storage));
+    }
+
+    #[test]
+    fn test_reply_withdraw_queued_refund() {
+        let mut deps = mock_dependencies();
+        mock_gateway(deps.as_mut());
+
+        set_withdraw_info(
+            &mut deps.storage,
+            Addr::unchecked(ADDR1),
+            mock_amounts(),
+            Some(3),
+        )
+        .unwrap();
+
+        // a queue_withdraw event of any other contract is not trusted
+        let forged = SubMsgResult::Ok(SubMsgResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("_contract_address", "other")
+                .add_attribute("action", "queue_withdraw")
+                .add_attribute("withdrawal_id", "5")],
+            data: None,
+        });
+        let paid = reply_withdraw(deps.as_mut(), mock_env(), mock_reply(forged)).unwrap();
+        assert_eq!(paid.attributes.len(), 3);
+
+        let queued =
+            reply_withdraw(deps.as_mut(), mock_env(), mock_reply(mock_leg(Some("7")))).unwrap();
+        assert_eq!(queued.attributes[3], attr("withdrawal_id", "7"));
+
+        assert!(QUEUED_REFUNDS.may_load(&deps.storage, 5).unwrap().is_none());
+        assert_eq!(
+            QUEUED_REFUNDS.load(&deps.storage, 7).unwrap(),
+            QueuedRefund {
+                seq: 3,
+                liquidity_manager: Addr::unchecked("contract"),
+                amount: coin(200, "uosmo"),
+            }
+        );
+    }
+}
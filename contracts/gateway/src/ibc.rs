@@ -0,0 +1,592 @@
+use cosmwasm_std::{
+    attr, entry_point, from_binary, to_binary, Binary, DepsMut, Env, IbcBasicResponse, IbcChannel,
+    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcOrder,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, Never, Reply,
+    Response, SubMsg, WasmMsg,
+};
+use mitosis_interface::gateway::{
+    ExecuteMsg, GatewayAck, GatewayPacket, IbcChannelInfo, OperationStatus, PacketV1,
+    IBC_APP_VERSION,
+};
+
+use crate::{
+    errors::ContractError,
+    execute::{
+        consts::REPLY_IBC_RECEIVE_FAILURE,
+        limits::{collect_outflows, pause_until_released},
+    },
+    state::{
+        channels::{assert_counterparty, load_open_channel, CHANNELS, SEND_CHANNEL},
+        limits::check_outflows,
+        operations::update_status,
+        policy::{assert_msgs_allowed, EXECUTE_POLICY},
+        LIQUIDITY_MANAGER, PAUSED,
+    },
+};
+
+pub fn ack_success() -> Binary {
+    to_binary(&GatewayAck::Result(Binary::default())).unwrap()
+}
+
+pub fn ack_fail(err: String) -> Binary {
+    to_binary(&GatewayAck::Error(err)).unwrap()
+}
+
+fn validate_channel(
+    deps: &DepsMut,
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != IbcOrder::Unordered {
+        return Err(ContractError::InvalidChannel {
+            reason: "channel must be unordered".to_string(),
+        });
+    }
+
+    if channel.version != IBC_APP_VERSION
+        || counterparty_version.is_some_and(|version| version != IBC_APP_VERSION)
+    {
+        return Err(ContractError::InvalidChannel {
+            reason: format!("channel version must be {IBC_APP_VERSION}"),
+        });
+    }
+
+    assert_counterparty(
+        deps.storage,
+        &channel.connection_id,
+        &channel.counterparty_endpoint.port_id,
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    validate_channel(&deps, msg.channel(), msg.counterparty_version())?;
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    validate_channel(&deps, msg.channel(), msg.counterparty_version())?;
+
+    let channel = msg.channel();
+    let info = IbcChannelInfo {
+        channel_id: channel.endpoint.channel_id.clone(),
+        connection_id: channel.connection_id.clone(),
+        counterparty_port_id: channel.counterparty_endpoint.port_id.clone(),
+        counterparty_channel_id: channel.counterparty_endpoint.channel_id.clone(),
+        open: true,
+    };
+    CHANNELS.save(deps.storage, &info.channel_id, &info)?;
+
+    Ok(IbcBasicResponse::new().add_attributes(vec![
+        attr("action", "ibc_channel_connect"),
+        attr("channel_id", info.channel_id),
+        attr("connection_id", info.connection_id),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+
+    if let Some(mut info) = CHANNELS.may_load(deps.storage, &channel_id)? {
+        info.open = false;
+        CHANNELS.save(deps.storage, &channel_id, &info)?;
+    }
+
+    // fall back to the relayer
+    if SEND_CHANNEL.may_load(deps.storage)? == Some(channel_id.clone()) {
+        SEND_CHANNEL.remove(deps.storage);
+    }
+
+    Ok(IbcBasicResponse::new().add_attributes(vec![
+        attr("action", "ibc_channel_close"),
+        attr("channel_id", channel_id),
+    ]))
+}
+
+// ibc_packet_receive never fails, errors are returned to the counterpart as an
+// error acknowledgement.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, Never> {
+    let channel_id = msg.packet.dest.channel_id.clone();
+
+    match receive_packet(deps, env, msg) {
+        Ok(response) => Ok(response),
+        Err(err) => Ok(IbcReceiveResponse::new()
+            .set_ack(ack_fail(err.to_string()))
+            .add_attributes(vec![
+                attr("action", "ibc_packet_receive"),
+                attr("channel_id", channel_id),
+                attr("error", err.to_string()),
+            ])),
+    }
+}
+
+// receive_packet checks the instruction and hands it to HandlePacket. Outflows
+// are checked here so a breach can pause the gateway without being reverted.
+fn receive_packet(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let channel_id = msg.packet.dest.channel_id;
+    let channel = load_open_channel(deps.storage, &channel_id)?;
+    assert_counterparty(
+        deps.storage,
+        &channel.connection_id,
+        &channel.counterparty_port_id,
+    )?;
+
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    let GatewayPacket::V1(packet) = from_binary(&msg.packet.data)?;
+
+    let outflows = match &packet {
+        PacketV1::Execute { msgs, .. } => {
+            let policy = EXECUTE_POLICY.may_load(deps.storage)?.unwrap_or_default();
+            assert_msgs_allowed(&policy, msgs)?;
+
+            collect_outflows(msgs, &LIQUIDITY_MANAGER.load(deps.storage)?)
+        }
+        PacketV1::Withdraw { amounts, .. } => amounts.clone(),
+        PacketV1::Send { .. } => return Err(ContractError::UnsupportedPacket {}),
+    };
+
+    if let Some(limit) = check_outflows(deps.storage, &outflows, env.block.time.seconds())? {
+        pause_until_released(deps.storage)?;
+
+        return Ok(IbcReceiveResponse::new()
+            .set_ack(ack_fail(format!("rate limited by {limit}")))
+            .add_attributes(vec![
+                attr("action", "rate_limited"),
+                attr("channel_id", channel_id),
+                attr("limit", limit),
+            ]));
+    }
+
+    let handle = WasmMsg::Execute {
+        contract_addr: env.contract.address.into_string(),
+        msg: to_binary(&ExecuteMsg::HandlePacket {
+            channel_id: channel_id.clone(),
+            packet,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(IbcReceiveResponse::new()
+        .set_ack(ack_success())
+        .add_submessage(SubMsg::reply_on_error(handle, REPLY_IBC_RECEIVE_FAILURE))
+        .add_attributes(vec![
+            attr("action", "ibc_packet_receive"),
+            attr("channel_id", channel_id),
+            attr("sequence", msg.packet.sequence.to_string()),
+        ]))
+}
+
+// reply_receive_failure replaces the acknowledgement of a failed instruction.
+pub fn reply_receive_failure(msg: Reply) -> Result<Response, ContractError> {
+    let err = match msg.result.into_result() {
+        Ok(_) => return Ok(Response::new()),
+        Err(err) => err,
+    };
+
+    Ok(Response::new()
+        .set_data(ack_fail(err.clone()))
+        .add_attributes(vec![
+            attr("action", "ibc_packet_failed"),
+            attr("error", err),
+        ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let mut response = IbcBasicResponse::new().add_attribute("action", "ibc_packet_ack");
+
+    let Ok(GatewayPacket::V1(PacketV1::Send { seq, .. })) = from_binary(&msg.original_packet.data)
+    else {
+        return Ok(response);
+    };
+    response = response.add_attribute("seq", seq.to_string());
+
+    // the ack must not fail, otherwise it is relayed again. An operation that
+    // can't be marked as relayed stays refundable after its deadline.
+    match from_binary(&msg.acknowledgement.data) {
+        Ok(GatewayAck::Result(_)) => {
+            match update_status(
+                deps.storage,
+                seq,
+                OperationStatus::Relayed,
+                env.block.time.seconds(),
+            ) {
+                Ok(_) => response = response.add_attribute("status", "relayed"),
+                Err(err) => response = response.add_attribute("error", err.to_string()),
+            }
+        }
+        Ok(GatewayAck::Error(err)) => response = response.add_attribute("error", err),
+        Err(err) => response = response.add_attribute("error", err.to_string()),
+    }
+
+    Ok(response)
+}
+
+// a timed out send stays pending and can be refunded, its deadline has passed
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let mut response = IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout");
+
+    if let Ok(GatewayPacket::V1(PacketV1::Send { seq, .. })) = from_binary(&msg.packet.data) {
+        response = response.add_attribute("seq", seq.to_string());
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coins,
+        testing::{
+            mock_dependencies, mock_env, mock_ibc_channel_close_init, mock_ibc_channel_connect_ack,
+            mock_ibc_channel_open_init, mock_ibc_channel_open_try, mock_ibc_packet_ack,
+            mock_ibc_packet_recv, mock_ibc_packet_timeout, MockApi, MockQuerier, MockStorage,
+        },
+        Addr, BankMsg, CosmosMsg, IbcAcknowledgement, OwnedDeps, SubMsgResponse, SubMsgResult,
+        Uint128,
+    };
+    use mitosis_interface::gateway::{ExecutePolicy, MsgKind, RateLimit};
+
+    use crate::state::{
+        channels::COUNTERPARTIES,
+        limits::set_rate_limit,
+        operations::{query_operation, record_operation},
+    };
+
+    use super::*;
+
+    const CHANNEL: &str = "channel-0";
+
+    fn mock_gateway() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+
+        PAUSED.save(&mut deps.storage, &Default::default()).unwrap();
+        LIQUIDITY_MANAGER
+            .save(&mut deps.storage, &Addr::unchecked("lmgr"))
+            .unwrap();
+        EXECUTE_POLICY
+            .save(
+                &mut deps.storage,
+                &ExecutePolicy {
+                    kinds: vec![MsgKind::Bank],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        // mock_ibc_channel connects to their_port over connection-2
+        COUNTERPARTIES
+            .save(&mut deps.storage, "connection-2", &"their_port".to_string())
+            .unwrap();
+
+        deps
+    }
+
+    fn mock_connected() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_gateway();
+
+        let msg = mock_ibc_channel_connect_ack(CHANNEL, IbcOrder::Unordered, IBC_APP_VERSION);
+        ibc_channel_connect(deps.as_mut(), mock_env(), msg).unwrap();
+
+        deps
+    }
+
+    fn mock_execute_packet(amount: u128) -> GatewayPacket {
+        GatewayPacket::V1(PacketV1::Execute {
+            req_op_id: 0,
+            msgs: vec![BankMsg::Send {
+                to_address: "addr1".to_string(),
+                amount: coins(amount, "uosmo"),
+            }
+            .into()],
+        })
+    }
+
+    fn assert_ack_fail(response: &IbcReceiveResponse) {
+        let ack: GatewayAck = from_binary(&response.acknowledgement).unwrap();
+        assert!(matches!(ack, GatewayAck::Error(_)));
+        assert!(response.messages.is_empty());
+    }
+
+    #[test]
+    fn test_channel_open() {
+        let mut deps = mock_gateway();
+        let env = mock_env();
+
+        let msg = mock_ibc_channel_open_init(CHANNEL, IbcOrder::Unordered, IBC_APP_VERSION);
+        ibc_channel_open(deps.as_mut(), env.clone(), msg).unwrap();
+
+        let msg = mock_ibc_channel_open_try(CHANNEL, IbcOrder::Unordered, IBC_APP_VERSION);
+        ibc_channel_open(deps.as_mut(), env.clone(), msg).unwrap();
+
+        let ordered = ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_init(CHANNEL, IbcOrder::Ordered, IBC_APP_VERSION),
+        )
+        .unwrap_err();
+        assert!(matches!(ordered, ContractError::InvalidChannel { .. }));
+
+        let other_version = ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try(CHANNEL, IbcOrder::Unordered, "ics20-1"),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            other_version,
+            ContractError::InvalidChannel { .. }
+        ));
+
+        COUNTERPARTIES.remove(&mut deps.storage, "connection-2");
+        let unknown = ibc_channel_open(
+            deps.as_mut(),
+            env,
+            mock_ibc_channel_open_init(CHANNEL, IbcOrder::Unordered, IBC_APP_VERSION),
+        )
+        .unwrap_err();
+        assert!(matches!(unknown, ContractError::UnknownCounterparty { .. }));
+    }
+
+    #[test]
+    fn test_channel_connect_and_close() {
+        let mut deps = mock_connected();
+
+        assert_eq!(
+            CHANNELS.load(&deps.storage, CHANNEL).unwrap(),
+            IbcChannelInfo {
+                channel_id: CHANNEL.to_string(),
+                connection_id: "connection-2".to_string(),
+                counterparty_port_id: "their_port".to_string(),
+                counterparty_channel_id: "channel-7".to_string(),
+                open: true,
+            }
+        );
+
+        SEND_CHANNEL
+            .save(&mut deps.storage, &CHANNEL.to_string())
+            .unwrap();
+
+        let msg = mock_ibc_channel_close_init(CHANNEL, IbcOrder::Unordered, IBC_APP_VERSION);
+        ibc_channel_close(deps.as_mut(), mock_env(), msg).unwrap();
+
+        assert!(!CHANNELS.load(&deps.storage, CHANNEL).unwrap().open);
+        assert!(SEND_CHANNEL.may_load(&deps.storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_packet_receive() {
+        let mut deps = mock_connected();
+        let env = mock_env();
+
+        let msg = mock_ibc_packet_recv(CHANNEL, &mock_execute_packet(100)).unwrap();
+        let response = ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap();
+
+        assert_eq!(response.acknowledgement, ack_success());
+        assert_eq!(
+            response.messages,
+            vec![SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    msg: to_binary(&ExecuteMsg::HandlePacket {
+                        channel_id: CHANNEL.to_string(),
+                        packet: match mock_execute_packet(100) {
+                            GatewayPacket::V1(packet) => packet,
+                        },
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                },
+                REPLY_IBC_RECEIVE_FAILURE,
+            )]
+        );
+    }
+
+    #[test]
+    fn test_packet_receive_rejected() {
+        let mut deps = mock_connected();
+        let env = mock_env();
+
+        // unknown channel
+        let msg = mock_ibc_packet_recv("channel-9", &mock_execute_packet(100)).unwrap();
+        assert_ack_fail(&ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap());
+
+        // malformed packet
+        let msg = mock_ibc_packet_recv(CHANNEL, &"hello").unwrap();
+        assert_ack_fail(&ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap());
+
+        // outbound packet type
+        let send = GatewayPacket::V1(PacketV1::Send {
+            seq: 0,
+            sender: Addr::unchecked("addr1"),
+            amounts: vec![],
+            aliases: vec![],
+            op_id: 0,
+            op_args: vec![],
+        });
+        let msg = mock_ibc_packet_recv(CHANNEL, &send).unwrap();
+        assert_ack_fail(&ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap());
+
+        // not allowed by the execute policy
+        let gov = GatewayPacket::V1(PacketV1::Execute {
+            req_op_id: 0,
+            msgs: vec![CosmosMsg::Custom(cosmwasm_std::Empty {})],
+        });
+        let msg = mock_ibc_packet_recv(CHANNEL, &gov).unwrap();
+        assert_ack_fail(&ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap());
+
+        // counterparty removed by the owner
+        COUNTERPARTIES.remove(&mut deps.storage, "connection-2");
+        let msg = mock_ibc_packet_recv(CHANNEL, &mock_execute_packet(100)).unwrap();
+        assert_ack_fail(&ibc_packet_receive(deps.as_mut(), env, msg).unwrap());
+    }
+
+    #[test]
+    fn test_packet_receive_rate_limited() {
+        let mut deps = mock_connected();
+        let env = mock_env();
+
+        set_rate_limit(
+            &mut deps.storage,
+            "uosmo",
+            Some(RateLimit {
+                max_amount: Uint128::new(99),
+                window: 60,
+            }),
+        )
+        .unwrap();
+
+        let msg = mock_ibc_packet_recv(CHANNEL, &mock_execute_packet(100)).unwrap();
+        let response = ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap();
+        assert_ack_fail(&response);
+        assert!(PAUSED.load(&deps.storage).unwrap().paused);
+
+        // paused
+        let msg = mock_ibc_packet_recv(CHANNEL, &mock_execute_packet(1)).unwrap();
+        assert_ack_fail(&ibc_packet_receive(deps.as_mut(), env, msg).unwrap());
+    }
+
+    #[test]
+    fn test_reply_receive_failure() {
+        let failed = reply_receive_failure(Reply {
+            id: REPLY_IBC_RECEIVE_FAILURE,
+            result: SubMsgResult::Err("insufficient funds".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            failed.data,
+            Some(ack_fail("insufficient funds".to_string()))
+        );
+
+        let succeeded = reply_receive_failure(Reply {
+            id: REPLY_IBC_RECEIVE_FAILURE,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        })
+        .unwrap();
+        assert_eq!(succeeded.data, None);
+    }
+
+    #[test]
+    fn test_packet_ack() {
+        let mut deps = mock_connected();
+        let env = mock_env();
+        let now = env.block.time.seconds();
+
+        for _ in 0..2 {
+            record_operation(
+                &mut deps.storage,
+                Addr::unchecked("addr1"),
+                coins(100, "uosmo"),
+                vec!["osmo".to_string()],
+                1,
+                vec![],
+                env.block.height,
+                now + 60,
+                None,
+            )
+            .unwrap();
+        }
+
+        let send = |seq| {
+            GatewayPacket::V1(PacketV1::Send {
+                seq,
+                sender: Addr::unchecked("addr1"),
+                amounts: coins(100, "uosmo"),
+                aliases: vec!["osmo".to_string()],
+                op_id: 1,
+                op_args: vec![],
+            })
+        };
+
+        let msg =
+            mock_ibc_packet_ack(CHANNEL, &send(0), IbcAcknowledgement::new(ack_success())).unwrap();
+        let response = ibc_packet_ack(deps.as_mut(), env.clone(), msg).unwrap();
+        assert_eq!(response.attributes[2], attr("status", "relayed"));
+        assert_eq!(
+            query_operation(&deps.storage, 0).unwrap().status,
+            OperationStatus::Relayed
+        );
+
+        // an error ack leaves the operation refundable
+        let msg = mock_ibc_packet_ack(
+            CHANNEL,
+            &send(1),
+            IbcAcknowledgement::new(ack_fail("unknown op".to_string())),
+        )
+        .unwrap();
+        let response = ibc_packet_ack(deps.as_mut(), env.clone(), msg).unwrap();
+        assert_eq!(response.attributes[2], attr("error", "unknown op"));
+        assert_eq!(
+            query_operation(&deps.storage, 1).unwrap().status,
+            OperationStatus::Pending
+        );
+
+        // acks never fail
+        let msg =
+            mock_ibc_packet_ack(CHANNEL, &send(0), IbcAcknowledgement::new(ack_success())).unwrap();
+        ibc_packet_ack(deps.as_mut(), env.clone(), msg).unwrap();
+
+        let msg = mock_ibc_packet_timeout(CHANNEL, &send(1)).unwrap();
+        let response = ibc_packet_timeout(deps.as_mut(), env, msg).unwrap();
+        assert_eq!(response.attributes[1], attr("seq", "1"));
+    }
+}
@@ -1,6 +1,9 @@
 pub mod contracts;
 pub mod errors;
 pub mod execute;
+pub mod ibc;
+pub mod migrations;
+pub mod proto;
 pub mod query;
 pub mod state;
 pub mod verify;
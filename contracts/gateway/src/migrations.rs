@@ -0,0 +1,196 @@
+use cosmwasm_std::{Env, HexBinary, Storage};
+use cw_storage_plus::Item;
+use mitosis_interface::gateway::{MigrateMsg, SignatureScheme, Signer};
+
+use crate::{
+    errors::ContractError,
+    execute::managers::validate_operation_timeout,
+    state::{
+        policy::EXECUTE_POLICY,
+        signers::{SignerSet, SIGNER_SET},
+        ADDRESS_PREFIX, OPERATION_TIMEOUT, PAUSED,
+    },
+    verify::{address_prefix, validate_prefix},
+};
+
+// gateways instantiated before signer sets kept their only signer key here
+pub const LEGACY_PUBLIC_KEY_KEY: &str = "public_key";
+pub const LEGACY_PUBLIC_KEY: Item<HexBinary> = Item::new(LEGACY_PUBLIC_KEY_KEY);
+
+// migrate_legacy upgrades a gateway still on the single public key layout. The
+// key becomes a one-signer set and the items added since are filled in, the
+// operation timeout has no sane default so the migration has to pass it.
+// Returns whether there was anything to upgrade.
+pub fn migrate_legacy(
+    storage: &mut dyn Storage,
+    env: &Env,
+    msg: &MigrateMsg,
+) -> Result<bool, ContractError> {
+    let public_key = match LEGACY_PUBLIC_KEY.may_load(storage)? {
+        Some(public_key) => public_key,
+        None => return Ok(false),
+    };
+
+    let operation_timeout =
+        msg.operation_timeout
+            .ok_or_else(|| ContractError::InvalidArgument {
+                msg: "operation_timeout is required to migrate a single key gateway".to_string(),
+            })?;
+    validate_operation_timeout(operation_timeout)?;
+    OPERATION_TIMEOUT.save(storage, &operation_timeout)?;
+
+    let signer = Signer {
+        public_key,
+        weight: 1,
+        scheme: SignatureScheme::Secp256k1,
+    };
+    SIGNER_SET.save(storage, &SignerSet::new(vec![signer], 1, 0)?)?;
+    LEGACY_PUBLIC_KEY.remove(storage);
+
+    if PAUSED.may_load(storage)?.is_none() {
+        PAUSED.save(storage, &Default::default())?;
+    }
+    if EXECUTE_POLICY.may_load(storage)?.is_none() {
+        EXECUTE_POLICY.save(storage, &Default::default())?;
+    }
+    if ADDRESS_PREFIX.may_load(storage)?.is_none() {
+        let prefix = address_prefix(env.contract.address.as_str())?;
+        validate_prefix(&prefix)?;
+        ADDRESS_PREFIX.save(storage, &prefix)?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        attr, from_binary,
+        testing::{mock_dependencies, mock_env},
+        Addr,
+    };
+    use cw2::{get_contract_version, set_contract_version};
+    use mitosis_interface::gateway::{ConfigResponse, ExecutePolicy, QueryMsg};
+
+    use crate::{
+        contracts::{migrate, query},
+        state::{DENOM_MANAGER, LIQUIDITY_MANAGER, OWNER},
+        CONTRACT_NAME, CONTRACT_VERSION,
+    };
+
+    use super::*;
+
+    const GATEWAY: &str = "osmo1f6j7u6875p2cvyrgjr0d2uecyzah0kgeek38h39czwdhe5em3zyselm50a";
+    const PUBLIC_KEY: &str = "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1";
+
+    fn mock_gateway_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(GATEWAY);
+        env
+    }
+
+    // the layout written by the first gateway release
+    fn mock_legacy(storage: &mut dyn Storage) {
+        set_contract_version(storage, CONTRACT_NAME, "0.0.1").unwrap();
+        OWNER.save(storage, &Addr::unchecked("owner")).unwrap();
+        LIQUIDITY_MANAGER
+            .save(storage, &Addr::unchecked("lmgr"))
+            .unwrap();
+        DENOM_MANAGER
+            .save(storage, &Addr::unchecked("dmgr"))
+            .unwrap();
+        LEGACY_PUBLIC_KEY
+            .save(storage, &HexBinary::from_hex(PUBLIC_KEY).unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let mut deps = mock_dependencies();
+        let env = mock_gateway_env();
+
+        mock_legacy(deps.as_mut().storage);
+
+        let no_timeout = migrate(
+            deps.as_mut(),
+            env.clone(),
+            MigrateMsg {
+                operation_timeout: None,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(no_timeout, ContractError::InvalidArgument { .. }));
+
+        let result = migrate(
+            deps.as_mut(),
+            env.clone(),
+            MigrateMsg {
+                operation_timeout: Some(3600),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("method", "migrate"),
+                attr("from_version", "0.0.1"),
+                attr("legacy_layout", "true"),
+            ]
+        );
+
+        let signer_set = SIGNER_SET.load(&deps.storage).unwrap();
+        assert_eq!(
+            signer_set.signers,
+            vec![Signer {
+                public_key: HexBinary::from_hex(PUBLIC_KEY).unwrap(),
+                weight: 1,
+                scheme: SignatureScheme::Secp256k1,
+            }]
+        );
+        assert_eq!(signer_set.threshold, 1);
+        assert!(LEGACY_PUBLIC_KEY.may_load(&deps.storage).unwrap().is_none());
+        assert!(!PAUSED.load(&deps.storage).unwrap().paused);
+        assert_eq!(
+            EXECUTE_POLICY.load(&deps.storage).unwrap(),
+            ExecutePolicy::default()
+        );
+        assert_eq!(
+            get_contract_version(&deps.storage).unwrap().version,
+            CONTRACT_VERSION
+        );
+
+        let config: ConfigResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap())
+                .unwrap();
+        assert_eq!(config.operation_timeout, 3600);
+        assert_eq!(config.address_prefix, "osmo");
+
+        // nothing left to upgrade
+        let again = migrate(
+            deps.as_mut(),
+            env,
+            MigrateMsg {
+                operation_timeout: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(again.attributes[2], attr("legacy_layout", "false"));
+    }
+
+    #[test]
+    fn test_migrate_other_contract() {
+        let mut deps = mock_dependencies();
+
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "1.0.0").unwrap();
+
+        let other = migrate(
+            deps.as_mut(),
+            mock_gateway_env(),
+            MigrateMsg {
+                operation_timeout: Some(3600),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(other, ContractError::InvalidArgument { .. }));
+    }
+}
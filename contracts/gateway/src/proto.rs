@@ -0,0 +1,36 @@
+// Protobuf messages of ibc-go's transfer module, which osmosis-std doesn't ship.
+
+pub const MSG_TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProtoCoin {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+    #[prost(string, tag = "2")]
+    pub amount: String,
+}
+
+// timeout_height (tag 6) is left out, transfers only time out by timestamp
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgTransfer {
+    #[prost(string, tag = "1")]
+    pub source_port: String,
+    #[prost(string, tag = "2")]
+    pub source_channel: String,
+    #[prost(message, optional, tag = "3")]
+    pub token: Option<ProtoCoin>,
+    #[prost(string, tag = "4")]
+    pub sender: String,
+    #[prost(string, tag = "5")]
+    pub receiver: String,
+    #[prost(uint64, tag = "7")]
+    pub timeout_timestamp: u64,
+    #[prost(string, tag = "8")]
+    pub memo: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgTransferResponse {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+}
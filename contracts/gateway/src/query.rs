@@ -1,21 +1,197 @@
-use cosmwasm_std::{to_binary, Deps, Env, QueryResponse};
-use mitosis_interface::gateway::ConfigResponse;
+use cosmwasm_std::{to_binary, Addr, Deps, Env, HexBinary, QueryResponse};
+use mitosis_interface::gateway::{
+    BatchOutcomesResponse, ChannelListResponse, ConfigResponse, IsExecutedResponse, OpListResponse,
+    OperationListResponse, OperationResponse, OperationStatus, OutflowWeightListResponse,
+    PendingOwnerResponse, RateLimitListResponse, RootResponse, SignerSetResponse,
+};
 
 use crate::{
     errors::ContractError,
-    state::{DENOM_MANAGER, LIQUIDITY_MANAGER, OWNER, PUBLIC_KEY},
+    state::{
+        batch::query_outcomes,
+        channels::{query_channels, CHANNELS, SEND_CHANNEL},
+        limits::{query_outflow_weights, query_rate_limit, query_rate_limits},
+        load_address_prefix,
+        operations::{
+            query_operation, query_operations, query_operations_by_sender,
+            query_operations_by_status, OperationInfo,
+        },
+        ops::{query_ops, OP_SPECS},
+        policy::EXECUTE_POLICY,
+        rbac::PENDING_OWNER,
+        requests::is_executed,
+        roots::ROOTS,
+        signers::SIGNER_SET,
+        DENOM_MANAGER, LIQUIDITY_MANAGER, OPERATION_TIMEOUT, OWNER,
+    },
 };
 
-pub fn get_config(deps: Deps, _env: Env) -> Result<QueryResponse, ContractError> {
+pub fn get_config(deps: Deps, env: Env) -> Result<QueryResponse, ContractError> {
     let owner = OWNER.load(deps.storage)?;
     let liquidity_manager = LIQUIDITY_MANAGER.load(deps.storage)?;
     let denom_manager = DENOM_MANAGER.load(deps.storage)?;
-    let public_key = PUBLIC_KEY.load(deps.storage)?;
+    let operation_timeout = OPERATION_TIMEOUT.load(deps.storage)?;
+    let send_channel = SEND_CHANNEL.may_load(deps.storage)?;
+    let address_prefix = load_address_prefix(deps.storage, &env)?;
 
     Ok(to_binary(&ConfigResponse {
         owner,
         liquidity_manager,
         denom_manager,
-        public_key,
+        operation_timeout,
+        send_channel,
+        address_prefix,
+    })?)
+}
+
+pub fn get_pending_owner(deps: Deps) -> Result<QueryResponse, ContractError> {
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+
+    Ok(to_binary(&PendingOwnerResponse {
+        pending_owner: pending.as_ref().map(|p| p.owner.clone()),
+        expires_at: pending.and_then(|p| p.expires_at),
     })?)
 }
+
+pub fn get_is_executed(deps: Deps, req_op_id: u64) -> Result<QueryResponse, ContractError> {
+    let executed = is_executed(deps.storage, req_op_id)?;
+
+    Ok(to_binary(&IsExecutedResponse {
+        req_op_id,
+        executed,
+    })?)
+}
+
+pub fn get_root(deps: Deps, env: Env, root: HexBinary) -> Result<QueryResponse, ContractError> {
+    let info = ROOTS.load(deps.storage, root.as_slice())?;
+
+    Ok(to_binary(&RootResponse {
+        root,
+        req_op_id: info.req_op_id,
+        expires_at: info.expires_at,
+        signer_set_version: info.signer_set_version,
+        expired: info.expires_at.is_expired(&env.block),
+    })?)
+}
+
+pub fn get_signer_set(deps: Deps) -> Result<QueryResponse, ContractError> {
+    let signer_set = SIGNER_SET.load(deps.storage)?;
+
+    Ok(to_binary(&SignerSetResponse {
+        signers: signer_set.signers,
+        retiring: signer_set.retiring,
+        threshold: signer_set.threshold,
+        version: signer_set.version,
+    })?)
+}
+
+pub fn get_execute_policy(deps: Deps) -> Result<QueryResponse, ContractError> {
+    let policy = EXECUTE_POLICY.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(to_binary(&policy)?)
+}
+
+pub fn get_rate_limit(deps: Deps, env: Env, denom: String) -> Result<QueryResponse, ContractError> {
+    let limit = query_rate_limit(deps.storage, denom, env.block.time.seconds())?;
+
+    Ok(to_binary(&limit)?)
+}
+
+pub fn get_rate_limits(deps: Deps, env: Env) -> Result<QueryResponse, ContractError> {
+    let items = query_rate_limits(deps.storage, env.block.time.seconds())?;
+
+    Ok(to_binary(&RateLimitListResponse { items })?)
+}
+
+pub fn get_outflow_weights(deps: Deps) -> Result<QueryResponse, ContractError> {
+    let items = query_outflow_weights(deps.storage)?;
+
+    Ok(to_binary(&OutflowWeightListResponse { items })?)
+}
+
+pub fn get_channel(deps: Deps, channel_id: String) -> Result<QueryResponse, ContractError> {
+    let channel = CHANNELS.load(deps.storage, &channel_id)?;
+
+    Ok(to_binary(&channel)?)
+}
+
+pub fn get_channels(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let items = query_channels(deps.storage, start_after, limit)?;
+
+    Ok(to_binary(&ChannelListResponse { items })?)
+}
+
+pub fn get_batch_outcomes(
+    deps: Deps,
+    req_op_id: u64,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let items = query_outcomes(deps.storage, req_op_id, start_after, limit)?;
+
+    Ok(to_binary(&BatchOutcomesResponse { req_op_id, items })?)
+}
+
+pub fn get_operation(deps: Deps, seq: u64) -> Result<QueryResponse, ContractError> {
+    let op = query_operation(deps.storage, seq)?;
+
+    Ok(to_binary(&OperationResponse::from(op))?)
+}
+
+fn to_list(ops: Vec<OperationInfo>) -> OperationListResponse {
+    OperationListResponse {
+        items: ops.into_iter().map(OperationResponse::from).collect(),
+    }
+}
+
+pub fn get_operations(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let ops = query_operations(deps.storage, start_after, limit)?;
+
+    Ok(to_binary(&to_list(ops))?)
+}
+
+pub fn get_operations_by_sender(
+    deps: Deps,
+    sender: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let ops = query_operations_by_sender(deps.storage, sender, start_after, limit)?;
+
+    Ok(to_binary(&to_list(ops))?)
+}
+
+pub fn get_operations_by_status(
+    deps: Deps,
+    status: OperationStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let ops = query_operations_by_status(deps.storage, status, start_after, limit)?;
+
+    Ok(to_binary(&to_list(ops))?)
+}
+
+pub fn get_op(deps: Deps, op_id: u64) -> Result<QueryResponse, ContractError> {
+    let spec = OP_SPECS.load(deps.storage, op_id)?;
+
+    Ok(to_binary(&spec)?)
+}
+
+pub fn get_ops(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let items = query_ops(deps.storage, start_after, limit)?;
+
+    Ok(to_binary(&OpListResponse { items })?)
+}
@@ -0,0 +1,166 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Coin, Order, StdResult, Storage, SubMsgResult};
+use cw_storage_plus::{Bound, Item, Map};
+use mitosis_interface::gateway::MessageOutcome;
+
+use crate::errors::ContractError;
+
+use super::operations::{DEFAULT_LIMIT, MAX_LIMIT};
+
+pub const BATCH_KEY: &str = "batch";
+pub const BATCH: Item<BatchContext> = Item::new(BATCH_KEY);
+
+// (req_op_id, index) -> outcome of the message
+pub const BATCH_OUTCOMES_KEY: &str = "batch_outcomes";
+pub const BATCH_OUTCOMES: Map<(u64, u32), MessageOutcome> = Map::new(BATCH_OUTCOMES_KEY);
+
+// BatchContext tracks the batch being dispatched. Every message replies right
+// after it runs, so replies arrive in message order.
+#[cw_serde]
+pub struct BatchContext {
+    pub req_op_id: u64,
+    pub next: u32,
+    pub total: u32,
+    // outflows counted for each message, released if the message fails
+    pub outflows: Vec<Vec<Coin>>,
+}
+
+pub fn start_batch(
+    storage: &mut dyn Storage,
+    req_op_id: u64,
+    total: u32,
+    outflows: Vec<Vec<Coin>>,
+) -> Result<(), ContractError> {
+    if BATCH.may_load(storage)?.is_some() {
+        return Err(ContractError::BatchInProgress {});
+    }
+
+    BATCH.save(
+        storage,
+        &BatchContext {
+            req_op_id,
+            next: 0,
+            total,
+            outflows,
+        },
+    )?;
+
+    Ok(())
+}
+
+// record_outcome stores the result of the next message of the batch, and
+// clears the batch after its last message. It also returns the outflows
+// counted for the message.
+pub fn record_outcome(
+    storage: &mut dyn Storage,
+    result: SubMsgResult,
+) -> Result<(u64, MessageOutcome, Vec<Coin>), ContractError> {
+    let mut batch = BATCH
+        .may_load(storage)?
+        .ok_or(ContractError::BatchContextNotFound {})?;
+
+    let outcome = match result {
+        SubMsgResult::Ok(_) => MessageOutcome {
+            index: batch.next,
+            success: true,
+            error: None,
+        },
+        SubMsgResult::Err(err) => MessageOutcome {
+            index: batch.next,
+            success: false,
+            error: Some(err),
+        },
+    };
+    BATCH_OUTCOMES.save(storage, (batch.req_op_id, batch.next), &outcome)?;
+    let outflows = batch
+        .outflows
+        .get(batch.next as usize)
+        .cloned()
+        .unwrap_or_default();
+
+    batch.next += 1;
+    if batch.next == batch.total {
+        BATCH.remove(storage);
+    } else {
+        BATCH.save(storage, &batch)?;
+    }
+
+    Ok((batch.req_op_id, outcome, outflows))
+}
+
+pub fn query_outcomes(
+    storage: &dyn Storage,
+    req_op_id: u64,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MessageOutcome>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    BATCH_OUTCOMES
+        .prefix(req_op_id)
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|r| r.map(|(_, outcome)| outcome))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{testing::MockStorage, SubMsgResponse};
+
+    use super::*;
+
+    fn ok() -> SubMsgResult {
+        SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        })
+    }
+
+    #[test]
+    fn test_record_outcome() {
+        let mut storage = MockStorage::new();
+
+        let not_started = record_outcome(&mut storage, ok()).unwrap_err();
+        assert!(matches!(
+            not_started,
+            ContractError::BatchContextNotFound {}
+        ));
+
+        start_batch(&mut storage, 7, 3, vec![]).unwrap();
+        let nested = start_batch(&mut storage, 8, 1, vec![]).unwrap_err();
+        assert!(matches!(nested, ContractError::BatchInProgress {}));
+
+        record_outcome(&mut storage, ok()).unwrap();
+        let (req_op_id, failed, _) =
+            record_outcome(&mut storage, SubMsgResult::Err("out of funds".to_string())).unwrap();
+        assert_eq!(req_op_id, 7);
+        assert_eq!(
+            failed,
+            MessageOutcome {
+                index: 1,
+                success: false,
+                error: Some("out of funds".to_string()),
+            }
+        );
+        record_outcome(&mut storage, ok()).unwrap();
+
+        // the batch is cleared after its last message
+        assert!(BATCH.may_load(&storage).unwrap().is_none());
+        start_batch(&mut storage, 8, 1, vec![]).unwrap();
+
+        let outcomes = query_outcomes(&storage, 7, None, None).unwrap();
+        assert_eq!(
+            outcomes.iter().map(|o| o.success).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+        let page = query_outcomes(&storage, 7, Some(0), Some(1)).unwrap();
+        assert_eq!(page, vec![failed]);
+        assert!(query_outcomes(&storage, 8, None, None).unwrap().is_empty());
+    }
+}
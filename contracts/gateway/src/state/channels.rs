@@ -0,0 +1,62 @@
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+use mitosis_interface::gateway::IbcChannelInfo;
+
+use crate::errors::ContractError;
+
+use super::operations::{DEFAULT_LIMIT, MAX_LIMIT};
+
+// connection id -> port id of the counterpart gateway
+pub const COUNTERPARTIES_KEY: &str = "ibc_counterparties";
+pub const COUNTERPARTIES: Map<&str, String> = Map::new(COUNTERPARTIES_KEY);
+
+pub const CHANNELS_KEY: &str = "ibc_channels";
+pub const CHANNELS: Map<&str, IbcChannelInfo> = Map::new(CHANNELS_KEY);
+
+pub const SEND_CHANNEL_KEY: &str = "ibc_send_channel";
+pub const SEND_CHANNEL: Item<String> = Item::new(SEND_CHANNEL_KEY);
+
+pub fn assert_counterparty(
+    storage: &dyn Storage,
+    connection_id: &str,
+    port_id: &str,
+) -> Result<(), ContractError> {
+    match COUNTERPARTIES.may_load(storage, connection_id)? {
+        Some(allowed) if allowed == port_id => Ok(()),
+        _ => Err(ContractError::UnknownCounterparty {
+            connection_id: connection_id.to_string(),
+            port_id: port_id.to_string(),
+        }),
+    }
+}
+
+pub fn load_open_channel(
+    storage: &dyn Storage,
+    channel_id: &str,
+) -> Result<IbcChannelInfo, ContractError> {
+    match CHANNELS.may_load(storage, channel_id)? {
+        Some(channel) if channel.open => Ok(channel),
+        _ => Err(ContractError::ChannelNotOpen {
+            channel_id: channel_id.to_string(),
+        }),
+    }
+}
+
+pub fn query_channels(
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<IbcChannelInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    CHANNELS
+        .range(
+            storage,
+            start_after.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|r| r.map(|(_, channel)| channel))
+        .collect()
+}
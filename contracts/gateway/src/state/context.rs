@@ -9,39 +9,73 @@ pub const WITHDRAW: Item<WithdrawInfo> = Item::new(WITHDRAW_KEY);
 
 #[cw_serde]
 pub struct WithdrawInfo {
+    pub to_address: Addr,
+    pub amount: Vec<Coin>,
+    // the operation refunded by the withdrawal, if any
+    #[serde(default)]
+    pub refund_seq: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WithdrawLeg {
     pub to_address: Addr,
     pub amount: Coin,
+    pub refund_seq: Option<u64>,
+    pub last: bool,
 }
 
 pub fn set_withdraw_info(
     storage: &mut dyn Storage,
     to_address: Addr,
-    amount: Coin,
+    amount: Vec<Coin>,
+    refund_seq: Option<u64>,
 ) -> Result<(), ContractError> {
     match WITHDRAW.may_load(storage)? {
         Some(_) => Err(ContractError::WithdrawNotFlushed {}),
         None => {
-            WITHDRAW.save(storage, &WithdrawInfo { to_address, amount })?;
+            WITHDRAW.save(
+                storage,
+                &WithdrawInfo {
+                    to_address,
+                    amount,
+                    refund_seq,
+                },
+            )?;
 
             Ok(())
         }
     }
 }
 
-// get_withdraw_info returns only
-pub fn get_withdraw_info(storage: &mut dyn Storage) -> Result<WithdrawInfo, ContractError> {
-    let result = WITHDRAW.load(storage)?;
+// next_withdraw_leg takes the coin of the leg which just replied, legs reply
+// in the order of the amounts. The context is flushed with the last one.
+pub fn next_withdraw_leg(storage: &mut dyn Storage) -> Result<WithdrawLeg, ContractError> {
+    let mut info = WITHDRAW.load(storage)?;
+    if info.amount.is_empty() {
+        WITHDRAW.remove(storage);
+        return Err(ContractError::WithdrawContextNotFound {});
+    }
 
-    // Flush withdraw info for refresh.
-    WITHDRAW.remove(storage);
+    let amount = info.amount.remove(0);
+    let last = info.amount.is_empty();
+    if last {
+        WITHDRAW.remove(storage);
+    } else {
+        WITHDRAW.save(storage, &info)?;
+    }
 
-    Ok(result)
+    Ok(WithdrawLeg {
+        to_address: info.to_address,
+        amount,
+        refund_seq: info.refund_seq,
+        last,
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use cosmwasm_std::{coin, testing::MockStorage};
+    use cosmwasm_std::{coin, coins, testing::MockStorage};
 
     const ADDR1_VALUE: &str = "addr1";
 
@@ -50,11 +84,11 @@ mod test {
         let mut storage: cosmwasm_std::MemoryStorage = MockStorage::new();
         let addr = Addr::unchecked(ADDR1_VALUE);
 
-        set_withdraw_info(&mut storage, addr.clone(), coin(100000, "uosmo")).unwrap();
+        set_withdraw_info(&mut storage, addr.clone(), coins(100000, "uosmo"), None).unwrap();
         let saved_result = WITHDRAW.load(&storage).unwrap();
 
         assert_eq!(saved_result.to_address, addr);
-        assert_eq!(saved_result.amount, coin(100000, "uosmo"));
+        assert_eq!(saved_result.amount, coins(100000, "uosmo"));
     }
 
     #[test]
@@ -67,40 +101,56 @@ mod test {
                 &mut storage,
                 &WithdrawInfo {
                     to_address: addr.clone(),
-                    amount: coin(100000, "uosmo"),
+                    amount: coins(100000, "uosmo"),
+                    refund_seq: None,
                 },
             )
             .unwrap();
 
-        let result = set_withdraw_info(&mut storage, addr, coin(1000, "uosmo")).unwrap_err();
+        let result = set_withdraw_info(&mut storage, addr, coins(1000, "uosmo"), None).unwrap_err();
         assert!(matches!(result, ContractError::WithdrawNotFlushed {}))
     }
 
     #[test]
-    fn test_get_withdraw_success() {
+    fn test_next_withdraw_leg() {
         let mut storage = MockStorage::new();
         let addr = Addr::unchecked(ADDR1_VALUE);
 
-        WITHDRAW
-            .save(
-                &mut storage,
-                &WithdrawInfo {
-                    to_address: addr.clone(),
-                    amount: coin(100000, "uosmo"),
-                },
-            )
-            .unwrap();
+        set_withdraw_info(
+            &mut storage,
+            addr.clone(),
+            vec![coin(100, "uatom"), coin(200, "uosmo")],
+            Some(3),
+        )
+        .unwrap();
+
+        let first = next_withdraw_leg(&mut storage).unwrap();
+        assert_eq!(
+            first,
+            WithdrawLeg {
+                to_address: addr.clone(),
+                amount: coin(100, "uatom"),
+                refund_seq: Some(3),
+                last: false,
+            }
+        );
+        assert_eq!(
+            WITHDRAW.load(&storage).unwrap().amount,
+            vec![coin(200, "uosmo")]
+        );
 
-        let result = get_withdraw_info(&mut storage).unwrap();
+        let last = next_withdraw_leg(&mut storage).unwrap();
         assert_eq!(
-            result,
-            WithdrawInfo {
+            last,
+            WithdrawLeg {
                 to_address: addr,
-                amount: coin(100000, "uosmo"),
+                amount: coin(200, "uosmo"),
+                refund_seq: Some(3),
+                last: true,
             }
         );
 
-        // remove after withdraw successfully
+        // flushed with the last leg
         let load_err = WITHDRAW.load(&storage).unwrap_err();
         assert!(matches!(load_err, cosmwasm_std::StdError::NotFound { .. }));
     }
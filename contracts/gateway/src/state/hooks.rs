@@ -0,0 +1,134 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Coin, StdResult, Storage};
+use cw_storage_plus::{Item, Map};
+use mitosis_interface::gateway::HookOrigin;
+
+use crate::errors::ContractError;
+
+// set while a hook send is being refunded, read by the withdraw reply
+pub const HOOK_REFUND_KEY: &str = "hook_refund";
+pub const HOOK_REFUND: Item<HookRefund> = Item::new(HOOK_REFUND_KEY);
+
+// transfers waiting for their packet sequence, in dispatch order
+pub const TRANSFER_QUEUE_KEY: &str = "hook_transfer_queue";
+pub const TRANSFER_QUEUE: Item<Vec<RefundTransfer>> = Item::new(TRANSFER_QUEUE_KEY);
+
+// (channel, packet sequence) -> transfer waiting for its acknowledgement
+pub const INFLIGHT_TRANSFERS_KEY: &str = "hook_inflight_transfers";
+pub const INFLIGHT_TRANSFERS: Map<(&str, u64), RefundTransfer> = Map::new(INFLIGHT_TRANSFERS_KEY);
+
+// operation seq -> funds returned by failed transfers, resent by RetryRefund
+pub const FAILED_REFUNDS_KEY: &str = "hook_failed_refunds";
+pub const FAILED_REFUNDS: Map<u64, Vec<Coin>> = Map::new(FAILED_REFUNDS_KEY);
+
+#[cw_serde]
+pub struct HookRefund {
+    pub seq: u64,
+    pub origin: HookOrigin,
+}
+
+#[cw_serde]
+pub struct RefundTransfer {
+    pub seq: u64,
+    pub channel: String,
+    pub amount: Coin,
+}
+
+pub fn take_hook_refund(storage: &mut dyn Storage) -> StdResult<Option<HookRefund>> {
+    let refund = HOOK_REFUND.may_load(storage)?;
+    HOOK_REFUND.remove(storage);
+
+    Ok(refund)
+}
+
+pub fn enqueue_transfers(
+    storage: &mut dyn Storage,
+    transfers: Vec<RefundTransfer>,
+) -> StdResult<()> {
+    let mut queue = TRANSFER_QUEUE.may_load(storage)?.unwrap_or_default();
+    queue.extend(transfers);
+
+    TRANSFER_QUEUE.save(storage, &queue)
+}
+
+pub fn dequeue_transfer(storage: &mut dyn Storage) -> Result<RefundTransfer, ContractError> {
+    let mut queue = TRANSFER_QUEUE.may_load(storage)?.unwrap_or_default();
+    if queue.is_empty() {
+        return Err(ContractError::TransferContextNotFound {});
+    }
+
+    let transfer = queue.remove(0);
+    if queue.is_empty() {
+        TRANSFER_QUEUE.remove(storage);
+    } else {
+        TRANSFER_QUEUE.save(storage, &queue)?;
+    }
+
+    Ok(transfer)
+}
+
+// fail_transfer keeps the funds of a failed transfer for a retry
+pub fn fail_transfer(storage: &mut dyn Storage, transfer: &RefundTransfer) -> StdResult<()> {
+    let mut failed = FAILED_REFUNDS
+        .may_load(storage, transfer.seq)?
+        .unwrap_or_default();
+    match failed.iter_mut().find(|c| c.denom == transfer.amount.denom) {
+        Some(coin) => coin.amount += transfer.amount.amount,
+        None => failed.push(transfer.amount.clone()),
+    }
+
+    FAILED_REFUNDS.save(storage, transfer.seq, &failed)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{coin, testing::MockStorage};
+
+    use super::*;
+
+    fn transfer(seq: u64, amount: Coin) -> RefundTransfer {
+        RefundTransfer {
+            seq,
+            channel: "channel-0".to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_transfer_queue() {
+        let mut storage = MockStorage::new();
+
+        enqueue_transfers(
+            &mut storage,
+            vec![transfer(0, coin(1, "uosmo")), transfer(0, coin(2, "uatom"))],
+        )
+        .unwrap();
+
+        assert_eq!(
+            dequeue_transfer(&mut storage).unwrap(),
+            transfer(0, coin(1, "uosmo"))
+        );
+        assert_eq!(
+            dequeue_transfer(&mut storage).unwrap(),
+            transfer(0, coin(2, "uatom"))
+        );
+        assert!(matches!(
+            dequeue_transfer(&mut storage).unwrap_err(),
+            ContractError::TransferContextNotFound {}
+        ));
+    }
+
+    #[test]
+    fn test_fail_transfer() {
+        let mut storage = MockStorage::new();
+
+        fail_transfer(&mut storage, &transfer(0, coin(1, "uosmo"))).unwrap();
+        fail_transfer(&mut storage, &transfer(0, coin(2, "uosmo"))).unwrap();
+        fail_transfer(&mut storage, &transfer(0, coin(3, "uatom"))).unwrap();
+
+        assert_eq!(
+            FAILED_REFUNDS.load(&storage, 0).unwrap(),
+            vec![coin(3, "uosmo"), coin(3, "uatom")]
+        );
+    }
+}
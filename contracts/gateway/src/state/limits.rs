@@ -0,0 +1,413 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Coin, Decimal, Order, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Map};
+use mitosis_interface::gateway::{OutflowWeight, RateLimit, RateLimitResponse};
+
+// the global cap is stored next to the denom caps under a key no denom can take.
+// It counts outflows in a common unit, see OUTFLOW_WEIGHTS.
+pub const GLOBAL_LIMIT: &str = "*";
+
+// number of slots a window is split into. Outflows are summed per slot, so an
+// outflow counts against its cap for the window plus at most one slot.
+pub const OUTFLOW_SLOTS: u64 = 24;
+
+pub const RATE_LIMITS_KEY: &str = "rate_limits";
+pub const RATE_LIMITS: Map<&str, RateLimit> = Map::new(RATE_LIMITS_KEY);
+
+// (denom, slot) -> amount sent in that slot
+pub const OUTFLOWS_KEY: &str = "outflows";
+pub const OUTFLOWS: Map<(&str, u64), Uint128> = Map::new(OUTFLOWS_KEY);
+
+// denom -> units of the global cap per unit of the denom. Denoms differ in
+// decimals, so only denoms with a weight count against the global cap.
+pub const OUTFLOW_WEIGHTS_KEY: &str = "outflow_weights";
+pub const OUTFLOW_WEIGHTS: Map<&str, Decimal> = Map::new(OUTFLOW_WEIGHTS_KEY);
+
+fn slot_len(limit: &RateLimit) -> u64 {
+    limit.window.div_ceil(OUTFLOW_SLOTS).max(1)
+}
+
+// first slot that overlaps the window ending at now
+fn first_slot(limit: &RateLimit, now: u64) -> u64 {
+    now.saturating_sub(limit.window) / slot_len(limit)
+}
+
+fn used(storage: &dyn Storage, denom: &str, limit: &RateLimit, now: u64) -> StdResult<Uint128> {
+    OUTFLOWS
+        .prefix(denom)
+        .range(
+            storage,
+            Some(Bound::inclusive(first_slot(limit, now))),
+            None,
+            Order::Ascending,
+        )
+        .map(|r| r.map(|(_, amount)| amount))
+        .sum()
+}
+
+fn clear_outflows(storage: &mut dyn Storage, denom: &str, before: Option<u64>) -> StdResult<()> {
+    let slots = OUTFLOWS
+        .prefix(denom)
+        .keys(
+            storage,
+            None,
+            before.map(Bound::exclusive),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for slot in slots {
+        OUTFLOWS.remove(storage, (denom, slot));
+    }
+
+    Ok(())
+}
+
+// totals per cap key. The weighted amounts are added up under the global key,
+// rounded up so that dust cannot slip past the global cap.
+fn totals<'a>(storage: &dyn Storage, amounts: &'a [Coin]) -> StdResult<BTreeMap<&'a str, Uint128>> {
+    let mut totals = BTreeMap::new();
+    for coin in amounts {
+        *totals.entry(coin.denom.as_str()).or_default() += coin.amount;
+
+        if let Some(weight) = OUTFLOW_WEIGHTS.may_load(storage, &coin.denom)? {
+            let weighted = coin
+                .amount
+                .checked_mul_ceil(weight)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            *totals.entry(GLOBAL_LIMIT).or_default() += weighted;
+        }
+    }
+
+    Ok(totals)
+}
+
+// check_outflows returns the key of the first cap the amounts would exceed.
+pub fn check_outflows(
+    storage: &dyn Storage,
+    amounts: &[Coin],
+    now: u64,
+) -> StdResult<Option<String>> {
+    for (denom, amount) in totals(storage, amounts)? {
+        let Some(limit) = RATE_LIMITS.may_load(storage, denom)? else {
+            continue;
+        };
+
+        let used = used(storage, denom, &limit, now)?;
+        if used.checked_add(amount)? > limit.max_amount {
+            return Ok(Some(denom.to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+// record_outflows counts the amounts against the caps they fall under and prunes
+// slots that left the window.
+pub fn record_outflows(storage: &mut dyn Storage, amounts: &[Coin], now: u64) -> StdResult<()> {
+    for (denom, amount) in totals(storage, amounts)? {
+        let Some(limit) = RATE_LIMITS.may_load(storage, denom)? else {
+            continue;
+        };
+
+        clear_outflows(storage, denom, Some(first_slot(&limit, now)))?;
+
+        let slot = now / slot_len(&limit);
+        OUTFLOWS.update(storage, (denom, slot), |sent| -> StdResult<_> {
+            Ok(sent.unwrap_or_default().checked_add(amount)?)
+        })?;
+    }
+
+    Ok(())
+}
+
+// release_outflows takes back outflows recorded at now which were not sent
+// after all.
+pub fn release_outflows(storage: &mut dyn Storage, amounts: &[Coin], now: u64) -> StdResult<()> {
+    for (denom, amount) in totals(storage, amounts)? {
+        let Some(limit) = RATE_LIMITS.may_load(storage, denom)? else {
+            continue;
+        };
+
+        let key = (denom, now / slot_len(&limit));
+        let sent = OUTFLOWS
+            .may_load(storage, key)?
+            .unwrap_or_default()
+            .saturating_sub(amount);
+        if sent.is_zero() {
+            OUTFLOWS.remove(storage, key);
+        } else {
+            OUTFLOWS.save(storage, key, &sent)?;
+        }
+    }
+
+    Ok(())
+}
+
+// set_rate_limit drops the recorded outflows when the cap is removed or its
+// window changes, as the slots no longer line up.
+pub fn set_rate_limit(
+    storage: &mut dyn Storage,
+    denom: &str,
+    limit: Option<RateLimit>,
+) -> StdResult<()> {
+    let current = RATE_LIMITS.may_load(storage, denom)?;
+    if current.map(|c| c.window) != limit.as_ref().map(|l| l.window) {
+        clear_outflows(storage, denom, None)?;
+    }
+
+    match limit {
+        Some(limit) => RATE_LIMITS.save(storage, denom, &limit),
+        None => {
+            RATE_LIMITS.remove(storage, denom);
+            Ok(())
+        }
+    }
+}
+
+pub fn set_outflow_weight(
+    storage: &mut dyn Storage,
+    denom: &str,
+    weight: Option<Decimal>,
+) -> StdResult<()> {
+    match weight {
+        Some(weight) => OUTFLOW_WEIGHTS.save(storage, denom, &weight),
+        None => {
+            OUTFLOW_WEIGHTS.remove(storage, denom);
+            Ok(())
+        }
+    }
+}
+
+pub fn query_outflow_weights(storage: &dyn Storage) -> StdResult<Vec<OutflowWeight>> {
+    OUTFLOW_WEIGHTS
+        .range(storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(denom, weight)| OutflowWeight { denom, weight }))
+        .collect()
+}
+
+fn to_response(
+    storage: &dyn Storage,
+    denom: String,
+    limit: RateLimit,
+    now: u64,
+) -> StdResult<RateLimitResponse> {
+    let used = used(storage, &denom, &limit, now)?;
+
+    Ok(RateLimitResponse {
+        denom,
+        remaining: limit.max_amount.saturating_sub(used),
+        limit,
+        used,
+    })
+}
+
+pub fn query_rate_limit(
+    storage: &dyn Storage,
+    denom: String,
+    now: u64,
+) -> StdResult<RateLimitResponse> {
+    let limit = RATE_LIMITS.load(storage, &denom)?;
+
+    to_response(storage, denom, limit, now)
+}
+
+pub fn query_rate_limits(storage: &dyn Storage, now: u64) -> StdResult<Vec<RateLimitResponse>> {
+    RATE_LIMITS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .map(|(denom, limit)| to_response(storage, denom, limit, now))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{coin, testing::MockStorage};
+
+    use super::*;
+
+    // 24 slots of 5 seconds
+    fn mock_limit(max_amount: u128) -> RateLimit {
+        RateLimit {
+            max_amount: Uint128::new(max_amount),
+            window: 120,
+        }
+    }
+
+    fn slots(storage: &dyn Storage, denom: &str) -> Vec<(u64, Uint128)> {
+        OUTFLOWS
+            .prefix(denom)
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let mut storage = MockStorage::new();
+        set_rate_limit(&mut storage, "uosmo", Some(mock_limit(1000))).unwrap();
+
+        record_outflows(&mut storage, &[coin(600, "uosmo")], 0).unwrap();
+        assert_eq!(
+            check_outflows(&storage, &[coin(500, "uosmo")], 50).unwrap(),
+            Some("uosmo".to_string())
+        );
+        assert_eq!(
+            check_outflows(&storage, &[coin(400, "uosmo")], 50).unwrap(),
+            None
+        );
+
+        // the first outflow leaves the window once its slot does
+        record_outflows(&mut storage, &[coin(400, "uosmo")], 50).unwrap();
+        assert_eq!(
+            check_outflows(&storage, &[coin(600, "uosmo")], 120).unwrap(),
+            Some("uosmo".to_string())
+        );
+        assert_eq!(
+            check_outflows(&storage, &[coin(600, "uosmo")], 125).unwrap(),
+            None
+        );
+        assert_eq!(
+            query_rate_limit(&storage, "uosmo".to_string(), 125).unwrap(),
+            RateLimitResponse {
+                denom: "uosmo".to_string(),
+                limit: mock_limit(1000),
+                used: Uint128::new(400),
+                remaining: Uint128::new(600),
+            }
+        );
+
+        // outflows within a slot are summed, expired slots are pruned on write
+        record_outflows(&mut storage, &[coin(1, "uosmo")], 171).unwrap();
+        record_outflows(&mut storage, &[coin(2, "uosmo")], 174).unwrap();
+        assert_eq!(
+            slots(&storage, "uosmo"),
+            vec![(10, Uint128::new(400)), (34, Uint128::new(3))]
+        );
+        record_outflows(&mut storage, &[coin(1, "uosmo")], 175).unwrap();
+        assert_eq!(
+            slots(&storage, "uosmo"),
+            vec![(34, Uint128::new(3)), (35, Uint128::new(1))]
+        );
+    }
+
+    #[test]
+    fn test_global_limit() {
+        let mut storage = MockStorage::new();
+        set_rate_limit(&mut storage, GLOBAL_LIMIT, Some(mock_limit(100))).unwrap();
+        set_rate_limit(&mut storage, "uatom", Some(mock_limit(50_000_000))).unwrap();
+
+        // a 6 decimal and an 18 decimal denom counted in whole tokens, uosmo
+        // has no weight and does not count
+        set_outflow_weight(
+            &mut storage,
+            "uatom",
+            Some(Decimal::from_ratio(1u128, 1_000_000u128)),
+        )
+        .unwrap();
+        set_outflow_weight(
+            &mut storage,
+            "aevmos",
+            Some(Decimal::from_ratio(1u128, 10u128.pow(18))),
+        )
+        .unwrap();
+
+        record_outflows(
+            &mut storage,
+            &[
+                coin(40_000_000, "uatom"),
+                coin(50 * 10u128.pow(18), "aevmos"),
+                coin(10u128.pow(12), "uosmo"),
+            ],
+            0,
+        )
+        .unwrap();
+        assert_eq!(slots(&storage, GLOBAL_LIMIT), vec![(0, Uint128::new(90))]);
+        assert_eq!(
+            query_rate_limit(&storage, GLOBAL_LIMIT.to_string(), 0)
+                .unwrap()
+                .remaining,
+            Uint128::new(10)
+        );
+
+        // the denom cap holds, the global one does not
+        assert_eq!(
+            check_outflows(&storage, &[coin(10_000_001, "uatom")], 0).unwrap(),
+            Some(GLOBAL_LIMIT.to_string())
+        );
+        assert_eq!(
+            check_outflows(&storage, &[coin(10_000_000, "uatom")], 0).unwrap(),
+            None
+        );
+
+        // dust is rounded up
+        assert_eq!(
+            check_outflows(&storage, &[coin(10_000_000, "uatom"), coin(1, "aevmos")], 0).unwrap(),
+            Some(GLOBAL_LIMIT.to_string())
+        );
+
+        release_outflows(&mut storage, &[coin(50 * 10u128.pow(18), "aevmos")], 0).unwrap();
+        assert_eq!(slots(&storage, GLOBAL_LIMIT), vec![(0, Uint128::new(40))]);
+        assert_eq!(
+            query_outflow_weights(&storage).unwrap(),
+            vec![
+                OutflowWeight {
+                    denom: "aevmos".to_string(),
+                    weight: Decimal::from_ratio(1u128, 10u128.pow(18)),
+                },
+                OutflowWeight {
+                    denom: "uatom".to_string(),
+                    weight: Decimal::from_ratio(1u128, 1_000_000u128),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_per_denom_limit() {
+        let mut storage = MockStorage::new();
+        set_rate_limit(&mut storage, "uatom", Some(mock_limit(100))).unwrap();
+
+        // uosmo has no cap and is not recorded
+        record_outflows(&mut storage, &[coin(800, "uosmo"), coin(60, "uatom")], 0).unwrap();
+        assert!(slots(&storage, "uosmo").is_empty());
+        assert_eq!(
+            check_outflows(&storage, &[coin(20, "uatom"), coin(21, "uatom")], 0).unwrap(),
+            Some("uatom".to_string())
+        );
+        assert_eq!(
+            check_outflows(&storage, &[coin(40, "uatom"), coin(10000, "uosmo")], 0).unwrap(),
+            None
+        );
+
+        let limits = query_rate_limits(&storage, 0).unwrap();
+        assert_eq!(
+            limits
+                .iter()
+                .map(|l| (l.denom.clone(), l.remaining.u128()))
+                .collect::<Vec<_>>(),
+            vec![("uatom".to_string(), 40)]
+        );
+
+        // a new max_amount keeps the history, a new window drops it
+        set_rate_limit(&mut storage, "uatom", Some(mock_limit(200))).unwrap();
+        assert_eq!(slots(&storage, "uatom").len(), 1);
+        set_rate_limit(
+            &mut storage,
+            "uatom",
+            Some(RateLimit {
+                window: 240,
+                ..mock_limit(200)
+            }),
+        )
+        .unwrap();
+        assert!(slots(&storage, "uatom").is_empty());
+
+        // removing a cap drops its history
+        record_outflows(&mut storage, &[coin(60, "uatom")], 0).unwrap();
+        set_rate_limit(&mut storage, "uatom", None).unwrap();
+        assert!(slots(&storage, "uatom").is_empty());
+        assert!(query_rate_limit(&storage, "uatom".to_string(), 0).is_err());
+    }
+}
@@ -1,10 +1,22 @@
+pub mod batch;
+pub mod channels;
 pub mod context;
+pub mod hooks;
+pub mod limits;
+pub mod operations;
+pub mod ops;
+pub mod policy;
+pub mod rbac;
+pub mod refunds;
+pub mod requests;
+pub mod roots;
+pub mod signers;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Env, HexBinary, StdResult, Storage};
+use cosmwasm_std::{Addr, Env, StdResult, Storage};
 use cw_storage_plus::Item;
 
-use crate::errors::ContractError;
+use crate::{errors::ContractError, verify::address_prefix};
 
 pub const OWNER_KEY: &str = "owner";
 pub const OWNER: Item<Addr> = Item::new(OWNER_KEY);
@@ -18,8 +30,11 @@ pub const LIQUIDITY_MANAGER: Item<Addr> = Item::new(LIQUIDITY_MANAGER_KEY);
 pub const DENOM_MANAGER_KEY: &str = "denom_manager";
 pub const DENOM_MANAGER: Item<Addr> = Item::new(DENOM_MANAGER_KEY);
 
-pub const PUBLIC_KEY_KEY: &str = "public_key";
-pub const PUBLIC_KEY: Item<HexBinary> = Item::new(PUBLIC_KEY_KEY);
+pub const OPERATION_TIMEOUT_KEY: &str = "operation_timeout";
+pub const OPERATION_TIMEOUT: Item<u64> = Item::new(OPERATION_TIMEOUT_KEY);
+
+pub const ADDRESS_PREFIX_KEY: &str = "address_prefix";
+pub const ADDRESS_PREFIX: Item<String> = Item::new(ADDRESS_PREFIX_KEY);
 
 #[cw_serde]
 #[derive(Default)]
@@ -38,6 +53,15 @@ pub fn assert_owned(storage: &dyn Storage, sender: Addr) -> Result<(), ContractE
     Ok(())
 }
 
+// load_address_prefix falls back to the prefix of the contract address for
+// gateways instantiated before the prefix was configurable.
+pub fn load_address_prefix(storage: &dyn Storage, env: &Env) -> Result<String, ContractError> {
+    match ADDRESS_PREFIX.may_load(storage)? {
+        Some(prefix) => Ok(prefix),
+        None => address_prefix(env.contract.address.as_str()),
+    }
+}
+
 impl PauseInfo {
     pub fn refresh(self, storage: &mut dyn Storage, env: &Env) -> StdResult<Self> {
         if self.paused {
@@ -0,0 +1,376 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, MultiIndex};
+use mitosis_interface::gateway::{HookOrigin, OperationResponse, OperationStatus};
+
+use crate::errors::ContractError;
+
+pub const OPERATIONS_KEY: &str = "operations";
+pub const OPERATIONS_SEQ_KEY: &str = "operation_seq";
+pub const OPERATIONS_SEQ: Item<u64> = Item::new(OPERATIONS_SEQ_KEY);
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+
+#[cw_serde]
+pub struct OperationInfo {
+    pub seq: u64,
+    pub sender: Addr,
+    pub amounts: Vec<Coin>,
+    // denom manager aliases of amounts, in the same order
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub op_id: u64,
+    pub op_args: Vec<String>,
+    pub height: u64,
+    pub deadline: u64,
+    pub status: OperationStatus,
+    pub origin: Option<HookOrigin>,
+}
+
+impl From<OperationInfo> for OperationResponse {
+    fn from(op: OperationInfo) -> Self {
+        Self {
+            seq: op.seq,
+            sender: op.sender,
+            amounts: op.amounts,
+            aliases: op.aliases,
+            op_id: op.op_id,
+            op_args: op.op_args,
+            height: op.height,
+            deadline: op.deadline,
+            status: op.status,
+            origin: op.origin,
+        }
+    }
+}
+
+pub fn status_key(status: OperationStatus) -> String {
+    match status {
+        OperationStatus::Pending => "pending",
+        OperationStatus::Relayed => "relayed",
+        OperationStatus::Finalized => "finalized",
+        OperationStatus::Refunded => "refunded",
+    }
+    .to_string()
+}
+
+pub struct OperationsIndexes<'a> {
+    pub sender: MultiIndex<'a, Addr, OperationInfo, u64>,
+    pub status: MultiIndex<'a, String, OperationInfo, u64>,
+}
+
+impl<'a> IndexList<OperationInfo> for OperationsIndexes<'a> {
+    fn get_indexes(
+        &'_ self,
+    ) -> Box<dyn Iterator<Item = &'_ dyn cw_storage_plus::Index<OperationInfo>> + '_> {
+        let v: Vec<&dyn Index<OperationInfo>> = vec![&self.sender, &self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn operations<'a>() -> IndexedMap<'a, u64, OperationInfo, OperationsIndexes<'a>> {
+    let indexes = OperationsIndexes {
+        sender: MultiIndex::new(|_, o| o.sender.clone(), OPERATIONS_KEY, "OPERATION_SENDER"),
+        status: MultiIndex::new(
+            |_, o| status_key(o.status),
+            OPERATIONS_KEY,
+            "OPERATION_STATUS",
+        ),
+    };
+
+    IndexedMap::new(OPERATIONS_KEY, indexes)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn record_operation(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    amounts: Vec<Coin>,
+    aliases: Vec<String>,
+    op_id: u64,
+    op_args: Vec<String>,
+    height: u64,
+    deadline: u64,
+    origin: Option<HookOrigin>,
+) -> StdResult<OperationInfo> {
+    let seq = OPERATIONS_SEQ.may_load(storage)?.unwrap_or_default();
+    let op = OperationInfo {
+        seq,
+        sender,
+        amounts,
+        aliases,
+        op_id,
+        op_args,
+        height,
+        deadline,
+        status: OperationStatus::Pending,
+        origin,
+    };
+
+    operations().save(storage, seq, &op)?;
+    OPERATIONS_SEQ.save(storage, &(seq + 1))?;
+
+    Ok(op)
+}
+
+// Pending -> (Relayed ->) Finalized. Only a pending operation can be refunded.
+fn can_transition(from: OperationStatus, to: OperationStatus) -> bool {
+    use OperationStatus::*;
+
+    matches!(
+        (from, to),
+        (Pending, Relayed) | (Pending, Finalized) | (Relayed, Finalized) | (Pending, Refunded)
+    )
+}
+
+// update_status also enforces the deadline of pending operations: they can be
+// acknowledged only before it and refunded only after it.
+pub fn update_status(
+    storage: &mut dyn Storage,
+    seq: u64,
+    status: OperationStatus,
+    now: u64,
+) -> Result<OperationInfo, ContractError> {
+    let mut op = operations().load(storage, seq)?;
+
+    if !can_transition(op.status, status) {
+        return Err(ContractError::InvalidStatusTransition {
+            from: status_key(op.status),
+            to: status_key(status),
+        });
+    }
+
+    if op.status == OperationStatus::Pending {
+        let expired = now >= op.deadline;
+
+        match status {
+            OperationStatus::Refunded if !expired => {
+                return Err(ContractError::OperationNotExpired { seq })
+            }
+            OperationStatus::Relayed | OperationStatus::Finalized if expired => {
+                return Err(ContractError::OperationExpired { seq })
+            }
+            _ => {}
+        }
+    }
+
+    op.status = status;
+    operations().save(storage, seq, &op)?;
+
+    Ok(op)
+}
+
+fn page(start_after: Option<u64>, limit: Option<u32>) -> (Option<Bound<'static, u64>>, usize) {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    (start_after.map(Bound::exclusive), limit)
+}
+
+pub fn query_operation(storage: &dyn Storage, seq: u64) -> StdResult<OperationInfo> {
+    operations().load(storage, seq)
+}
+
+pub fn query_operations(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OperationInfo>> {
+    let (start, limit) = page(start_after, limit);
+
+    operations()
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|r| r.map(|(_, op)| op))
+        .collect()
+}
+
+pub fn query_operations_by_sender(
+    storage: &dyn Storage,
+    sender: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OperationInfo>> {
+    let (start, limit) = page(start_after, limit);
+
+    operations()
+        .idx
+        .sender
+        .prefix(sender)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|r| r.map(|(_, op)| op))
+        .collect()
+}
+
+pub fn query_operations_by_status(
+    storage: &dyn Storage,
+    status: OperationStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OperationInfo>> {
+    let (start, limit) = page(start_after, limit);
+
+    operations()
+        .idx
+        .status
+        .prefix(status_key(status))
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|r| r.map(|(_, op)| op))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{coin, testing::MockStorage};
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+    const DEADLINE: u64 = 1000;
+
+    fn record(storage: &mut dyn Storage, sender: &str) -> OperationInfo {
+        record_operation(
+            storage,
+            Addr::unchecked(sender),
+            vec![coin(100, "uosmo")],
+            vec!["osmo".to_string()],
+            1,
+            vec!["arg".to_string()],
+            12345,
+            DEADLINE,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_operation() {
+        let mut storage = MockStorage::new();
+
+        let first = record(&mut storage, ADDR1);
+        let second = record(&mut storage, ADDR2);
+
+        assert_eq!(first.seq, 0);
+        assert_eq!(second.seq, 1);
+        assert_eq!(first.status, OperationStatus::Pending);
+        assert_eq!(query_operation(&storage, 1).unwrap(), second);
+    }
+
+    #[test]
+    fn test_update_status() {
+        let mut storage = MockStorage::new();
+
+        record(&mut storage, ADDR1);
+
+        let relayed = update_status(&mut storage, 0, OperationStatus::Relayed, 0).unwrap();
+        assert_eq!(relayed.status, OperationStatus::Relayed);
+
+        let backwards = update_status(&mut storage, 0, OperationStatus::Pending, 0).unwrap_err();
+        assert!(matches!(
+            backwards,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+
+        let refund_relayed =
+            update_status(&mut storage, 0, OperationStatus::Refunded, DEADLINE).unwrap_err();
+        assert!(matches!(
+            refund_relayed,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+
+        update_status(&mut storage, 0, OperationStatus::Finalized, DEADLINE).unwrap();
+        let final_status =
+            update_status(&mut storage, 0, OperationStatus::Refunded, DEADLINE).unwrap_err();
+        assert!(matches!(
+            final_status,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+
+        let not_found = update_status(&mut storage, 1, OperationStatus::Relayed, 0).unwrap_err();
+        assert!(matches!(not_found, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn test_update_status_deadline() {
+        let mut storage = MockStorage::new();
+
+        record(&mut storage, ADDR1);
+        record(&mut storage, ADDR1);
+
+        let early_refund =
+            update_status(&mut storage, 0, OperationStatus::Refunded, DEADLINE - 1).unwrap_err();
+        assert!(matches!(
+            early_refund,
+            ContractError::OperationNotExpired { seq: 0 }
+        ));
+
+        let late_ack =
+            update_status(&mut storage, 0, OperationStatus::Relayed, DEADLINE).unwrap_err();
+        assert!(matches!(
+            late_ack,
+            ContractError::OperationExpired { seq: 0 }
+        ));
+
+        let refunded = update_status(&mut storage, 0, OperationStatus::Refunded, DEADLINE).unwrap();
+        assert_eq!(refunded.status, OperationStatus::Refunded);
+
+        // acknowledged operations are not refundable, even after the deadline
+        update_status(&mut storage, 1, OperationStatus::Relayed, DEADLINE - 1).unwrap();
+        let refund_relayed =
+            update_status(&mut storage, 1, OperationStatus::Refunded, DEADLINE).unwrap_err();
+        assert!(matches!(
+            refund_relayed,
+            ContractError::InvalidStatusTransition { .. }
+        ));
+        update_status(&mut storage, 1, OperationStatus::Finalized, DEADLINE).unwrap();
+    }
+
+    #[test]
+    fn test_query_operations() {
+        let mut storage = MockStorage::new();
+
+        for i in 0..5 {
+            record(&mut storage, if i % 2 == 0 { ADDR1 } else { ADDR2 });
+        }
+        update_status(&mut storage, 1, OperationStatus::Relayed, 0).unwrap();
+        update_status(&mut storage, 4, OperationStatus::Relayed, 0).unwrap();
+
+        let seqs = |ops: Vec<OperationInfo>| ops.into_iter().map(|op| op.seq).collect::<Vec<_>>();
+
+        assert_eq!(
+            seqs(query_operations(&storage, None, Some(2)).unwrap()),
+            vec![0, 1]
+        );
+        assert_eq!(
+            seqs(query_operations(&storage, Some(1), None).unwrap()),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            seqs(query_operations_by_sender(&storage, Addr::unchecked(ADDR1), None, None).unwrap()),
+            vec![0, 2, 4]
+        );
+        assert_eq!(
+            seqs(
+                query_operations_by_sender(&storage, Addr::unchecked(ADDR1), Some(0), Some(1))
+                    .unwrap()
+            ),
+            vec![2]
+        );
+        assert_eq!(
+            seqs(
+                query_operations_by_status(&storage, OperationStatus::Pending, None, None).unwrap()
+            ),
+            vec![0, 2, 3]
+        );
+        assert_eq!(
+            seqs(
+                query_operations_by_status(&storage, OperationStatus::Relayed, Some(1), None)
+                    .unwrap()
+            ),
+            vec![4]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::str::FromStr;
+
+use bech32::FromBase32;
+use cosmwasm_std::{HexBinary, Order, StdResult, Storage, Uint256};
+use cw_storage_plus::{Bound, Map};
+use mitosis_interface::gateway::{OpArgType, OpSpec};
+
+use crate::errors::ContractError;
+
+use super::operations::{DEFAULT_LIMIT, MAX_LIMIT};
+
+pub const OP_SPECS_KEY: &str = "op_specs";
+pub const OP_SPECS: Map<u64, OpSpec> = Map::new(OP_SPECS_KEY);
+
+pub fn arg_type_name(arg_type: OpArgType) -> String {
+    match arg_type {
+        OpArgType::Address => "address",
+        OpArgType::Uint => "uint",
+        OpArgType::Denom => "denom",
+        OpArgType::Bytes => "bytes",
+    }
+    .to_string()
+}
+
+fn strip_hex_prefix(value: &str) -> &str {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
+// same rule as the cosmos-sdk: [a-zA-Z][a-zA-Z0-9/:._-]{2,127}
+pub(crate) fn is_valid_denom(value: &str) -> bool {
+    let mut chars = value.chars();
+
+    (3..=128).contains(&value.len())
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+}
+
+// addresses usually belong to a remote chain, so any bech32 prefix is accepted
+// as long as it holds a 20-byte account or a 32-byte contract address.
+fn is_valid_bech32(value: &str) -> bool {
+    match bech32::decode(value) {
+        Ok((_, data, bech32::Variant::Bech32)) => {
+            Vec::<u8>::from_base32(&data).is_ok_and(|data| matches!(data.len(), 20 | 32))
+        }
+        _ => false,
+    }
+}
+
+fn is_valid_arg(arg_type: OpArgType, value: &str) -> bool {
+    match arg_type {
+        OpArgType::Address => match value.strip_prefix("0x") {
+            Some(hex) => hex.len() == 40 && HexBinary::from_hex(hex).is_ok(),
+            None => is_valid_bech32(value),
+        },
+        OpArgType::Uint => {
+            !value.is_empty()
+                && value.chars().all(|c| c.is_ascii_digit())
+                && Uint256::from_str(value).is_ok()
+        }
+        OpArgType::Denom => is_valid_denom(value),
+        OpArgType::Bytes => {
+            let hex = strip_hex_prefix(value);
+            !hex.is_empty() && HexBinary::from_hex(hex).is_ok()
+        }
+    }
+}
+
+// load_enabled_op returns the spec of op_id, failing for unknown or disabled ops.
+pub fn load_enabled_op(storage: &dyn Storage, op_id: u64) -> Result<OpSpec, ContractError> {
+    let spec = OP_SPECS
+        .may_load(storage, op_id)?
+        .ok_or(ContractError::UnknownOp { op_id })?;
+
+    if !spec.enabled {
+        return Err(ContractError::OpDisabled { op_id });
+    }
+
+    Ok(spec)
+}
+
+pub fn validate_op_args(spec: &OpSpec, args: &[String]) -> Result<(), ContractError> {
+    if spec.args.len() != args.len() {
+        return Err(ContractError::WrongArgCount {
+            op_id: spec.op_id,
+            expected: spec.args.len(),
+            actual: args.len(),
+        });
+    }
+
+    for (index, (&arg_type, value)) in spec.args.iter().zip(args).enumerate() {
+        if !is_valid_arg(arg_type, value) {
+            return Err(ContractError::InvalidOpArg {
+                op_id: spec.op_id,
+                index,
+                expected: arg_type_name(arg_type),
+                value: value.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn query_ops(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OpSpec>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    OP_SPECS
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|r| r.map(|(_, spec)| spec))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::mock_dependencies;
+
+    use super::*;
+
+    const ADDR: &str = "osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2";
+
+    fn spec(args: Vec<OpArgType>) -> OpSpec {
+        OpSpec {
+            op_id: 1,
+            name: "transfer".to_string(),
+            args,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_validate_op_args() {
+        let spec = spec(vec![
+            OpArgType::Address,
+            OpArgType::Uint,
+            OpArgType::Denom,
+            OpArgType::Bytes,
+        ]);
+        let args = |values: [&str; 4]| values.map(String::from).to_vec();
+
+        // any bech32 prefix, holding an account or a contract address
+        for addr in [
+            ADDR,
+            "inj1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnflx7rp",
+            "osmo1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0snqss8g",
+        ] {
+            validate_op_args(&spec, &args([addr, "1", "uosmo", "00"])).unwrap();
+        }
+
+        validate_op_args(&spec, &args([ADDR, "100", "factory/addr1/uosmo", "0xdead"])).unwrap();
+        validate_op_args(
+            &spec,
+            &args([
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+                "0",
+                "uosmo",
+                "beef",
+            ]),
+        )
+        .unwrap();
+
+        let invalid = [
+            (0, args(["", "100", "uosmo", "00"])),
+            (0, args(["0x1234", "100", "uosmo", "00"])),
+            (0, args(["addr1", "100", "uosmo", "00"])),
+            // 19 bytes
+            (
+                0,
+                args([
+                    "osmo1qqqsyqcyq5rqwzqfpg9scrgwpugpzysafk9ry",
+                    "100",
+                    "uosmo",
+                    "00",
+                ]),
+            ),
+            // wrong checksum
+            (
+                0,
+                args([
+                    "osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e3",
+                    "100",
+                    "uosmo",
+                    "00",
+                ]),
+            ),
+            (1, args([ADDR, "-1", "uosmo", "00"])),
+            (1, args([ADDR, "1.5", "uosmo", "00"])),
+            (
+                1,
+                args([
+                    ADDR,
+                    "115792089237316195423570985008687907853269984665640564039457584007913129639936",
+                    "uosmo",
+                    "00",
+                ]),
+            ),
+            (2, args([ADDR, "100", "u", "00"])),
+            (2, args([ADDR, "100", "1osmo", "00"])),
+            (3, args([ADDR, "100", "uosmo", "0xzz"])),
+            (3, args([ADDR, "100", "uosmo", ""])),
+            (3, args([ADDR, "100", "uosmo", "0x"])),
+        ];
+        for (expected_index, args) in invalid {
+            let err = validate_op_args(&spec, &args).unwrap_err();
+            assert!(
+                matches!(err, ContractError::InvalidOpArg { index, .. } if index == expected_index),
+                "{args:?}"
+            );
+        }
+
+        let count = validate_op_args(&spec, &args([ADDR, "100", "uosmo", "00"])[..3]).unwrap_err();
+        assert!(matches!(
+            count,
+            ContractError::WrongArgCount {
+                op_id: 1,
+                expected: 4,
+                actual: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn test_load_enabled_op() {
+        let mut deps = mock_dependencies();
+
+        let unknown = load_enabled_op(&deps.storage, 1).unwrap_err();
+        assert!(matches!(unknown, ContractError::UnknownOp { op_id: 1 }));
+
+        let mut disabled = spec(vec![]);
+        disabled.enabled = false;
+        OP_SPECS.save(&mut deps.storage, 1, &disabled).unwrap();
+        let err = load_enabled_op(&deps.storage, 1).unwrap_err();
+        assert!(matches!(err, ContractError::OpDisabled { op_id: 1 }));
+
+        OP_SPECS.save(&mut deps.storage, 1, &spec(vec![])).unwrap();
+        assert_eq!(load_enabled_op(&deps.storage, 1).unwrap(), spec(vec![]));
+    }
+}
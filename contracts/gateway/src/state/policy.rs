@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{from_slice, Api, Binary, CosmosMsg, WasmMsg};
+use cw_storage_plus::Item;
+use mitosis_interface::gateway::{ExecutePolicy, MsgKind};
+use serde::de::IgnoredAny;
+
+use crate::errors::ContractError;
+
+pub const EXECUTE_POLICY_KEY: &str = "execute_policy";
+pub const EXECUTE_POLICY: Item<ExecutePolicy> = Item::new(EXECUTE_POLICY_KEY);
+
+pub fn validate_policy(api: &dyn Api, policy: &ExecutePolicy) -> Result<(), ContractError> {
+    for target in policy.wasm_targets.iter() {
+        api.addr_validate(target.contract.as_str())?;
+    }
+
+    if let Some(type_url) = policy
+        .stargate_type_urls
+        .iter()
+        .find(|type_url| !type_url.starts_with('/'))
+    {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("invalid stargate type url: {type_url}"),
+        });
+    }
+
+    Ok(())
+}
+
+fn msg_kind(msg: &CosmosMsg) -> Option<MsgKind> {
+    match msg {
+        CosmosMsg::Bank(_) => Some(MsgKind::Bank),
+        CosmosMsg::Wasm(WasmMsg::Execute { .. }) => Some(MsgKind::WasmExecute),
+        CosmosMsg::Wasm(WasmMsg::Instantiate { .. }) => Some(MsgKind::WasmInstantiate),
+        CosmosMsg::Wasm(WasmMsg::Migrate { .. }) => Some(MsgKind::WasmMigrate),
+        CosmosMsg::Wasm(WasmMsg::UpdateAdmin { .. } | WasmMsg::ClearAdmin { .. }) => {
+            Some(MsgKind::WasmAdmin)
+        }
+        CosmosMsg::Stargate { .. } => Some(MsgKind::Stargate),
+        CosmosMsg::Ibc(_) => Some(MsgKind::Ibc),
+        CosmosMsg::Gov(_) => Some(MsgKind::Gov),
+        _ => None,
+    }
+}
+
+// execute messages are externally tagged enums, e.g. {"deposit":{...}}
+fn execute_msg_key(msg: &Binary) -> Option<String> {
+    let parsed: BTreeMap<String, IgnoredAny> = from_slice(msg).ok()?;
+    if parsed.len() != 1 {
+        return None;
+    }
+
+    parsed.into_keys().next()
+}
+
+fn check_msg(policy: &ExecutePolicy, msg: &CosmosMsg) -> Result<(), String> {
+    let kind = msg_kind(msg).ok_or_else(|| "unsupported message".to_string())?;
+    if !policy.kinds.contains(&kind) {
+        return Err(format!("{kind:?} messages are not allowed"));
+    }
+
+    let find_target = |contract: &str| {
+        policy
+            .wasm_targets
+            .iter()
+            .find(|target| target.contract.as_str() == contract)
+            .ok_or_else(|| format!("contract {contract} is not allowed"))
+    };
+
+    match msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) => {
+            let target = find_target(contract_addr)?;
+            if target.messages.is_empty() {
+                return Ok(());
+            }
+
+            let key =
+                execute_msg_key(msg).ok_or_else(|| "malformed execute message".to_string())?;
+            if !target.messages.contains(&key) {
+                return Err(format!("{key} is not allowed on {contract_addr}"));
+            }
+        }
+        CosmosMsg::Wasm(
+            WasmMsg::Migrate { contract_addr, .. }
+            | WasmMsg::UpdateAdmin { contract_addr, .. }
+            | WasmMsg::ClearAdmin { contract_addr },
+        ) => {
+            find_target(contract_addr)?;
+        }
+        CosmosMsg::Stargate { type_url, .. } if !policy.stargate_type_urls.contains(type_url) => {
+            return Err(format!("{type_url} is not allowed"));
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// assert_msgs_allowed rejects the whole batch if any message falls outside the policy.
+pub fn assert_msgs_allowed(
+    policy: &ExecutePolicy,
+    msgs: &[CosmosMsg],
+) -> Result<(), ContractError> {
+    for (index, msg) in msgs.iter().enumerate() {
+        check_msg(policy, msg).map_err(|reason| ContractError::MsgNotAllowed { index, reason })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coins, testing::MockApi, to_binary, Addr, BankMsg, Empty, GovMsg, VoteOption,
+    };
+    use mitosis_interface::{gateway::WasmTarget, liquidity_manager};
+
+    use super::*;
+
+    const LMGR: &str = "lmgr";
+
+    fn mock_policy() -> ExecutePolicy {
+        ExecutePolicy {
+            kinds: vec![MsgKind::Bank, MsgKind::WasmExecute, MsgKind::Stargate],
+            wasm_targets: vec![WasmTarget {
+                contract: Addr::unchecked(LMGR),
+                messages: vec!["deposit".to_string()],
+            }],
+            stargate_type_urls: vec!["/cosmos.staking.v1beta1.MsgDelegate".to_string()],
+        }
+    }
+
+    fn mock_execute(contract: &str, msg: &liquidity_manager::ExecuteMsg) -> CosmosMsg {
+        WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_binary(msg).unwrap(),
+            funds: vec![],
+        }
+        .into()
+    }
+
+    fn assert_rejected(policy: &ExecutePolicy, msg: CosmosMsg) {
+        let bank: CosmosMsg = BankMsg::Burn {
+            amount: coins(1, "uosmo"),
+        }
+        .into();
+
+        let err = assert_msgs_allowed(policy, &[bank, msg]).unwrap_err();
+        assert!(matches!(err, ContractError::MsgNotAllowed { index: 1, .. }));
+    }
+
+    #[test]
+    fn test_allowed() {
+        let policy = mock_policy();
+
+        let msgs = vec![
+            BankMsg::Send {
+                to_address: "addr1".to_string(),
+                amount: coins(1, "uosmo"),
+            }
+            .into(),
+            mock_execute(
+                LMGR,
+                &liquidity_manager::ExecuteMsg::Deposit { depositor: None },
+            ),
+            CosmosMsg::Stargate {
+                type_url: "/cosmos.staking.v1beta1.MsgDelegate".to_string(),
+                value: Binary::default(),
+            },
+        ];
+        assert_msgs_allowed(&policy, &msgs).unwrap();
+
+        // an empty batch has nothing to reject
+        assert_msgs_allowed(&ExecutePolicy::default(), &[]).unwrap();
+    }
+
+    #[test]
+    fn test_rejected() {
+        let policy = mock_policy();
+
+        // kind
+        assert_rejected(
+            &policy,
+            WasmMsg::Migrate {
+                contract_addr: LMGR.to_string(),
+                new_code_id: 2,
+                msg: Binary::default(),
+            }
+            .into(),
+        );
+        assert_rejected(
+            &policy,
+            CosmosMsg::Gov(GovMsg::Vote {
+                proposal_id: 1,
+                vote: VoteOption::Yes,
+            }),
+        );
+        assert_rejected(&policy, CosmosMsg::Custom(Empty {}));
+
+        // contract
+        assert_rejected(
+            &policy,
+            mock_execute(
+                "other",
+                &liquidity_manager::ExecuteMsg::Deposit { depositor: None },
+            ),
+        );
+
+        // execute message
+        assert_rejected(
+            &policy,
+            mock_execute(
+                LMGR,
+                &liquidity_manager::ExecuteMsg::Withdraw {
+                    withdrawer: None,
+                    amount: coins(1, "uosmo")[0].clone(),
+                },
+            ),
+        );
+        assert_rejected(
+            &policy,
+            WasmMsg::Execute {
+                contract_addr: LMGR.to_string(),
+                msg: Binary::from(b"{\"deposit\":{},\"withdraw\":{}}".to_vec()),
+                funds: vec![],
+            }
+            .into(),
+        );
+
+        // type url
+        assert_rejected(
+            &policy,
+            CosmosMsg::Stargate {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: Binary::default(),
+            },
+        );
+
+        // nothing is allowed by default
+        let err = assert_msgs_allowed(
+            &ExecutePolicy::default(),
+            &[BankMsg::Burn {
+                amount: coins(1, "uosmo"),
+            }
+            .into()],
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MsgNotAllowed { index: 0, .. }));
+    }
+
+    #[test]
+    fn test_any_message_on_target() {
+        let mut policy = mock_policy();
+        policy.wasm_targets[0].messages = vec![];
+
+        let msg = mock_execute(
+            LMGR,
+            &liquidity_manager::ExecuteMsg::Withdraw {
+                withdrawer: None,
+                amount: coins(1, "uosmo")[0].clone(),
+            },
+        );
+        assert_msgs_allowed(&policy, &[msg]).unwrap();
+    }
+
+    #[test]
+    fn test_validate_policy() {
+        let api = MockApi::default();
+
+        validate_policy(&api, &mock_policy()).unwrap();
+
+        let mut bad_url = mock_policy();
+        bad_url.stargate_type_urls = vec!["cosmos.bank.v1beta1.MsgSend".to_string()];
+        let err = validate_policy(&api, &bad_url).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidArgument { .. }));
+
+        let mut bad_target = mock_policy();
+        bad_target.wasm_targets[0].contract = Addr::unchecked("LMGR");
+        assert!(validate_policy(&api, &bad_target).is_err());
+    }
+}
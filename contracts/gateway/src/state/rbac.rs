@@ -0,0 +1,182 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Storage};
+use cw_storage_plus::{Item, Map};
+
+use crate::errors::ContractError;
+
+use super::{assert_owned, OWNER};
+
+pub const ADDR_ROLE_KEY: &str = "roles";
+pub const ADDR_ROLE: Map<(String, Addr), bool> = Map::new(ADDR_ROLE_KEY);
+
+pub const PENDING_OWNER_KEY: &str = "pending_owner";
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new(PENDING_OWNER_KEY);
+
+// guardians can pause, but not release or administer
+pub const GUARDIAN_ROLE: &str = "guardian_role";
+
+#[cw_serde]
+pub struct PendingOwner {
+    pub owner: Addr,
+    pub expires_at: Option<u64>,
+}
+
+pub fn assert_role(storage: &dyn Storage, role: String, addr: Addr) -> Result<(), ContractError> {
+    match ADDR_ROLE.may_load(storage, (role.clone(), addr.clone()))? {
+        Some(true) => Ok(()),
+        _ => Err(ContractError::RoleNotExist { addr, role }),
+    }
+}
+
+// assert_pauser returns the role the sender pauses with, the owner or a guardian.
+pub fn assert_pauser(storage: &dyn Storage, sender: Addr) -> Result<&'static str, ContractError> {
+    if assert_owned(storage, sender.clone()).is_ok() {
+        return Ok("owner");
+    }
+
+    assert_role(storage, GUARDIAN_ROLE.to_string(), sender)
+        .map(|_| "guardian")
+        .map_err(|_| ContractError::Unauthorized {})
+}
+
+pub fn grant_role(
+    storage: &mut dyn Storage,
+    role: String,
+    addr: Addr,
+) -> Result<(), ContractError> {
+    ADDR_ROLE.save(storage, (role, addr), &true)?;
+
+    Ok(())
+}
+
+pub fn revoke_role(
+    storage: &mut dyn Storage,
+    role: String,
+    addr: Addr,
+) -> Result<(), ContractError> {
+    assert_role(storage, role.clone(), addr.clone())?;
+
+    ADDR_ROLE.remove(storage, (role, addr));
+
+    Ok(())
+}
+
+pub fn propose_owner(
+    storage: &mut dyn Storage,
+    new_owner: Addr,
+    expires_at: Option<u64>,
+) -> Result<(), ContractError> {
+    PENDING_OWNER.save(
+        storage,
+        &PendingOwner {
+            owner: new_owner,
+            expires_at,
+        },
+    )?;
+
+    Ok(())
+}
+
+// accept_owner moves the ownership to the sender when it is the pending owner
+// and the proposal is still open.
+pub fn accept_owner(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    now: u64,
+) -> Result<(), ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    if pending.owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if matches!(pending.expires_at, Some(expires_at) if expires_at <= now) {
+        return Err(ContractError::PendingOwnerExpired {});
+    }
+
+    OWNER.save(storage, &pending.owner)?;
+    PENDING_OWNER.remove(storage);
+
+    Ok(())
+}
+
+pub fn cancel_owner(storage: &mut dyn Storage) -> Result<Addr, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    PENDING_OWNER.remove(storage);
+
+    Ok(pending.owner)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::MockStorage;
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+    const ADDR3: &str = "addr3";
+
+    #[test]
+    fn test_assert_pauser() {
+        let mut storage = MockStorage::new();
+
+        let owner = Addr::unchecked(ADDR1);
+        let guardian = Addr::unchecked(ADDR2);
+        let abuser = Addr::unchecked(ADDR3);
+
+        OWNER.save(&mut storage, &owner).unwrap();
+        grant_role(&mut storage, GUARDIAN_ROLE.to_string(), guardian.clone()).unwrap();
+        grant_role(&mut storage, "other_role".to_string(), abuser.clone()).unwrap();
+
+        assert_eq!(assert_pauser(&storage, owner).unwrap(), "owner");
+        assert_eq!(
+            assert_pauser(&storage, guardian.clone()).unwrap(),
+            "guardian"
+        );
+        let failed = assert_pauser(&storage, abuser).unwrap_err();
+        assert!(matches!(failed, ContractError::Unauthorized {}));
+
+        revoke_role(&mut storage, GUARDIAN_ROLE.to_string(), guardian.clone()).unwrap();
+        let revoked = assert_pauser(&storage, guardian.clone()).unwrap_err();
+        assert!(matches!(revoked, ContractError::Unauthorized {}));
+
+        let not_exist = revoke_role(&mut storage, GUARDIAN_ROLE.to_string(), guardian).unwrap_err();
+        assert!(matches!(not_exist, ContractError::RoleNotExist { .. }));
+    }
+
+    #[test]
+    fn test_accept_owner() {
+        let mut storage = MockStorage::new();
+
+        let owner = Addr::unchecked(ADDR1);
+        let new_owner = Addr::unchecked(ADDR2);
+
+        OWNER.save(&mut storage, &owner).unwrap();
+
+        let no_pending = accept_owner(&mut storage, new_owner.clone(), 0).unwrap_err();
+        assert!(matches!(no_pending, ContractError::NoPendingOwner {}));
+
+        propose_owner(&mut storage, new_owner.clone(), Some(100)).unwrap();
+
+        let abuser = accept_owner(&mut storage, Addr::unchecked(ADDR3), 0).unwrap_err();
+        assert!(matches!(abuser, ContractError::Unauthorized {}));
+        let expired = accept_owner(&mut storage, new_owner.clone(), 100).unwrap_err();
+        assert!(matches!(expired, ContractError::PendingOwnerExpired {}));
+        assert_eq!(OWNER.load(&storage).unwrap(), owner);
+
+        accept_owner(&mut storage, new_owner.clone(), 99).unwrap();
+        assert_eq!(OWNER.load(&storage).unwrap(), new_owner);
+        assert!(PENDING_OWNER.may_load(&storage).unwrap().is_none());
+
+        propose_owner(&mut storage, owner.clone(), None).unwrap();
+        assert_eq!(cancel_owner(&mut storage).unwrap(), owner);
+        let cancelled = cancel_owner(&mut storage).unwrap_err();
+        assert!(matches!(cancelled, ContractError::NoPendingOwner {}));
+    }
+}
@@ -0,0 +1,63 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, StdResult, Storage};
+use cw_storage_plus::Map;
+
+// withdrawal id -> refund leg the liquidity manager queued behind a withdraw
+// tier, resolved by SettleRefund once the withdrawal is finalized or cancelled
+pub const QUEUED_REFUNDS_KEY: &str = "queued_refunds";
+pub const QUEUED_REFUNDS: Map<u64, QueuedRefund> = Map::new(QUEUED_REFUNDS_KEY);
+
+// operation seq -> refund legs whose withdrawal was cancelled. The funds are
+// back in the gateway's deposit and Refund withdraws them again.
+pub const CANCELLED_REFUNDS_KEY: &str = "cancelled_refunds";
+pub const CANCELLED_REFUNDS: Map<u64, Vec<Coin>> = Map::new(CANCELLED_REFUNDS_KEY);
+
+#[cw_serde]
+pub struct QueuedRefund {
+    pub seq: u64,
+    // the withdrawal id is only meaningful on the manager which queued it
+    pub liquidity_manager: Addr,
+    pub amount: Coin,
+}
+
+pub fn cancel_refund(storage: &mut dyn Storage, seq: u64, amount: &Coin) -> StdResult<()> {
+    let mut cancelled = CANCELLED_REFUNDS
+        .may_load(storage, seq)?
+        .unwrap_or_default();
+    match cancelled.iter_mut().find(|c| c.denom == amount.denom) {
+        Some(coin) => coin.amount += amount.amount,
+        None => cancelled.push(amount.clone()),
+    }
+
+    CANCELLED_REFUNDS.save(storage, seq, &cancelled)
+}
+
+pub fn take_cancelled_refund(storage: &mut dyn Storage, seq: u64) -> StdResult<Option<Vec<Coin>>> {
+    let cancelled = CANCELLED_REFUNDS.may_load(storage, seq)?;
+    CANCELLED_REFUNDS.remove(storage, seq);
+
+    Ok(cancelled)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{coin, testing::MockStorage};
+
+    use super::*;
+
+    #[test]
+    fn test_cancelled_refunds() {
+        let mut storage = MockStorage::new();
+
+        cancel_refund(&mut storage, 0, &coin(1, "uosmo")).unwrap();
+        cancel_refund(&mut storage, 0, &coin(2, "uosmo")).unwrap();
+        cancel_refund(&mut storage, 0, &coin(3, "uatom")).unwrap();
+
+        assert!(take_cancelled_refund(&mut storage, 1).unwrap().is_none());
+        assert_eq!(
+            take_cancelled_refund(&mut storage, 0).unwrap(),
+            Some(vec![coin(3, "uosmo"), coin(3, "uatom")])
+        );
+        assert!(take_cancelled_refund(&mut storage, 0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,60 @@
+use cosmwasm_std::{StdResult, Storage};
+use cw_storage_plus::Map;
+
+use crate::errors::ContractError;
+
+pub const EXECUTED_REQUESTS_KEY: &str = "executed_requests";
+pub const EXECUTED_REQUESTS: Map<u64, bool> = Map::new(EXECUTED_REQUESTS_KEY);
+
+pub fn is_executed(storage: &dyn Storage, req_op_id: u64) -> StdResult<bool> {
+    Ok(EXECUTED_REQUESTS
+        .may_load(storage, req_op_id)?
+        .unwrap_or_default())
+}
+
+// consume_request marks req_op_id as executed. Each request can be consumed only once.
+pub fn consume_request(storage: &mut dyn Storage, req_op_id: u64) -> Result<(), ContractError> {
+    if is_executed(storage, req_op_id)? {
+        return Err(ContractError::AlreadyExecuted { req_op_id });
+    }
+
+    EXECUTED_REQUESTS.save(storage, req_op_id, &true)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::MockStorage;
+
+    use super::*;
+
+    #[test]
+    fn test_is_executed() {
+        let mut storage = MockStorage::new();
+
+        assert!(!is_executed(&storage, 1).unwrap());
+
+        EXECUTED_REQUESTS.save(&mut storage, 1, &true).unwrap();
+
+        assert!(is_executed(&storage, 1).unwrap());
+        assert!(!is_executed(&storage, 2).unwrap());
+    }
+
+    #[test]
+    fn test_consume_request() {
+        let mut storage = MockStorage::new();
+
+        consume_request(&mut storage, 1).unwrap();
+        assert!(EXECUTED_REQUESTS.load(&storage, 1).unwrap());
+
+        let duplicated = consume_request(&mut storage, 1).unwrap_err();
+        assert!(matches!(
+            duplicated,
+            ContractError::AlreadyExecuted { req_op_id: 1 }
+        ));
+
+        // other requests are not affected
+        consume_request(&mut storage, 2).unwrap();
+    }
+}
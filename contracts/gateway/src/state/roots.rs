@@ -0,0 +1,40 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{BlockInfo, HexBinary, Storage};
+use cw_storage_plus::Map;
+use cw_utils::Expiration;
+
+use crate::errors::ContractError;
+
+// Merkle root -> commitment, see ExecuteMsg::CommitRoot
+pub const ROOTS_KEY: &str = "merkle_roots";
+pub const ROOTS: Map<&[u8], RootInfo> = Map::new(ROOTS_KEY);
+
+#[cw_serde]
+pub struct RootInfo {
+    pub req_op_id: u64,
+    pub expires_at: Expiration,
+    // version of the signer set which signed the root
+    pub signer_set_version: u64,
+}
+
+impl RootInfo {
+    // stale_reason tells why the leaves of a root can't be executed anymore.
+    // A root dies with its signer set, a replacement or a rotation outdates it.
+    pub fn stale_reason(&self, block: &BlockInfo, signer_set_version: u64) -> Option<&'static str> {
+        if self.expires_at.is_expired(block) {
+            Some("expired")
+        } else if self.signer_set_version < signer_set_version {
+            Some("outdated_signer_set")
+        } else {
+            None
+        }
+    }
+}
+
+pub fn load_root(storage: &dyn Storage, root: &HexBinary) -> Result<RootInfo, ContractError> {
+    ROOTS
+        .may_load(storage, root.as_slice())?
+        .ok_or_else(|| ContractError::RootNotFound {
+            root: root.to_hex(),
+        })
+}
@@ -0,0 +1,331 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::HexBinary;
+use cw_storage_plus::Item;
+use mitosis_interface::gateway::{RetiringSigner, SignatureScheme, Signer};
+
+use crate::errors::ContractError;
+
+pub const SIGNER_SET_KEY: &str = "signer_set";
+pub const SIGNER_SET: Item<SignerSet> = Item::new(SIGNER_SET_KEY);
+
+// validate_public_key checks the key length expected by the scheme:
+// compressed or uncompressed secp256k1 keys, ed25519 keys and eth addresses.
+pub fn validate_public_key(
+    scheme: SignatureScheme,
+    public_key: &HexBinary,
+) -> Result<(), ContractError> {
+    let valid = match scheme {
+        SignatureScheme::Secp256k1 => matches!(public_key.len(), 33 | 65),
+        SignatureScheme::Ed25519 => public_key.len() == 32,
+        SignatureScheme::Eip191 => public_key.len() == 20,
+    };
+
+    if !valid {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("invalid {scheme:?} public key {public_key}"),
+        });
+    }
+
+    Ok(())
+}
+
+#[cw_serde]
+pub struct SignerSet {
+    pub signers: Vec<Signer>,
+    pub retiring: Vec<RetiringSigner>,
+    pub threshold: u64,
+    pub version: u64,
+}
+
+impl SignerSet {
+    pub fn new(signers: Vec<Signer>, threshold: u64, version: u64) -> Result<Self, ContractError> {
+        if signers.is_empty() {
+            return Err(ContractError::InvalidArgument {
+                msg: "signers must not be empty".to_string(),
+            });
+        }
+
+        let mut total_weight: u64 = 0;
+        for (i, signer) in signers.iter().enumerate() {
+            if signer.weight == 0 {
+                return Err(ContractError::InvalidArgument {
+                    msg: "signer weight must be positive".to_string(),
+                });
+            }
+            validate_public_key(signer.scheme, &signer.public_key)?;
+            if signers[..i]
+                .iter()
+                .any(|s| s.public_key == signer.public_key)
+            {
+                return Err(ContractError::InvalidArgument {
+                    msg: format!("duplicated signer {}", signer.public_key),
+                });
+            }
+
+            total_weight =
+                total_weight
+                    .checked_add(signer.weight)
+                    .ok_or(ContractError::InvalidArgument {
+                        msg: "total weight overflow".to_string(),
+                    })?;
+        }
+
+        if threshold == 0 || threshold > total_weight {
+            return Err(ContractError::InvalidArgument {
+                msg: "threshold must be between 1 and total weight".to_string(),
+            });
+        }
+
+        Ok(Self {
+            signers,
+            retiring: vec![],
+            threshold,
+            version,
+        })
+    }
+
+    pub fn get(&self, public_key: &HexBinary) -> Option<&Signer> {
+        self.signers.iter().find(|s| &s.public_key == public_key)
+    }
+
+    // resolve returns the signer slot of the key and the scheme the key signs
+    // with, following retiring keys to their successor while the grace window
+    // is open.
+    pub fn resolve(&self, public_key: &HexBinary, now: u64) -> Option<(&Signer, SignatureScheme)> {
+        if let Some(signer) = self.get(public_key) {
+            return Some((signer, signer.scheme));
+        }
+
+        self.retiring
+            .iter()
+            .find(|r| &r.public_key == public_key && now < r.expires_at)
+            .and_then(|r| self.get(&r.successor).map(|signer| (signer, r.scheme)))
+    }
+
+    // rotate replaces old_public_key with new_public_key in place, keeping its
+    // weight. The old key stays valid for the slot until expires_at.
+    pub fn rotate(
+        &mut self,
+        old_public_key: &HexBinary,
+        new_public_key: &HexBinary,
+        scheme: SignatureScheme,
+        expires_at: u64,
+        now: u64,
+    ) -> Result<(), ContractError> {
+        validate_public_key(scheme, new_public_key)?;
+        if self.get(new_public_key).is_some()
+            || self
+                .retiring
+                .iter()
+                .any(|r| &r.public_key == new_public_key)
+        {
+            return Err(ContractError::InvalidArgument {
+                msg: format!("signer {new_public_key} already registered"),
+            });
+        }
+
+        let signer = self
+            .signers
+            .iter_mut()
+            .find(|s| &s.public_key == old_public_key)
+            .ok_or_else(|| ContractError::UnknownSigner {
+                public_key: old_public_key.to_hex(),
+            })?;
+        let old_scheme = signer.scheme;
+        signer.public_key = new_public_key.clone();
+        signer.scheme = scheme;
+
+        self.retiring.retain(|r| now < r.expires_at);
+        for retiring in self.retiring.iter_mut() {
+            if &retiring.successor == old_public_key {
+                retiring.successor = new_public_key.clone();
+            }
+        }
+        if now < expires_at {
+            self.retiring.push(RetiringSigner {
+                public_key: old_public_key.clone(),
+                scheme: old_scheme,
+                successor: new_public_key.clone(),
+                expires_at,
+            });
+        }
+
+        self.version += 1;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use mitosis_interface::gateway::SignatureScheme;
+
+    use super::*;
+
+    fn signer(key: u8, weight: u64) -> Signer {
+        Signer {
+            public_key: HexBinary::from(vec![key; 33]),
+            weight,
+            scheme: SignatureScheme::Secp256k1,
+        }
+    }
+
+    #[test]
+    fn test_new_signer_set() {
+        let set = SignerSet::new(vec![signer(1, 1), signer(2, 2)], 3, 0).unwrap();
+
+        assert_eq!(set.threshold, 3);
+        assert_eq!(set.get(&HexBinary::from(vec![2; 33])), Some(&signer(2, 2)));
+        assert_eq!(set.get(&HexBinary::from(vec![3; 33])), None);
+    }
+
+    #[test]
+    fn test_new_signer_set_failure() {
+        let empty = SignerSet::new(vec![], 1, 0).unwrap_err();
+        assert!(matches!(empty, ContractError::InvalidArgument { .. }));
+
+        let zero_weight = SignerSet::new(vec![signer(1, 0)], 1, 0).unwrap_err();
+        assert!(matches!(zero_weight, ContractError::InvalidArgument { .. }));
+
+        let duplicated = SignerSet::new(vec![signer(1, 1), signer(1, 1)], 1, 0).unwrap_err();
+        assert!(matches!(duplicated, ContractError::InvalidArgument { .. }));
+
+        let zero_threshold = SignerSet::new(vec![signer(1, 1)], 0, 0).unwrap_err();
+        assert!(matches!(
+            zero_threshold,
+            ContractError::InvalidArgument { .. }
+        ));
+
+        let unreachable = SignerSet::new(vec![signer(1, 1), signer(2, 1)], 3, 0).unwrap_err();
+        assert!(matches!(unreachable, ContractError::InvalidArgument { .. }));
+
+        for (scheme, len) in [
+            (SignatureScheme::Secp256k1, 32),
+            (SignatureScheme::Secp256k1, 64),
+            (SignatureScheme::Ed25519, 33),
+            (SignatureScheme::Eip191, 32),
+        ] {
+            let wrong_length = SignerSet::new(
+                vec![Signer {
+                    public_key: HexBinary::from(vec![1; len]),
+                    weight: 1,
+                    scheme,
+                }],
+                1,
+                0,
+            )
+            .unwrap_err();
+            assert!(matches!(
+                wrong_length,
+                ContractError::InvalidArgument { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_public_key() {
+        for (scheme, len) in [
+            (SignatureScheme::Secp256k1, 33),
+            (SignatureScheme::Secp256k1, 65),
+            (SignatureScheme::Ed25519, 32),
+            (SignatureScheme::Eip191, 20),
+        ] {
+            validate_public_key(scheme, &HexBinary::from(vec![1; len])).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let key = |k: u8| HexBinary::from(vec![k; 33]);
+        let mut set = SignerSet::new(vec![signer(1, 1), signer(2, 2)], 3, 0).unwrap();
+
+        set.rotate(&key(2), &key(3), SignatureScheme::Secp256k1, 200, 100)
+            .unwrap();
+        assert_eq!(set.version, 1);
+        assert_eq!(set.signers, vec![signer(1, 1), signer(3, 2)]);
+        assert_eq!(
+            set.resolve(&key(2), 199),
+            Some((&signer(3, 2), SignatureScheme::Secp256k1))
+        );
+        assert_eq!(set.resolve(&key(2), 200), None);
+
+        // chained rotation keeps retiring keys pointing at the live slot
+        set.rotate(&key(3), &key(4), SignatureScheme::Secp256k1, 300, 150)
+            .unwrap();
+        assert_eq!(
+            set.resolve(&key(2), 150),
+            Some((&signer(4, 2), SignatureScheme::Secp256k1))
+        );
+        assert_eq!(
+            set.resolve(&key(3), 150),
+            Some((&signer(4, 2), SignatureScheme::Secp256k1))
+        );
+
+        // no grace window
+        set.rotate(&key(1), &key(5), SignatureScheme::Secp256k1, 0, 150)
+            .unwrap();
+        assert_eq!(set.resolve(&key(1), 150), None);
+
+        // expired retiring keys are pruned
+        set.rotate(&key(5), &key(6), SignatureScheme::Secp256k1, 0, 250)
+            .unwrap();
+        assert_eq!(set.retiring.len(), 1);
+        assert_eq!(set.retiring[0].public_key, key(3));
+    }
+
+    #[test]
+    fn test_rotate_failure() {
+        let key = |k: u8| HexBinary::from(vec![k; 33]);
+        let mut set = SignerSet::new(vec![signer(1, 1), signer(2, 2)], 3, 0).unwrap();
+
+        let unknown = set
+            .rotate(&key(3), &key(4), SignatureScheme::Secp256k1, 200, 100)
+            .unwrap_err();
+        assert!(matches!(unknown, ContractError::UnknownSigner { .. }));
+
+        let registered = set
+            .rotate(&key(1), &key(2), SignatureScheme::Secp256k1, 200, 100)
+            .unwrap_err();
+        assert!(matches!(registered, ContractError::InvalidArgument { .. }));
+
+        set.rotate(&key(1), &key(3), SignatureScheme::Secp256k1, 200, 100)
+            .unwrap();
+        let retiring = set
+            .rotate(&key(2), &key(1), SignatureScheme::Secp256k1, 200, 100)
+            .unwrap_err();
+        assert!(matches!(retiring, ContractError::InvalidArgument { .. }));
+
+        let wrong_length = set
+            .rotate(&key(2), &key(4), SignatureScheme::Ed25519, 200, 100)
+            .unwrap_err();
+        assert!(matches!(
+            wrong_length,
+            ContractError::InvalidArgument { .. }
+        ));
+        assert_eq!(set.version, 1);
+    }
+
+    #[test]
+    fn test_rotate_scheme() {
+        let mut set = SignerSet::new(vec![signer(1, 1), signer(2, 2)], 3, 0).unwrap();
+        let ed25519_key = HexBinary::from(vec![3; 32]);
+
+        set.rotate(
+            &HexBinary::from(vec![2; 33]),
+            &ed25519_key,
+            SignatureScheme::Ed25519,
+            200,
+            100,
+        )
+        .unwrap();
+
+        // the slot takes the new scheme, the retiring key keeps its own
+        let (slot, scheme) = set.resolve(&ed25519_key, 100).unwrap();
+        assert_eq!(slot.scheme, SignatureScheme::Ed25519);
+        assert_eq!(scheme, SignatureScheme::Ed25519);
+
+        let (slot, scheme) = set.resolve(&HexBinary::from(vec![2; 33]), 100).unwrap();
+        assert_eq!(slot.public_key, ed25519_key);
+        assert_eq!(scheme, SignatureScheme::Secp256k1);
+    }
+}
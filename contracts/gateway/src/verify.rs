@@ -1,9 +1,10 @@
 use bech32::ToBase32;
-use cosmwasm_std::Binary;
-use ripemd::{Digest, Ripemd160};
-use sha2::Sha256;
+use cosmwasm_std::{to_binary, Api, BlockInfo, HexBinary};
+use mitosis_interface::gateway::{MerkleLeaf, SignatureScheme, SignerSignature};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 
-use crate::errors::ContractError;
+use crate::{errors::ContractError, state::signers::SignerSet};
 
 pub fn sha256_digest(bz: impl AsRef<[u8]>) -> Result<[u8; 32], ContractError> {
     let mut hasher = Sha256::new();
@@ -17,8 +18,8 @@ pub fn sha256_digest(bz: impl AsRef<[u8]>) -> Result<[u8; 32], ContractError> {
         .map_err(|_| ContractError::WrongLength {})
 }
 
-pub fn ripemd160_digest(bz: impl AsRef<[u8]>) -> Result<[u8; 20], ContractError> {
-    let mut hasher = Ripemd160::new();
+pub fn keccak256_digest(bz: impl AsRef<[u8]>) -> Result<[u8; 32], ContractError> {
+    let mut hasher = Keccak256::new();
 
     hasher.update(bz);
 
@@ -29,12 +30,254 @@ pub fn ripemd160_digest(bz: impl AsRef<[u8]>) -> Result<[u8; 20], ContractError>
         .map_err(|_| ContractError::WrongLength {})
 }
 
-pub fn pub_to_addr(pub_key: Binary, prefix: &str) -> Result<String, ContractError> {
-    let sha_hash = sha256_digest(pub_key)?;
-    let rip_hash = ripemd160_digest(sha_hash)?;
+// derive_intermediate_sender returns the address ibc-hooks executes a transfer's
+// wasm hook from: sha256(sha256("ibc-wasm-hook-intermediary") || "channel/sender").
+pub fn derive_intermediate_sender(
+    channel: &str,
+    original_sender: &str,
+    prefix: &str,
+) -> Result<String, ContractError> {
+    let type_hash = sha256_digest("ibc-wasm-hook-intermediary")?;
+    let hash = sha256_digest(
+        [
+            &type_hash[..],
+            format!("{channel}/{original_sender}").as_bytes(),
+        ]
+        .concat(),
+    )?;
 
-    let addr = bech32::encode(prefix, rip_hash.to_base32(), bech32::Variant::Bech32)
-        .map_err(|_| ContractError::InvalidPubKey {})?;
+    bech32::encode(prefix, hash.to_base32(), bech32::Variant::Bech32).map_err(|_| {
+        ContractError::InvalidArgument {
+            msg: format!("invalid address prefix: {prefix}"),
+        }
+    })
+}
+
+// validate_prefix checks that prefix can be used as the human readable part of
+// a bech32 address.
+pub fn validate_prefix(prefix: &str) -> Result<(), ContractError> {
+    if prefix.is_empty()
+        || prefix
+            .chars()
+            .any(|c| !c.is_ascii_lowercase() && !c.is_ascii_digit())
+    {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("invalid address prefix: {prefix}"),
+        });
+    }
+
+    Ok(())
+}
+
+// address_prefix returns the human readable part of a bech32 address
+pub fn address_prefix(addr: &str) -> Result<String, ContractError> {
+    let (prefix, _, _) = bech32::decode(addr).map_err(|_| ContractError::InvalidArgument {
+        msg: format!("not a bech32 address: {addr}"),
+    })?;
+
+    Ok(prefix)
+}
+
+// merkle_leaf_hash hashes a leaf as sha256(0x00 || json(leaf)). The prefix keeps
+// a leaf from being passed off as an inner node.
+pub fn merkle_leaf_hash(leaf: &MerkleLeaf) -> Result<[u8; 32], ContractError> {
+    sha256_digest([&[0x00], to_binary(leaf)?.as_slice()].concat())
+}
+
+// merkle_node_hash hashes a sorted pair as sha256(0x01 || min(a, b) || max(a, b)).
+pub fn merkle_node_hash(a: &[u8], b: &[u8]) -> Result<[u8; 32], ContractError> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+
+    sha256_digest([&[0x01], first, second].concat())
+}
+
+// verify_merkle_proof hashes the leaf up the proof and compares it to the root.
+pub fn verify_merkle_proof(
+    root: &[u8],
+    leaf: [u8; 32],
+    proof: &[HexBinary],
+) -> Result<(), ContractError> {
+    let mut hash = leaf;
+    for sibling in proof {
+        if sibling.len() != 32 {
+            return Err(ContractError::InvalidProof {});
+        }
+        hash = merkle_node_hash(&hash, sibling)?;
+    }
+
+    if hash.as_slice() != root {
+        return Err(ContractError::InvalidProof {});
+    }
+
+    Ok(())
+}
+
+// verify_signatures checks every signature against the signer set and requires
+// the accumulated weight of distinct signers to reach the threshold. A retiring
+// key counts for its successor's slot, so a slot is never counted twice.
+pub fn verify_signatures(
+    api: &dyn Api,
+    block: &BlockInfo,
+    signer_set: &SignerSet,
+    sign_bytes: &[u8],
+    signatures: &[SignerSignature],
+) -> Result<(), ContractError> {
+    let hash = sha256_digest(sign_bytes)?;
+
+    let mut signed: Vec<&HexBinary> = vec![];
+    let mut weight: u64 = 0;
+    for SignerSignature {
+        public_key,
+        signature,
+    } in signatures
+    {
+        let (signer, scheme) = signer_set
+            .resolve(public_key, block.time.seconds())
+            .ok_or_else(|| ContractError::UnknownSigner {
+                public_key: public_key.to_hex(),
+            })?;
+
+        if signed.contains(&&signer.public_key) {
+            return Err(ContractError::DuplicateSigner {
+                public_key: public_key.to_hex(),
+            });
+        }
+
+        verify_signature(api, scheme, &hash, signature, public_key)?;
+
+        signed.push(&signer.public_key);
+        weight += signer.weight;
+    }
+
+    if weight < signer_set.threshold {
+        return Err(ContractError::ThresholdNotMet {
+            weight,
+            threshold: signer_set.threshold,
+        });
+    }
+
+    Ok(())
+}
+
+// verify_signature checks a single signature over the digest with the given scheme.
+pub fn verify_signature(
+    api: &dyn Api,
+    scheme: SignatureScheme,
+    hash: &[u8; 32],
+    signature: &HexBinary,
+    public_key: &HexBinary,
+) -> Result<(), ContractError> {
+    let verify = match scheme {
+        SignatureScheme::Secp256k1 => api
+            .secp256k1_verify(hash, signature.as_slice(), public_key.as_slice())
+            .map_err(|_| ContractError::InvalidPubKey {})?,
+        SignatureScheme::Ed25519 => api
+            .ed25519_verify(hash, signature.as_slice(), public_key.as_slice())
+            .map_err(|_| ContractError::InvalidPubKey {})?,
+        SignatureScheme::Eip191 => {
+            if signature.len() != 65 {
+                return Err(ContractError::InvalidPubKey {});
+            }
+
+            // personal_sign over the 32-byte digest
+            let message_hash = keccak256_digest(
+                [
+                    b"\x19Ethereum Signed Message:\n32".as_slice(),
+                    hash.as_slice(),
+                ]
+                .concat(),
+            )?;
+
+            let recovery_param = match signature[64] {
+                v @ (0 | 1) => v,
+                v @ (27 | 28) => v - 27,
+                _ => return Err(ContractError::InvalidPubKey {}),
+            };
+
+            let recovered = api
+                .secp256k1_recover_pubkey(&message_hash, &signature[..64], recovery_param)
+                .map_err(|_| ContractError::InvalidPubKey {})?;
+
+            // eth address is the last 20 bytes of keccak256 over the uncompressed key
+            let address = keccak256_digest(&recovered[1..])?;
+
+            address[12..] == *public_key.as_slice()
+        }
+    };
+
+    if !verify {
+        return Err(ContractError::InvalidPubKey {});
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use mitosis_interface::gateway::Signer;
+
+    use super::*;
+
+    // the same key in uncompressed (65-byte) and compressed (33-byte) form, and
+    // its signature over sha256("mitosis-gateway")
+    const UNCOMPRESSED_KEY: &str = "046311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a15a21fe0a260806f39f0a975a3a08d75dbeca0adbe09376421b77e28a4803d36f";
+    const COMPRESSED_KEY: &str =
+        "036311eaafa93a92ed771874b471ded89ac463628e72f46882d2f11c31f326f3a1";
+    const SIGNATURE: &str = "968e95f6218f7975aab98c3480b557a48a54130ee36d76f1ce9d73990692154160ddf71833da3f3d3f8b7798a2467eee56ad92673ea765dbbaffeefc55e2746d";
+
+    #[test]
+    fn test_validate_prefix() {
+        validate_prefix("osmo").unwrap();
+        validate_prefix("neutron").unwrap();
+        validate_prefix("sei").unwrap();
+        validate_prefix("inj").unwrap();
+
+        assert!(validate_prefix("").is_err());
+        assert!(validate_prefix("Osmo").is_err());
+        assert!(validate_prefix("os mo").is_err());
+    }
+
+    #[test]
+    fn test_verify_uncompressed_key() {
+        let deps = mock_dependencies();
+        let env = mock_env();
+
+        for key in [UNCOMPRESSED_KEY, COMPRESSED_KEY] {
+            let public_key = HexBinary::from_hex(key).unwrap();
+            let signer_set = SignerSet::new(
+                vec![Signer {
+                    public_key: public_key.clone(),
+                    weight: 1,
+                    scheme: SignatureScheme::Secp256k1,
+                }],
+                1,
+                0,
+            )
+            .unwrap();
+            let signatures = vec![SignerSignature {
+                public_key,
+                signature: HexBinary::from_hex(SIGNATURE).unwrap(),
+            }];
+
+            verify_signatures(
+                &deps.api,
+                &env.block,
+                &signer_set,
+                b"mitosis-gateway",
+                &signatures,
+            )
+            .unwrap();
 
-    Ok(addr)
+            let err = verify_signatures(
+                &deps.api,
+                &env.block,
+                &signer_set,
+                b"mitosis-gateway/v2",
+                &signatures,
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidPubKey {}));
+        }
+    }
 }
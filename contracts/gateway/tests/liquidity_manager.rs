@@ -0,0 +1,534 @@
+use cosmwasm_std::{coin, coins, Addr, Coin, Empty, Event, HexBinary, Uint128};
+use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+use mitosis_gateway::errors::ContractError as GatewayError;
+use mitosis_interface::{
+    denom_manager,
+    gateway::{self, OperationResponse, OperationStatus, SignatureScheme, Signer},
+    liquidity_manager,
+};
+use mitosis_liquidity_manager::{state::rbac::GATEWAY_ROLE, ContractError as LmError};
+
+const OWNER: &str = "owner";
+const USER: &str = "user";
+const DENOM: &str = "uosmo";
+const TIMEOUT: u64 = 100;
+
+fn gateway_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            mitosis_gateway::contracts::execute,
+            mitosis_gateway::contracts::instantiate,
+            mitosis_gateway::contracts::query,
+        )
+        .with_reply(mitosis_gateway::contracts::reply),
+    )
+}
+
+fn liquidity_manager_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            mitosis_liquidity_manager::contracts::execute,
+            mitosis_liquidity_manager::contracts::instantiate,
+            mitosis_liquidity_manager::contracts::query,
+        )
+        .with_reply(mitosis_liquidity_manager::contracts::reply),
+    )
+}
+
+fn denom_manager_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mitosis_denom_manager::contracts::execute,
+        mitosis_denom_manager::contracts::instantiate,
+        mitosis_denom_manager::contracts::query,
+    ))
+}
+
+struct Suite {
+    app: App,
+    gateway: Addr,
+    lmgr: Addr,
+}
+
+// setup deploys the three contracts and grants the gateway its role on the
+// liquidity manager, the way a deployment wires them together.
+fn setup() -> Suite {
+    let owner = Addr::unchecked(OWNER);
+
+    let mut app = AppBuilder::new().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &Addr::unchecked(USER), coins(1000, DENOM))
+            .unwrap();
+    });
+
+    let gateway_code = app.store_code(gateway_contract());
+    let lmgr_code = app.store_code(liquidity_manager_contract());
+    let dmgr_code = app.store_code(denom_manager_contract());
+
+    let lmgr = app
+        .instantiate_contract(
+            lmgr_code,
+            owner.clone(),
+            &liquidity_manager::InstantiateMsg { vaults: vec![] },
+            &[],
+            "liquidity manager",
+            None,
+        )
+        .unwrap();
+    let dmgr = app
+        .instantiate_contract(
+            dmgr_code,
+            owner.clone(),
+            &denom_manager::InstantiateMsg {},
+            &[],
+            "denom manager",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        dmgr.clone(),
+        &denom_manager::ExecuteMsg::AddAlias {
+            token: DENOM.to_string(),
+            denom: "osmo".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let gateway = app
+        .instantiate_contract(
+            gateway_code,
+            owner.clone(),
+            &gateway::InstantiateMsg {
+                liquidity_manager: lmgr.clone(),
+                denom_manager: dmgr,
+                signers: vec![Signer {
+                    public_key: HexBinary::from_hex(
+                        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                    )
+                    .unwrap(),
+                    weight: 1,
+                    scheme: SignatureScheme::Secp256k1,
+                }],
+                threshold: 1,
+                operation_timeout: TIMEOUT,
+                execute_policy: None,
+                address_prefix: Some("osmo".to_string()),
+            },
+            &[],
+            "gateway",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        gateway.clone(),
+        &gateway::ExecuteMsg::RegisterOp {
+            op_id: 1,
+            name: "bridge".to_string(),
+            args: vec![],
+            enabled: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner,
+        lmgr.clone(),
+        &liquidity_manager::ExecuteMsg::GrantRole {
+            role: GATEWAY_ROLE.to_string(),
+            addr: gateway.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    Suite { app, gateway, lmgr }
+}
+
+fn deposited(suite: &Suite, depositor: &Addr) -> Vec<Coin> {
+    let balance: liquidity_manager::GetBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.lmgr,
+            &liquidity_manager::QueryMsg::GetBalance {
+                depositor: depositor.clone(),
+            },
+        )
+        .unwrap();
+
+    balance.assets
+}
+
+#[test]
+fn test_send_and_refund_through_liquidity_manager() {
+    let mut suite = setup();
+    let user = Addr::unchecked(USER);
+
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::Send {
+                op_id: 1,
+                op_args: vec![],
+            },
+            &coins(400, DENOM),
+        )
+        .unwrap();
+
+    // the send is held by the liquidity manager on the gateway's behalf
+    assert_eq!(deposited(&suite, &suite.gateway), coins(400, DENOM));
+    assert_eq!(
+        suite.app.wrap().query_balance(&suite.lmgr, DENOM).unwrap(),
+        coin(400, DENOM)
+    );
+
+    suite.app.update_block(|block| {
+        block.time = block.time.plus_seconds(TIMEOUT);
+    });
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::Refund { seq: 0 },
+            &[],
+        )
+        .unwrap();
+
+    // paid back to the user by the liquidity manager
+    assert_eq!(
+        suite.app.wrap().query_balance(&user, DENOM).unwrap(),
+        coin(1000, DENOM)
+    );
+    assert_eq!(
+        suite.app.wrap().query_balance(&suite.lmgr, DENOM).unwrap(),
+        coin(0, DENOM)
+    );
+    assert_eq!(
+        suite
+            .app
+            .wrap()
+            .query_balance(&suite.gateway, DENOM)
+            .unwrap(),
+        coin(0, DENOM)
+    );
+    assert_eq!(deposited(&suite, &suite.gateway), coins(0, DENOM));
+
+    let op: OperationResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.gateway, &gateway::QueryMsg::GetOperation { seq: 0 })
+        .unwrap();
+    assert_eq!(op.status, OperationStatus::Refunded);
+}
+
+#[test]
+fn test_withdraw_only_by_gateway_from_its_deposit() {
+    let mut suite = setup();
+    let user = Addr::unchecked(USER);
+    let owner = Addr::unchecked(OWNER);
+    let other_gateway = Addr::unchecked("other_gateway");
+
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::Send {
+                op_id: 1,
+                op_args: vec![],
+            },
+            &coins(400, DENOM),
+        )
+        .unwrap();
+
+    let withdraw = liquidity_manager::ExecuteMsg::Withdraw {
+        withdrawer: Some(user.clone()),
+        amount: coin(400, DENOM),
+    };
+
+    // neither the owner nor the user can pull the gateway's deposit
+    for sender in [owner.clone(), user.clone()] {
+        let err = suite
+            .app
+            .execute_contract(sender, suite.lmgr.clone(), &withdraw, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<LmError>().unwrap(),
+            LmError::Unauthorized {}
+        ));
+    }
+
+    // another gateway only reaches its own, empty, deposit
+    suite
+        .app
+        .execute_contract(
+            owner,
+            suite.lmgr.clone(),
+            &liquidity_manager::ExecuteMsg::GrantRole {
+                role: GATEWAY_ROLE.to_string(),
+                addr: other_gateway.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    let err = suite
+        .app
+        .execute_contract(other_gateway, suite.lmgr.clone(), &withdraw, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<LmError>().unwrap(),
+        LmError::DepositAssetNotFound { .. }
+    ));
+
+    assert_eq!(deposited(&suite, &suite.gateway), coins(400, DENOM));
+    assert_eq!(
+        suite.app.wrap().query_balance(&user, DENOM).unwrap(),
+        coin(600, DENOM)
+    );
+}
+
+#[test]
+fn test_refund_queued_by_withdraw_tier() {
+    let mut suite = setup();
+    let user = Addr::unchecked(USER);
+    let owner = Addr::unchecked(OWNER);
+
+    suite
+        .app
+        .execute_contract(
+            owner,
+            suite.lmgr.clone(),
+            &liquidity_manager::ExecuteMsg::SetWithdrawTiers {
+                denom: DENOM.to_string(),
+                tiers: vec![liquidity_manager::WithdrawTier {
+                    min_amount: Uint128::new(300),
+                    delay: 600,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::Send {
+                op_id: 1,
+                op_args: vec![],
+            },
+            &coins(400, DENOM),
+        )
+        .unwrap();
+
+    suite.app.update_block(|block| {
+        block.time = block.time.plus_seconds(TIMEOUT);
+    });
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::Refund { seq: 0 },
+            &[],
+        )
+        .unwrap();
+
+    // queued for the user, nothing was paid out yet
+    let queued: liquidity_manager::WithdrawalResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.lmgr,
+            &liquidity_manager::QueryMsg::GetWithdrawal { withdrawal_id: 0 },
+        )
+        .unwrap();
+    assert_eq!(queued.withdrawer, user);
+    assert_eq!(queued.amount, coin(400, DENOM));
+    assert_eq!(
+        suite.app.wrap().query_balance(&user, DENOM).unwrap(),
+        coin(600, DENOM)
+    );
+    assert_eq!(
+        suite.app.wrap().query_balance(&suite.lmgr, DENOM).unwrap(),
+        coin(400, DENOM)
+    );
+
+    suite.app.update_block(|block| {
+        block.time = block.time.plus_seconds(600);
+    });
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.lmgr.clone(),
+            &liquidity_manager::ExecuteMsg::FinalizeWithdrawal { withdrawal_id: 0 },
+            &[],
+        )
+        .unwrap();
+
+    assert_eq!(
+        suite.app.wrap().query_balance(&user, DENOM).unwrap(),
+        coin(1000, DENOM)
+    );
+    assert_eq!(
+        suite
+            .app
+            .wrap()
+            .query_balance(&suite.gateway, DENOM)
+            .unwrap(),
+        coin(0, DENOM)
+    );
+
+    // the queued leg is settled once, with nothing left to pay
+    let result = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::SettleRefund { withdrawal_id: 0 },
+            &[],
+        )
+        .unwrap();
+    assert!(result.has_event(
+        &Event::new("wasm")
+            .add_attribute("withdrawal_id", "0")
+            .add_attribute("status", "finalized")
+    ));
+    assert_eq!(
+        suite.app.wrap().query_balance(&user, DENOM).unwrap(),
+        coin(1000, DENOM)
+    );
+    let err = suite
+        .app
+        .execute_contract(
+            user,
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::SettleRefund { withdrawal_id: 0 },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<GatewayError>().unwrap(),
+        GatewayError::NoQueuedRefund { withdrawal_id: 0 }
+    ));
+}
+
+#[test]
+fn test_refund_queued_and_cancelled() {
+    let mut suite = setup();
+    let user = Addr::unchecked(USER);
+    let owner = Addr::unchecked(OWNER);
+
+    let set_tiers = |tiers| liquidity_manager::ExecuteMsg::SetWithdrawTiers {
+        denom: DENOM.to_string(),
+        tiers,
+    };
+    suite
+        .app
+        .execute_contract(
+            owner.clone(),
+            suite.lmgr.clone(),
+            &set_tiers(vec![liquidity_manager::WithdrawTier {
+                min_amount: Uint128::new(300),
+                delay: 600,
+            }]),
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.gateway.clone(),
+            &gateway::ExecuteMsg::Send {
+                op_id: 1,
+                op_args: vec![],
+            },
+            &coins(400, DENOM),
+        )
+        .unwrap();
+
+    suite.app.update_block(|block| {
+        block.time = block.time.plus_seconds(TIMEOUT);
+    });
+    let refund = gateway::ExecuteMsg::Refund { seq: 0 };
+    suite
+        .app
+        .execute_contract(user.clone(), suite.gateway.clone(), &refund, &[])
+        .unwrap();
+
+    // settling waits on the liquidity manager
+    let settle = gateway::ExecuteMsg::SettleRefund { withdrawal_id: 0 };
+    let err = suite
+        .app
+        .execute_contract(user.clone(), suite.gateway.clone(), &settle, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<GatewayError>().unwrap(),
+        GatewayError::WithdrawalQueued { withdrawal_id: 0 }
+    ));
+
+    suite
+        .app
+        .execute_contract(
+            owner.clone(),
+            suite.lmgr.clone(),
+            &liquidity_manager::ExecuteMsg::CancelWithdrawal { withdrawal_id: 0 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(deposited(&suite, &suite.gateway), coins(400, DENOM));
+
+    // a refunded operation can't be refunded again until the leg is settled
+    suite
+        .app
+        .execute_contract(user.clone(), suite.gateway.clone(), &refund, &[])
+        .unwrap_err();
+
+    let result = suite
+        .app
+        .execute_contract(user.clone(), suite.gateway.clone(), &settle, &[])
+        .unwrap();
+    assert!(result.has_event(
+        &Event::new("wasm")
+            .add_attribute("withdrawal_id", "0")
+            .add_attribute("status", "cancelled")
+    ));
+
+    // the cancelled leg is withdrawn again
+    suite
+        .app
+        .execute_contract(owner, suite.lmgr.clone(), &set_tiers(vec![]), &[])
+        .unwrap();
+    suite
+        .app
+        .execute_contract(user.clone(), suite.gateway.clone(), &refund, &[])
+        .unwrap();
+
+    assert_eq!(
+        suite.app.wrap().query_balance(&user, DENOM).unwrap(),
+        coin(1000, DENOM)
+    );
+    assert_eq!(deposited(&suite, &suite.gateway), coins(0, DENOM));
+
+    let op: OperationResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.gateway, &gateway::QueryMsg::GetOperation { seq: 0 })
+        .unwrap();
+    assert_eq!(op.status, OperationStatus::Refunded);
+
+    // and only once
+    suite
+        .app
+        .execute_contract(user, suite.gateway.clone(), &refund, &[])
+        .unwrap_err();
+}
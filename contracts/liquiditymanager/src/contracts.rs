@@ -1,21 +1,16 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{
-    CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryResponse, Reply, Response, SubMsg, Uint128,
-};
-use cw2::set_contract_version;
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, QueryResponse, Reply, Response};
+use cw2::{get_contract_version, set_contract_version};
 use mitosis_interface::liquidity_manager::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use osmosis_std::types::osmosis::tokenfactory::v1beta1::{MsgCreateDenom, MsgCreateDenomResponse};
 
 use crate::{
     execute::{
         consts::{REPLY_CREATE_DENOM_SUCCESS, REPLY_WITHDRAW_SUBMESSAGE_FAILURE},
-        lp,
-    },
-    state::{
-        bond::init_unbonds_id, delegates::DELEGATE_BALANCE, rbac::OWNER, ConfigInfo, DenomInfo,
-        CONFIG, DENOM, PAUSED,
+        lp, vault,
     },
+    migrations::migrate_legacy,
+    state::{bond::init_unbonds_id, rbac::OWNER, withdrawals::init_withdrawals_id, PAUSED},
     ContractError, CONTRACT_NAME, CONTRACT_VERSION,
 };
 
@@ -30,38 +25,37 @@ pub fn instantiate(
 
     OWNER.save(deps.storage, &info.sender)?;
     PAUSED.save(deps.storage, &Default::default())?;
-    DELEGATE_BALANCE.save(deps.storage, &Uint128::new(0))?;
-
-    let denom = DenomInfo {
-        denom: msg.denom,
-        lp_denom: "".to_string(),
-    };
-    DENOM.save(deps.storage, &denom)?;
     init_unbonds_id(deps.storage)?;
+    init_withdrawals_id(deps.storage)?;
 
-    let config = ConfigInfo {
-        unbonding_period: msg.unbonding_period,
-    };
-    CONFIG.save(deps.storage, &config)?;
-
-    // Only consider single asset.
-    let msg_create_denom: CosmosMsg = MsgCreateDenom {
-        sender: env.contract.address.to_string(),
-        subdenom: msg.lp_denom,
-    }
-    .into();
-
-    let submessage = SubMsg::reply_on_success(msg_create_denom, REPLY_CREATE_DENOM_SUCCESS);
+    let submessages = msg
+        .vaults
+        .into_iter()
+        .map(|v| vault::create_vault_msg(deps.storage, &env, v))
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(Response::new()
-        .add_submessage(submessage)
+        .add_submessages(submessages)
         .add_attribute("method", "instantiate")
         .add_attribute("owner", info.sender))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidArgument {
+            msg: format!("cannot migrate from {}", stored.contract),
+        });
+    }
+
+    let legacy = migrate_legacy(deps.storage)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("legacy_layout", legacy.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -71,7 +65,9 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    use crate::execute::{delegate, deposit, gov, rbac, withdraw};
+    use crate::execute::{
+        cancel_withdrawal, delegate, deposit, finalize_withdrawal, gov, rbac, rewards, withdraw,
+    };
 
     match msg {
         ExecuteMsg::Deposit { depositor } => deposit(deps, env, info, depositor),
@@ -80,16 +76,40 @@ pub fn execute(
         }
         ExecuteMsg::Delegate {} => delegate::delegate(deps, env, info),
         ExecuteMsg::Undelegate {} => delegate::undelegate(deps, env, info),
+        ExecuteMsg::ReportYield {} => delegate::report_yield(deps, env, info),
+        ExecuteMsg::ReportLoss { denom, amount } => {
+            delegate::report_loss(deps, env, info, denom, amount)
+        }
         ExecuteMsg::Bond {} => lp::bond_lp(deps, env, info),
-        ExecuteMsg::StartUnbond { amount } => lp::start_unbond_lp(deps, env, info, amount),
+        ExecuteMsg::StartUnbond { denom, amount } => {
+            lp::start_unbond_lp(deps, env, info, denom, amount)
+        }
         ExecuteMsg::Unbond { unbond_id } => lp::finish_unbond_lp(deps, env, info, unbond_id),
-        ExecuteMsg::ChangeOwner { new_owner } => rbac::change_owner(deps, env, info, new_owner),
+        ExecuteMsg::FundRewards { denom } => rewards::fund_rewards(deps, env, info, denom),
+        ExecuteMsg::ClaimRewards { denom } => rewards::claim_rewards(deps, env, info, denom),
+        ExecuteMsg::ChangeOwner {
+            new_owner,
+            expires_at,
+        } => rbac::change_owner(deps, env, info, new_owner, expires_at),
+        ExecuteMsg::AcceptOwner {} => rbac::accept_owner(deps, env, info),
+        ExecuteMsg::CancelOwner {} => rbac::cancel_owner(deps, env, info),
         ExecuteMsg::GrantRole { role, addr } => rbac::grant_role(deps, env, info, role, addr),
         ExecuteMsg::RevokeRole { role, addr } => rbac::revoke_role(deps, env, info, role, addr),
         ExecuteMsg::Pause { expires_at } => gov::pause(deps, env, info, expires_at),
         ExecuteMsg::Release {} => gov::release(deps, env, info),
-        ExecuteMsg::ChangeConfig { unbonding_period } => {
-            gov::change_config(deps, info, unbonding_period)
+        ExecuteMsg::CreateVault(msg) => vault::create_vault(deps, env, info, msg),
+        ExecuteMsg::ChangeConfig {
+            denom,
+            unbonding_period,
+        } => gov::change_config(deps, info, denom, unbonding_period),
+        ExecuteMsg::SetWithdrawTiers { denom, tiers } => {
+            gov::set_withdraw_tiers(deps, info, denom, tiers)
+        }
+        ExecuteMsg::CancelWithdrawal { withdrawal_id } => {
+            cancel_withdrawal(deps, env, info, withdrawal_id)
+        }
+        ExecuteMsg::FinalizeWithdrawal { withdrawal_id } => {
+            finalize_withdrawal(deps, env, info, withdrawal_id)
         }
     }
 }
@@ -98,18 +118,7 @@ pub fn execute(
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         REPLY_WITHDRAW_SUBMESSAGE_FAILURE => Ok(Response::new()),
-        REPLY_CREATE_DENOM_SUCCESS => {
-            let conv_msg: MsgCreateDenomResponse = msg.result.unwrap().data.unwrap().try_into()?;
-
-            let mut denom = DENOM.load(deps.storage)?;
-            denom.lp_denom = conv_msg.new_token_denom;
-            DENOM.save(deps.storage, &denom)?;
-
-            let resp = Response::new()
-                .add_attribute("action", "reply_instantiate")
-                .add_attribute("new_denom", denom.lp_denom);
-            Ok(resp)
-        }
+        REPLY_CREATE_DENOM_SUCCESS => vault::reply_create_denom(deps, msg),
         id => Err(ContractError::ReplyIdNotFound { id }),
     }
 }
@@ -120,11 +129,23 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<QueryResponse, Contr
 
     match msg {
         QueryMsg::GetConfig {} => query::get_config(deps, env),
+        QueryMsg::GetPendingOwner {} => query::get_pending_owner(deps),
+        QueryMsg::GetVault { denom } => query::get_vault(deps, denom),
+        QueryMsg::GetVaults { start_after, limit } => query::get_vaults(deps, start_after, limit),
         QueryMsg::PauseInfo {} => query::get_paused_info(deps, env),
         QueryMsg::GetBalance { depositor } => query::get_balance(deps, env, depositor),
-        QueryMsg::GetBond { bonder } => query::get_bonds(deps, bonder),
+        QueryMsg::GetBond { bonder, denom } => query::get_bonds(deps, bonder, denom),
         QueryMsg::GetUnbond { unbond_id } => query::get_unbond(deps, unbond_id),
         QueryMsg::GetUnbondsByOwner { owner } => query::get_unbonds_by_owner(deps, owner),
-        QueryMsg::GetTotalDelegates {} => query::get_total_delegates(deps),
+        QueryMsg::GetPendingRewards { bonder, denom } => {
+            query::get_pending_rewards(deps, bonder, denom)
+        }
+        QueryMsg::GetTotalDelegates { denom } => query::get_total_delegates(deps, denom),
+        QueryMsg::GetExchangeRate { denom } => query::get_exchange_rate(deps, denom),
+        QueryMsg::GetWithdrawTiers { denom } => query::get_withdraw_tiers(deps, denom),
+        QueryMsg::GetWithdrawal { withdrawal_id } => query::get_withdrawal(deps, withdrawal_id),
+        QueryMsg::GetPendingWithdrawals { start_after, limit } => {
+            query::get_pending_withdrawals(deps, start_after, limit)
+        }
     }
 }
@@ -33,6 +33,12 @@ pub enum ContractError {
     #[error("Role Error: Addr {addr:?} has not role {role:?}")]
     RoleNotExist { addr: Addr, role: String },
 
+    #[error("No pending owner")]
+    NoPendingOwner {},
+
+    #[error("Pending owner expired")]
+    PendingOwnerExpired {},
+
     #[error("{msg:?}")]
     InvalidArgument { msg: String },
 
@@ -47,4 +53,28 @@ pub enum ContractError {
 
     #[error("Undelegate more than delegated")]
     InsufficientUndelegateAsset {},
+
+    #[error("Amount too small to mint or redeem")]
+    ZeroShareAmount {},
+
+    #[error("Loss exceeds the delegated assets")]
+    InsufficientVaultAssets {},
+
+    #[error("No bonded LP to distribute rewards to")]
+    NoBondedShares {},
+
+    #[error("No rewards to claim")]
+    NoRewards {},
+
+    #[error("Vault for {denom:?} already exists")]
+    VaultAlreadyExists { denom: String },
+
+    #[error("Withdrawal {id:?} not found")]
+    WithdrawalNotFound { id: u64 },
+
+    #[error("Withdrawal is still delayed")]
+    WithdrawalLocked {},
+
+    #[error("Withdrawal delay is over")]
+    WithdrawalReleased {},
 }
@@ -1,5 +1,5 @@
-use cosmwasm_std::{coin, CosmosMsg, DepsMut, Env, MessageInfo, Response};
-use cw_utils::must_pay;
+use cosmwasm_std::{attr, coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128};
+use cw_utils::one_coin;
 use osmosis_std::types::{
     cosmos::bank::v1beta1::MsgSend,
     osmosis::tokenfactory::v1beta1::{MsgBurn, MsgMint},
@@ -7,10 +7,13 @@ use osmosis_std::types::{
 
 use crate::{
     state::{
-        delegates::{delegate_balance, undelegate_balance},
+        delegates::{
+            add_assets, delegate_balance, sub_assets, to_assets, to_shares, undelegate_balance,
+        },
+        load_vault, load_vault_by_lp,
+        rbac::assert_owned,
         PAUSED,
     },
-    state::{DenomInfo, DENOM},
     ContractError,
 };
 
@@ -20,11 +23,16 @@ pub fn delegate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response,
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    let denom: DenomInfo = DENOM.load(deps.storage)?;
-    let balance = must_pay(&info, &denom.denom)
-        .map_err(|_| ContractError::DenomNotFound { denom: denom.denom })?;
+    let paid = one_coin(&info).map_err(|_| ContractError::AssetNotFound {})?;
+    let vault = load_vault(deps.storage, &paid.denom)?;
+    let balance = paid.amount;
 
-    let lp_amount = coin(balance.into(), denom.lp_denom);
+    let shares = to_shares(deps.storage, &vault.denom, balance)?;
+    if shares.is_zero() {
+        return Err(ContractError::ZeroShareAmount {});
+    }
+
+    let lp_amount = coin(shares.into(), vault.lp_denom);
 
     let mint_message: CosmosMsg = MsgMint {
         sender: env.contract.address.to_string(),
@@ -39,13 +47,15 @@ pub fn delegate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response,
     }
     .into();
 
-    let saved_balances = delegate_balance(deps.storage, lp_amount.amount)?;
+    add_assets(deps.storage, &vault.denom, balance)?;
+    let saved_balances = delegate_balance(deps.storage, &vault.denom, lp_amount.amount)?;
 
     Ok(Response::new()
         .add_messages(vec![mint_message, send_message])
         .add_attribute("action", "delegate")
         .add_attribute("executor", info.sender)
         .add_attribute("amount", balance)
+        .add_attribute("shares", shares)
         .add_attribute("total", saved_balances))
 }
 
@@ -55,36 +65,99 @@ pub fn undelegate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    let denom: DenomInfo = DENOM.load(deps.storage)?;
-    let balance = must_pay(&info, &denom.lp_denom).map_err(|_| ContractError::DenomNotFound {
-        denom: denom.lp_denom.clone(),
-    })?;
+    let paid = one_coin(&info).map_err(|_| ContractError::AssetNotFound {})?;
+    let vault = load_vault_by_lp(deps.storage, &paid.denom)?;
+    let balance = paid.amount;
+
+    let assets = to_assets(deps.storage, &vault.denom, balance)?;
+    if assets.is_zero() {
+        return Err(ContractError::ZeroShareAmount {});
+    }
+
     let burn_message: CosmosMsg = MsgBurn {
         sender: env.clone().contract.address.into_string(),
-        amount: Some(coin(balance.into(), denom.lp_denom).into()),
+        amount: Some(coin(balance.into(), vault.lp_denom).into()),
     }
     .into();
 
     let send_message: CosmosMsg = MsgSend {
         from_address: env.contract.address.into_string(),
         to_address: info.clone().sender.into_string(),
-        amount: vec![coin(balance.into(), denom.denom).into()],
+        amount: vec![coin(assets.into(), vault.denom.clone()).into()],
     }
     .into();
 
-    let left_amount = undelegate_balance(deps.storage, balance)?;
+    sub_assets(deps.storage, &vault.denom, assets)?;
+    let left_amount = undelegate_balance(deps.storage, &vault.denom, balance)?;
 
     Ok(Response::new()
         .add_messages(vec![burn_message, send_message])
         .add_attribute("action", "undelegate")
         .add_attribute("executor", info.sender)
-        .add_attribute("amount", balance)
+        .add_attribute("amount", assets)
+        .add_attribute("shares", balance)
         .add_attribute("total", left_amount))
 }
 
+// report_yield adds the paid underlying to the delegated assets, raising the
+// value of every LP share.
+pub fn report_yield(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    let paid = one_coin(&info).map_err(|_| ContractError::AssetNotFound {})?;
+    let vault = load_vault(deps.storage, &paid.denom)?;
+
+    let total_assets = add_assets(deps.storage, &vault.denom, paid.amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "report_yield"),
+        attr("executor", info.sender),
+        attr("denom", vault.denom),
+        attr("amount", paid.amount),
+        attr("total_assets", total_assets),
+    ]))
+}
+
+// report_loss writes down the delegated assets when the underlying was lost
+// upstream. LP holders share the loss through a lower exchange rate.
+pub fn report_loss(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    let vault = load_vault(deps.storage, &denom)?;
+    let total_assets = sub_assets(deps.storage, &vault.denom, amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "report_loss"),
+        attr("executor", info.sender),
+        attr("denom", vault.denom),
+        attr("amount", amount),
+        attr("total_assets", total_assets),
+    ]))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::state::{delegates::DELEGATE_BALANCE, DenomInfo, PauseInfo, DENOM, PAUSED};
+    use crate::state::{
+        delegates::{DELEGATE_ASSETS, DELEGATE_BALANCE},
+        rbac::OWNER,
+        PauseInfo, VaultInfo, LP_DENOMS, PAUSED, VAULTS,
+    };
     use cosmwasm_std::{
         attr, coin,
         testing::{mock_dependencies, mock_env, mock_info},
@@ -93,15 +166,22 @@ mod test {
 
     use super::*;
     const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+
+    const DENOM: &str = "uusdc";
 
-    fn mock_denom(storage: &mut dyn Storage, env: Env) -> DenomInfo {
-        let denom_info = DenomInfo {
-            denom: "uusdc".to_string(),
+    fn mock_denom(storage: &mut dyn Storage, env: Env) -> VaultInfo {
+        let vault = VaultInfo {
+            denom: DENOM.to_string(),
             lp_denom: format!("factory/{}/uusdc", env.contract.address),
+            unbonding_period: 20,
         };
 
-        DENOM.save(storage, &denom_info).unwrap();
-        denom_info
+        VAULTS.save(storage, DENOM, &vault).unwrap();
+        LP_DENOMS
+            .save(storage, &vault.lp_denom, &vault.denom)
+            .unwrap();
+        vault
     }
 
     fn resume(storage: &mut dyn Storage, now: u64) {
@@ -169,7 +249,10 @@ mod test {
         resume(deps.as_mut().storage, env.block.time.seconds());
 
         DELEGATE_BALANCE
-            .save(deps.as_mut().storage, &Uint128::new(0))
+            .save(deps.as_mut().storage, DENOM, &Uint128::new(0))
+            .unwrap();
+        DELEGATE_ASSETS
+            .save(deps.as_mut().storage, DENOM, &Uint128::new(0))
             .unwrap();
         let response = delegate(deps.as_mut(), env.clone(), info).unwrap();
 
@@ -193,6 +276,7 @@ mod test {
                 attr("action", "delegate"),
                 attr("executor", addr.to_string()),
                 attr("amount", Uint128::new(200000)),
+                attr("shares", Uint128::new(200000)),
                 attr("total", Uint128::new(200000)),
             ]
         )
@@ -239,7 +323,10 @@ mod test {
         resume(deps.as_mut().storage, env.block.time.seconds());
 
         DELEGATE_BALANCE
-            .save(deps.as_mut().storage, &Uint128::new(300000))
+            .save(deps.as_mut().storage, DENOM, &Uint128::new(300000))
+            .unwrap();
+        DELEGATE_ASSETS
+            .save(deps.as_mut().storage, DENOM, &Uint128::new(300000))
             .unwrap();
         let response = undelegate(deps.as_mut(), env.clone(), info).unwrap();
 
@@ -263,8 +350,108 @@ mod test {
                 attr("action", "undelegate"),
                 attr("executor", addr.to_string()),
                 attr("amount", Uint128::new(200000)),
+                attr("shares", Uint128::new(200000)),
                 attr("total", Uint128::new(100000)),
             ]
         )
     }
+
+    #[test]
+    fn test_delegate_after_yield() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let denom = mock_denom(deps.as_mut().storage, env.clone());
+
+        let owner = Addr::unchecked(ADDR1);
+        let addr = Addr::unchecked(ADDR2);
+
+        resume(deps.as_mut().storage, env.block.time.seconds());
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        DELEGATE_BALANCE
+            .save(deps.as_mut().storage, DENOM, &Uint128::new(1000000))
+            .unwrap();
+        DELEGATE_ASSETS
+            .save(deps.as_mut().storage, DENOM, &Uint128::new(1000000))
+            .unwrap();
+
+        let abuser_err = report_yield(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr.as_str(), &[coin(1000000, "uusdc")]),
+        )
+        .unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+
+        let reported = report_yield(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[coin(1000000, "uusdc")]),
+        )
+        .unwrap();
+        assert_eq!(
+            reported.attributes,
+            vec![
+                attr("action", "report_yield"),
+                attr("executor", owner.as_str()),
+                attr("denom", DENOM),
+                attr("amount", Uint128::new(1000000)),
+                attr("total_assets", Uint128::new(2000000)),
+            ]
+        );
+
+        // (1M + 1M virtual) shares over (2M + 1M virtual) assets
+        let info = mock_info(addr.as_str(), &[coin(300000, "uusdc")]);
+        let response = delegate(deps.as_mut(), env.clone(), info).unwrap();
+        assert_eq!(
+            response.messages[0],
+            SubMsg::new(MsgMint {
+                sender: env.contract.address.to_string(),
+                amount: Some(coin(200000, denom.lp_denom.clone()).into()),
+            })
+        );
+
+        let dust = delegate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr.as_str(), &[coin(1, "uusdc")]),
+        )
+        .unwrap_err();
+        assert!(matches!(dust, ContractError::ZeroShareAmount {}));
+
+        let info = mock_info(addr.as_str(), &[coin(200000, denom.lp_denom.clone())]);
+        let response = undelegate(deps.as_mut(), env.clone(), info).unwrap();
+        assert_eq!(
+            response.messages[1],
+            SubMsg::new(MsgSend {
+                from_address: env.contract.address.to_string(),
+                to_address: addr.to_string(),
+                amount: vec![coin(300000, "uusdc").into()],
+            })
+        );
+
+        report_loss(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            DENOM.to_string(),
+            Uint128::new(1500000),
+        )
+        .unwrap();
+        let too_much = report_loss(
+            deps.as_mut(),
+            env,
+            mock_info(owner.as_str(), &[]),
+            DENOM.to_string(),
+            Uint128::new(1000000),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            too_much,
+            ContractError::InsufficientVaultAssets {}
+        ));
+        assert_eq!(
+            DELEGATE_ASSETS.load(deps.as_ref().storage, DENOM).unwrap(),
+            Uint128::new(500000)
+        );
+    }
 }
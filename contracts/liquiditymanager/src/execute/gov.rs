@@ -1,38 +1,51 @@
 use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response};
+use mitosis_interface::liquidity_manager::WithdrawTier;
 
 use crate::{
-    state::{rbac::assert_owned, CONFIG, PAUSED},
+    state::{
+        load_vault,
+        rbac::{assert_owned, assert_pauser},
+        withdrawals, PAUSED, VAULTS,
+    },
     ContractError,
 };
 
+// pause can be called by the owner or a guardian. Without expires_at, the
+// contract stays paused until the owner releases it.
 pub fn pause(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    expires_at: u64,
+    expires_at: Option<u64>,
 ) -> Result<Response, ContractError> {
-    assert_owned(deps.storage, info.sender.clone())?;
+    let role = assert_pauser(deps.storage, info.sender.clone())?;
 
     let mut pause_info = PAUSED
         .load(deps.storage)?
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    if env.block.time.seconds() >= expires_at {
-        return Err(ContractError::InvalidArgument {
-            msg: "expires_at must be in the future".to_string(),
-        });
+    if let Some(expires_at) = expires_at {
+        if env.block.time.seconds() >= expires_at {
+            return Err(ContractError::InvalidArgument {
+                msg: "expires_at must be in the future".to_string(),
+            });
+        }
     }
 
     pause_info.paused = true;
-    pause_info.expires_at = Some(expires_at);
+    pause_info.expires_at = expires_at;
 
     PAUSED.save(deps.storage, &pause_info)?;
 
     let response = Response::new().add_attributes(vec![
         attr("action", "pause"),
         attr("executor", info.sender),
-        attr("expires_at", pause_info.expires_at.unwrap().to_string()),
+        attr("role", role),
+        attr(
+            "expires_at",
+            expires_at.map_or("never".to_string(), |e| e.to_string()),
+        ),
     ]);
 
     Ok(response)
@@ -59,32 +72,67 @@ pub fn release(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, C
 pub fn change_config(
     deps: DepsMut,
     info: MessageInfo,
+    denom: String,
     unbonding_period: u64,
 ) -> Result<Response, ContractError> {
     assert_owned(deps.storage, info.sender.clone())?;
 
-    let mut config = CONFIG.load(deps.storage)?;
-    config.unbonding_period = unbonding_period;
-    CONFIG.save(deps.storage, &config)?;
+    let mut vault = load_vault(deps.storage, &denom)?;
+    vault.unbonding_period = unbonding_period;
+    VAULTS.save(deps.storage, &denom, &vault)?;
 
     let response = Response::new().add_attributes(vec![
         attr("action", "change_config"),
         attr("executor", info.sender),
+        attr("denom", denom),
         attr("unbonding_period", unbonding_period.to_string()),
     ]);
 
     Ok(response)
 }
 
+// set_withdraw_tiers covers deposits of any denom, not only vault underlyings.
+pub fn set_withdraw_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    tiers: Vec<WithdrawTier>,
+) -> Result<Response, ContractError> {
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    // no tiers clears the denom's, attribute values can't be empty
+    let tiers_attr = match tiers.is_empty() {
+        true => "none".to_string(),
+        false => tiers
+            .iter()
+            .map(|tier| format!("{}:{}", tier.min_amount, tier.delay))
+            .collect::<Vec<_>>()
+            .join(","),
+    };
+    withdrawals::set_withdraw_tiers(deps.storage, &denom, tiers)?;
+
+    let response = Response::new().add_attributes(vec![
+        attr("action", "set_withdraw_tiers"),
+        attr("executor", info.sender),
+        attr("denom", denom),
+        attr("tiers", tiers_attr),
+    ]);
+
+    Ok(response)
+}
+
 #[cfg(test)]
 mod test {
     use cosmwasm_std::{
         testing::{mock_dependencies, mock_env, mock_info},
-        Addr, Storage,
+        Addr, Storage, Uint128,
     };
 
     use crate::{
-        state::{rbac::OWNER, PauseInfo, PAUSED},
+        state::{
+            rbac::{ADDR_ROLE, GUARDIAN_ROLE, OWNER},
+            PauseInfo, PAUSED,
+        },
         ContractError,
     };
 
@@ -108,7 +156,7 @@ mod test {
         mock_owner(deps.as_mut().storage, owner);
         let info = mock_info(abuser.as_str(), &[]);
 
-        let unauth_pause = pause(deps.as_mut(), env.clone(), info.clone(), 0).unwrap_err();
+        let unauth_pause = pause(deps.as_mut(), env.clone(), info.clone(), Some(0)).unwrap_err();
         assert!(matches!(unauth_pause, ContractError::Unauthorized {}));
 
         let unauth_release = release(deps.as_mut(), env, info).unwrap_err();
@@ -137,7 +185,7 @@ mod test {
             deps.as_mut(),
             env.clone(),
             info.clone(),
-            env.block.time.seconds() + 1,
+            Some(env.block.time.seconds() + 1),
         )
         .unwrap_err();
 
@@ -167,7 +215,7 @@ mod test {
             deps.as_mut(),
             env.clone(),
             info,
-            env.block.time.seconds() - 1,
+            Some(env.block.time.seconds() - 1),
         )
         .unwrap_err();
 
@@ -189,13 +237,14 @@ mod test {
 
         let expires_at: u64 = env.block.time.seconds() + 1;
 
-        let response = pause(deps.as_mut(), env, info, expires_at).unwrap();
+        let response = pause(deps.as_mut(), env, info, Some(expires_at)).unwrap();
 
         assert_eq!(
             response.attributes,
             vec![
                 attr("action", "pause"),
                 attr("executor", owner.to_string()),
+                attr("role", "owner"),
                 attr("expires_at", expires_at.to_string())
             ]
         )
@@ -232,4 +281,108 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn test_guardian_pause() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let guardian = Addr::unchecked(ADDR2);
+        let info = mock_info(guardian.as_str(), &[]);
+
+        mock_owner(deps.as_mut().storage, owner);
+        ADDR_ROLE
+            .save(
+                deps.as_mut().storage,
+                (GUARDIAN_ROLE.to_string(), guardian.clone()),
+                &true,
+            )
+            .unwrap();
+        PAUSED
+            .save(deps.as_mut().storage, &Default::default())
+            .unwrap();
+
+        // paused until the owner releases it
+        let response = pause(deps.as_mut(), env.clone(), info.clone(), None).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "pause"),
+                attr("executor", guardian.to_string()),
+                attr("role", "guardian"),
+                attr("expires_at", "never"),
+            ]
+        );
+
+        let mut later = env;
+        later.block.time = later.block.time.plus_seconds(365 * 24 * 3600);
+        let paused = PAUSED
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .refresh(deps.as_mut().storage, &later)
+            .unwrap();
+        assert!(paused.paused);
+
+        let unauth_release = release(deps.as_mut(), later, info).unwrap_err();
+        assert!(matches!(unauth_release, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_set_withdraw_tiers() {
+        let mut deps = mock_dependencies();
+
+        let owner = Addr::unchecked(ADDR1);
+        mock_owner(deps.as_mut().storage, owner.clone());
+
+        let tiers = vec![
+            WithdrawTier {
+                min_amount: Uint128::new(1000),
+                delay: 60,
+            },
+            WithdrawTier {
+                min_amount: Uint128::new(5000),
+                delay: 600,
+            },
+        ];
+
+        let unauthorized = set_withdraw_tiers(
+            deps.as_mut(),
+            mock_info(ADDR2, &[]),
+            "uosmo".to_string(),
+            tiers.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(unauthorized, ContractError::Unauthorized {}));
+
+        let response = set_withdraw_tiers(
+            deps.as_mut(),
+            mock_info(owner.as_str(), &[]),
+            "uosmo".to_string(),
+            tiers,
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "set_withdraw_tiers"),
+                attr("executor", owner.to_string()),
+                attr("denom", "uosmo"),
+                attr("tiers", "1000:60,5000:600"),
+            ]
+        );
+
+        let response = set_withdraw_tiers(
+            deps.as_mut(),
+            mock_info(owner.as_str(), &[]),
+            "uosmo".to_string(),
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(response.attributes[3], attr("tiers", "none"));
+        assert!(withdrawals::WITHDRAW_TIERS
+            .may_load(&deps.storage, "uosmo")
+            .unwrap()
+            .is_none());
+    }
 }
@@ -1,11 +1,11 @@
 use cosmwasm_std::{attr, coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128};
-use cw_utils::must_pay;
+use cw_utils::one_coin;
 use osmosis_std::types::cosmos::bank::v1beta1::MsgSend;
 
 use crate::{
     state::{
         bond::{self},
-        DenomInfo, DENOM, PAUSED,
+        load_vault, load_vault_by_lp, rewards, PAUSED,
     },
     ContractError,
 };
@@ -16,16 +16,23 @@ pub fn bond_lp(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, C
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    let denom: DenomInfo = DENOM.load(deps.storage)?;
-    let balance = must_pay(&info, &denom.lp_denom).map_err(|_| ContractError::DenomNotFound {
-        denom: denom.lp_denom.clone(),
-    })?;
+    let paid = one_coin(&info).map_err(|_| ContractError::AssetNotFound {})?;
+    let vault = load_vault_by_lp(deps.storage, &paid.denom)?;
+    let balance = paid.amount;
 
-    let bond_info = bond::bond(deps.storage, env, info.sender.clone(), balance)?;
+    rewards::add_stake(deps.storage, &vault.denom, info.sender.clone(), balance)?;
+    let bond_info = bond::bond(
+        deps.storage,
+        env,
+        &vault.denom,
+        info.sender.clone(),
+        balance,
+    )?;
 
     let respnose = Response::new().add_attributes(vec![
         attr("action", "bond"),
         attr("executor", info.sender),
+        attr("denom", vault.denom),
         attr("amount", bond_info.amount),
     ]);
 
@@ -36,6 +43,7 @@ pub fn start_unbond_lp(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    denom: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     PAUSED
@@ -43,11 +51,16 @@ pub fn start_unbond_lp(
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    let unbond_info = bond::start_unbond(deps.storage, env, info.sender.clone(), amount)?;
+    let vault = load_vault(deps.storage, &denom)?;
+
+    let unbond_info = bond::start_unbond(deps.storage, env, &vault, info.sender.clone(), amount)?;
+    // unbonding LP stops earning right away
+    rewards::remove_stake(deps.storage, &vault.denom, info.sender.clone(), amount)?;
 
     let response = Response::new().add_attributes(vec![
         attr("action", "start_unbond"),
         attr("executor", info.sender),
+        attr("denom", vault.denom),
         attr("unbond_id", unbond_info.unbond_id.to_string()),
         attr("amount", unbond_info.amount),
     ]);
@@ -66,15 +79,14 @@ pub fn finish_unbond_lp(
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    let denom: DenomInfo = DENOM.load(deps.storage)?;
-
     let unbond_info =
         bond::finish_unbond(deps.storage, env.clone(), info.sender.clone(), unbond_id)?;
+    let vault = load_vault(deps.storage, &unbond_info.denom)?;
 
     let message: CosmosMsg = MsgSend {
         from_address: env.contract.address.into_string(),
         to_address: info.sender.clone().into_string(),
-        amount: vec![coin(unbond_info.amount.u128(), denom.lp_denom).into()],
+        amount: vec![coin(unbond_info.amount.u128(), vault.lp_denom).into()],
     }
     .into();
 
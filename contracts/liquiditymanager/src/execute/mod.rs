@@ -6,6 +6,8 @@ pub mod delegate;
 pub mod gov;
 pub mod lp;
 pub mod rbac;
+pub mod rewards;
+pub mod vault;
 
 pub use deposit::deposit;
-pub use withdraw::withdraw;
+pub use withdraw::{cancel_withdrawal, finalize_withdrawal, withdraw};
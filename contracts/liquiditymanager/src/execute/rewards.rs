@@ -0,0 +1,221 @@
+use cosmwasm_std::{attr, CosmosMsg, DepsMut, Env, MessageInfo, Response};
+use osmosis_std::types::cosmos::bank::v1beta1::MsgSend;
+
+use crate::{
+    state::{
+        load_vault,
+        rbac::{assert_owned, assert_role, DISTRIBUTOR_ROLE},
+        rewards, PAUSED,
+    },
+    ContractError,
+};
+
+// fund_rewards splits the sent funds over the currently bonded LP.
+pub fn fund_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    if assert_owned(deps.storage, info.sender.clone()).is_err() {
+        assert_role(
+            deps.storage,
+            DISTRIBUTOR_ROLE.to_string(),
+            info.sender.clone(),
+        )
+        .map_err(|_| ContractError::Unauthorized {})?;
+    }
+
+    if info.funds.is_empty() || info.funds.iter().any(|c| c.amount.is_zero()) {
+        return Err(ContractError::AssetNotFound {});
+    }
+
+    let vault = load_vault(deps.storage, &denom)?;
+    rewards::fund(deps.storage, &vault.denom, &info.funds)?;
+
+    let funded = info
+        .funds
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "fund_rewards"),
+        attr("executor", info.sender),
+        attr("denom", vault.denom),
+        attr("amount", funded),
+    ]))
+}
+
+pub fn claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    let vault = load_vault(deps.storage, &denom)?;
+    let claimed = rewards::claim(deps.storage, &vault.denom, info.sender.clone())?;
+    if claimed.is_empty() {
+        return Err(ContractError::NoRewards {});
+    }
+
+    let claimed_attr = claimed
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let message: CosmosMsg = MsgSend {
+        from_address: env.contract.address.into_string(),
+        to_address: info.sender.to_string(),
+        amount: claimed.into_iter().map(Into::into).collect(),
+    }
+    .into();
+
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        attr("action", "claim_rewards"),
+        attr("executor", info.sender),
+        attr("denom", vault.denom),
+        attr("amount", claimed_attr),
+    ]))
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        coin,
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, SubMsg, Uint128,
+    };
+
+    use crate::state::{
+        rbac::{ADDR_ROLE, OWNER},
+        rewards::add_stake,
+        VaultInfo, VAULTS,
+    };
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+    const ADDR3: &str = "addr3";
+    const DENOM: &str = "uusdc";
+
+    #[test]
+    fn test_fund_and_claim_rewards() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let distributor = Addr::unchecked(ADDR2);
+        let bonder = Addr::unchecked(ADDR3);
+
+        PAUSED
+            .save(deps.as_mut().storage, &Default::default())
+            .unwrap();
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+        ADDR_ROLE
+            .save(
+                deps.as_mut().storage,
+                (DISTRIBUTOR_ROLE.to_string(), distributor.clone()),
+                &true,
+            )
+            .unwrap();
+        VAULTS
+            .save(
+                deps.as_mut().storage,
+                DENOM,
+                &VaultInfo {
+                    denom: DENOM.to_string(),
+                    lp_denom: "factory/contract/uusdc".to_string(),
+                    unbonding_period: 20,
+                },
+            )
+            .unwrap();
+        add_stake(
+            deps.as_mut().storage,
+            DENOM,
+            bonder.clone(),
+            Uint128::new(1000),
+        )
+        .unwrap();
+
+        let abuser_err = fund_rewards(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(bonder.as_str(), &[coin(100, "uosmo")]),
+            DENOM.to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+
+        let empty_err = fund_rewards(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            DENOM.to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(empty_err, ContractError::AssetNotFound {}));
+
+        fund_rewards(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[coin(100, "uosmo")]),
+            DENOM.to_string(),
+        )
+        .unwrap();
+        let funded = fund_rewards(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(distributor.as_str(), &[coin(50, "uosmo")]),
+            DENOM.to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            funded.attributes,
+            vec![
+                attr("action", "fund_rewards"),
+                attr("executor", distributor.as_str()),
+                attr("denom", DENOM),
+                attr("amount", "50uosmo"),
+            ]
+        );
+
+        let claimed = claim_rewards(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(bonder.as_str(), &[]),
+            DENOM.to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            claimed.messages,
+            vec![SubMsg::new(MsgSend {
+                from_address: env.contract.address.to_string(),
+                to_address: bonder.to_string(),
+                amount: vec![coin(150, "uosmo").into()],
+            })]
+        );
+
+        let nothing = claim_rewards(
+            deps.as_mut(),
+            env,
+            mock_info(bonder.as_str(), &[]),
+            DENOM.to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(nothing, ContractError::NoRewards {}));
+    }
+}
@@ -0,0 +1,229 @@
+use cosmwasm_std::{
+    attr, CosmosMsg, DepsMut, Env, MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg,
+};
+use mitosis_interface::liquidity_manager::VaultMsg;
+use osmosis_std::types::osmosis::tokenfactory::v1beta1::{MsgCreateDenom, MsgCreateDenomResponse};
+
+use crate::{
+    execute::consts::REPLY_CREATE_DENOM_SUCCESS,
+    state::{
+        delegates::init_vault_balances, rbac::assert_owned, PendingVault, VaultInfo, LP_DENOMS,
+        PAUSED, PENDING_VAULTS, VAULTS,
+    },
+    ContractError,
+};
+
+// create_vault_msg parks the vault until its LP denom is created, the reply of
+// MsgCreateDenom registers it.
+pub fn create_vault_msg(
+    storage: &mut dyn Storage,
+    env: &Env,
+    vault: VaultMsg,
+) -> Result<SubMsg, ContractError> {
+    if vault.denom.is_empty() || vault.lp_subdenom.is_empty() {
+        return Err(ContractError::InvalidArgument {
+            msg: "denom and lp_subdenom must not be empty".to_string(),
+        });
+    }
+
+    let pending = PENDING_VAULTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let is_pending = pending
+        .iter()
+        .any(|(subdenom, p)| subdenom == &vault.lp_subdenom || p.denom == vault.denom);
+
+    if VAULTS.has(storage, &vault.denom) || is_pending {
+        return Err(ContractError::VaultAlreadyExists { denom: vault.denom });
+    }
+
+    PENDING_VAULTS.save(
+        storage,
+        &vault.lp_subdenom,
+        &PendingVault {
+            denom: vault.denom,
+            unbonding_period: vault.unbonding_period,
+        },
+    )?;
+
+    let msg_create_denom: CosmosMsg = MsgCreateDenom {
+        sender: env.contract.address.to_string(),
+        subdenom: vault.lp_subdenom,
+    }
+    .into();
+
+    Ok(SubMsg::reply_on_success(
+        msg_create_denom,
+        REPLY_CREATE_DENOM_SUCCESS,
+    ))
+}
+
+pub fn create_vault(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault: VaultMsg,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    assert_owned(deps.storage, info.sender.clone())?;
+
+    let denom = vault.denom.clone();
+    let submessage = create_vault_msg(deps.storage, &env, vault)?;
+
+    Ok(Response::new()
+        .add_submessage(submessage)
+        .add_attributes(vec![
+            attr("action", "create_vault"),
+            attr("executor", info.sender),
+            attr("denom", denom),
+        ]))
+}
+
+pub fn reply_create_denom(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let conv_msg: MsgCreateDenomResponse = msg.result.unwrap().data.unwrap().try_into()?;
+    let lp_denom = conv_msg.new_token_denom;
+
+    // factory/{contract}/{subdenom}
+    let subdenom = lp_denom.rsplit('/').next().unwrap_or_default();
+    let pending = PENDING_VAULTS.load(deps.storage, subdenom)?;
+    PENDING_VAULTS.remove(deps.storage, subdenom);
+
+    let vault = VaultInfo {
+        denom: pending.denom,
+        lp_denom,
+        unbonding_period: pending.unbonding_period,
+    };
+    VAULTS.save(deps.storage, &vault.denom, &vault)?;
+    LP_DENOMS.save(deps.storage, &vault.lp_denom, &vault.denom)?;
+    init_vault_balances(deps.storage, &vault.denom)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reply_create_vault")
+        .add_attribute("denom", vault.denom)
+        .add_attribute("new_denom", vault.lp_denom))
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, Binary, SubMsgResponse, SubMsgResult,
+    };
+
+    use crate::state::{load_vault, load_vault_by_lp, rbac::OWNER};
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+
+    fn vault_msg(denom: &str, lp_subdenom: &str) -> VaultMsg {
+        VaultMsg {
+            denom: denom.to_string(),
+            lp_subdenom: lp_subdenom.to_string(),
+            unbonding_period: 20,
+        }
+    }
+
+    fn created(lp_denom: String) -> Reply {
+        Reply {
+            id: REPLY_CREATE_DENOM_SUCCESS,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(MsgCreateDenomResponse {
+                    new_token_denom: lp_denom,
+                })),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_create_vault() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = Addr::unchecked(ADDR1);
+        let abuser = Addr::unchecked(ADDR2);
+
+        PAUSED
+            .save(deps.as_mut().storage, &Default::default())
+            .unwrap();
+        OWNER.save(deps.as_mut().storage, &owner).unwrap();
+
+        let abuser_err = create_vault(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(abuser.as_str(), &[]),
+            vault_msg("uatom", "lpatom"),
+        )
+        .unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+
+        let response = create_vault(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            vault_msg("uatom", "lpatom"),
+        )
+        .unwrap();
+        assert_eq!(
+            response.messages,
+            vec![SubMsg::reply_on_success(
+                MsgCreateDenom {
+                    sender: env.contract.address.to_string(),
+                    subdenom: "lpatom".to_string(),
+                },
+                REPLY_CREATE_DENOM_SUCCESS
+            )]
+        );
+
+        // the vault only exists once its LP denom does
+        assert!(load_vault(deps.as_ref().storage, "uatom").is_err());
+
+        let pending_err = create_vault(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            vault_msg("uosmo", "lpatom"),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            pending_err,
+            ContractError::VaultAlreadyExists { .. }
+        ));
+
+        let lp_denom = format!("factory/{}/lpatom", env.contract.address);
+        reply_create_denom(deps.as_mut(), created(lp_denom.clone())).unwrap();
+
+        let vault = load_vault(deps.as_ref().storage, "uatom").unwrap();
+        assert_eq!(
+            vault,
+            VaultInfo {
+                denom: "uatom".to_string(),
+                lp_denom: lp_denom.clone(),
+                unbonding_period: 20,
+            }
+        );
+        assert_eq!(
+            load_vault_by_lp(deps.as_ref().storage, &lp_denom).unwrap(),
+            vault
+        );
+        assert!(!PENDING_VAULTS.has(deps.as_ref().storage, "lpatom"));
+
+        let exists_err = create_vault(
+            deps.as_mut(),
+            env,
+            mock_info(owner.as_str(), &[]),
+            vault_msg("uatom", "lpatom2"),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            exists_err,
+            ContractError::VaultAlreadyExists { .. }
+        ));
+    }
+}
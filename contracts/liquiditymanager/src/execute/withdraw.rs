@@ -1,7 +1,12 @@
 use cosmwasm_std::{attr, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Response};
 
 use crate::{
-    state::{balances::withdraw_balance, rbac::assert_owned, PAUSED},
+    state::{
+        balances::withdraw_balance,
+        rbac::{assert_pauser, assert_role, GATEWAY_ROLE},
+        withdrawals::{self, queue_withdrawal, withdraw_delay},
+        PAUSED,
+    },
     ContractError,
 };
 
@@ -17,16 +22,44 @@ pub fn withdraw(
         .refresh(deps.storage, &env)?
         .assert_not_paused()?;
 
-    assert_owned(deps.storage, info.sender.clone())?;
+    // only gateways withdraw, and only out of their own deposit
+    assert_role(deps.storage, GATEWAY_ROLE.to_string(), info.sender.clone())
+        .map_err(|_| ContractError::Unauthorized {})?;
 
-    // TODO: can be weakness point
     let withdrawer = match withdrawer {
         Some(withdrawer) => withdrawer,
         None => info.sender.clone(),
     };
 
+    let now = env.block.time.seconds();
     let withdraw_result =
-        withdraw_balance(deps.storage, env, info.clone(), withdrawer.clone(), amount)?;
+        withdraw_balance(deps.storage, env, info.clone(), info.sender.clone(), amount)?;
+
+    // large amounts leave the deposit now but are only paid out after a delay
+    let delay = withdraw_delay(deps.storage, &withdraw_result, now)?;
+    if delay > 0 {
+        let release_at = now
+            .checked_add(delay)
+            .ok_or_else(|| ContractError::InvalidArgument {
+                msg: "release time overflow".to_string(),
+            })?;
+        let withdrawal = queue_withdrawal(
+            deps.storage,
+            info.sender.clone(),
+            withdrawer.clone(),
+            withdraw_result,
+            release_at,
+        )?;
+
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "queue_withdraw"),
+            attr("executor", info.sender),
+            attr("withdrawer", withdrawer),
+            attr("withdrawal_id", withdrawal.withdrawal_id.to_string()),
+            attr("amount", withdrawal.amount.to_string()),
+            attr("release_at", withdrawal.release_at.to_string()),
+        ]));
+    }
 
     let withdraw_message = BankMsg::Send {
         to_address: withdrawer.to_string(),
@@ -43,14 +76,73 @@ pub fn withdraw(
     Ok(response)
 }
 
+// cancel_withdrawal is left out of the pause on purpose, so that a guardian can
+// pause and then cancel what is queued.
+pub fn cancel_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    withdrawal_id: u64,
+) -> Result<Response, ContractError> {
+    let role = assert_pauser(deps.storage, info.sender.clone())?;
+
+    let withdrawal =
+        withdrawals::cancel_withdrawal(deps.storage, withdrawal_id, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_withdrawal"),
+        attr("executor", info.sender),
+        attr("role", role),
+        attr("withdrawal_id", withdrawal_id.to_string()),
+        attr("depositor", withdrawal.depositor),
+        attr("amount", withdrawal.amount.to_string()),
+    ]))
+}
+
+pub fn finalize_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    withdrawal_id: u64,
+) -> Result<Response, ContractError> {
+    PAUSED
+        .load(deps.storage)?
+        .refresh(deps.storage, &env)?
+        .assert_not_paused()?;
+
+    let withdrawal =
+        withdrawals::finalize_withdrawal(deps.storage, withdrawal_id, env.block.time.seconds())?;
+
+    let withdraw_message = BankMsg::Send {
+        to_address: withdrawal.withdrawer.to_string(),
+        amount: vec![withdrawal.amount.clone()],
+    };
+
+    Ok(Response::new()
+        .add_message(withdraw_message)
+        .add_attributes(vec![
+            attr("action", "finalize_withdrawal"),
+            attr("executor", info.sender),
+            attr("withdrawal_id", withdrawal_id.to_string()),
+            attr("withdrawer", withdrawal.withdrawer),
+            attr("amount", withdrawal.amount.to_string()),
+        ]))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::state::{balances::BALANCE, rbac::OWNER, PauseInfo, PAUSED};
+    use crate::state::{
+        balances::BALANCE,
+        rbac::{ADDR_ROLE, GUARDIAN_ROLE, OWNER},
+        withdrawals::{init_withdrawals_id, set_withdraw_tiers},
+        PauseInfo, PAUSED,
+    };
     use cosmwasm_std::{
         attr, coin,
         testing::{mock_dependencies, mock_env, mock_info},
         Addr, Storage, SubMsg, Uint128,
     };
+    use mitosis_interface::liquidity_manager::WithdrawTier;
 
     use super::*;
 
@@ -98,6 +190,12 @@ mod test {
         OWNER.save(storage, &owner).unwrap();
     }
 
+    fn mock_gateway(storage: &mut dyn Storage, gateway: Addr) {
+        ADDR_ROLE
+            .save(storage, (GATEWAY_ROLE.to_string(), gateway), &true)
+            .unwrap();
+    }
+
     #[test]
     fn test_withdraw_paused() {
         let mut deps = mock_dependencies();
@@ -126,7 +224,7 @@ mod test {
         let env = mock_env();
 
         resume(deps.as_mut().storage, env.block.time.seconds());
-        mock_owner(deps.as_mut().storage, owner_addr);
+        mock_owner(deps.as_mut().storage, owner_addr.clone());
 
         let try_withdraw = withdraw(
             deps.as_mut(),
@@ -140,6 +238,17 @@ mod test {
         println!("{}", try_withdraw);
 
         assert!(matches!(try_withdraw, ContractError::Unauthorized {}));
+
+        // the owner is not a gateway
+        let try_withdraw = withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner_addr.as_str(), &[]),
+            None,
+            coin(100000, DENOM.to_string()),
+        )
+        .unwrap_err();
+        assert!(matches!(try_withdraw, ContractError::Unauthorized {}));
     }
 
     #[test]
@@ -154,7 +263,7 @@ mod test {
         let info = mock_info(sender.as_str(), &[]);
         let amount = coin(50000, DENOM.to_string());
 
-        mock_owner(deps.as_mut().storage, sender.clone());
+        mock_gateway(deps.as_mut().storage, sender.clone());
         mock_balances(deps.as_mut().storage);
 
         // Test unspecified accounts wallet
@@ -209,6 +318,20 @@ mod test {
                 amount: vec![amount,]
             })]
         );
+
+        // both withdrawals came out of the gateway's own deposit
+        assert_eq!(
+            BALANCE
+                .load(deps.as_ref().storage, (sender, DENOM.to_string()))
+                .unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            BALANCE
+                .load(deps.as_ref().storage, (withdrawer, DENOM.to_string()))
+                .unwrap(),
+            Uint128::new(200000)
+        );
     }
 
     #[test]
@@ -223,7 +346,7 @@ mod test {
         let info = mock_info(sender.as_str(), &[]);
         let amount = coin(300000, DENOM.to_string());
 
-        mock_owner(deps.as_mut().storage, sender);
+        mock_gateway(deps.as_mut().storage, sender);
         let resp = withdraw(
             deps.as_mut(),
             env.clone(),
@@ -252,7 +375,7 @@ mod test {
         let info = mock_info(sender.as_str(), &[]);
         let amount = coin(300000, DENOM.to_string());
 
-        mock_owner(deps.as_mut().storage, sender);
+        mock_gateway(deps.as_mut().storage, sender);
         mock_balances(deps.as_mut().storage);
 
         let resp = withdraw(
@@ -268,10 +391,135 @@ mod test {
             ContractError::InsufficientWithdrawableAsset { .. }
         ));
 
-        let resp = withdraw(deps.as_mut(), env, info, Some(withdrawer), amount).unwrap_err();
+        // the withdrawer's deposit would cover it, the gateway's does not
+        let resp = withdraw(
+            deps.as_mut(),
+            env,
+            info,
+            Some(withdrawer),
+            coin(150000, DENOM.to_string()),
+        )
+        .unwrap_err();
         assert!(matches!(
             resp,
             ContractError::InsufficientWithdrawableAsset { .. }
         ));
     }
+
+    #[test]
+    fn test_queued_withdraw() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        resume(deps.as_mut().storage, env.block.time.seconds());
+
+        let gateway = Addr::unchecked(ADDR1);
+        let withdrawer = Addr::unchecked(ADDR2);
+        let owner = Addr::unchecked("owner");
+        let guardian = Addr::unchecked("guardian");
+
+        mock_owner(deps.as_mut().storage, owner);
+        mock_gateway(deps.as_mut().storage, gateway.clone());
+        mock_balances(deps.as_mut().storage);
+        ADDR_ROLE
+            .save(
+                deps.as_mut().storage,
+                (GUARDIAN_ROLE.to_string(), guardian.clone()),
+                &true,
+            )
+            .unwrap();
+        init_withdrawals_id(deps.as_mut().storage).unwrap();
+        set_withdraw_tiers(
+            deps.as_mut().storage,
+            DENOM,
+            vec![WithdrawTier {
+                min_amount: Uint128::new(50000),
+                delay: 600,
+            }],
+        )
+        .unwrap();
+
+        let release_at = env.block.time.seconds() + 600;
+        let queued = withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(gateway.as_str(), &[]),
+            Some(withdrawer.clone()),
+            coin(60000, DENOM),
+        )
+        .unwrap();
+        assert!(queued.messages.is_empty());
+        assert_eq!(
+            queued.attributes,
+            vec![
+                attr("action", "queue_withdraw"),
+                attr("executor", gateway.as_str()),
+                attr("withdrawer", withdrawer.as_str()),
+                attr("withdrawal_id", "0"),
+                attr("amount", "60000uosmo"),
+                attr("release_at", release_at.to_string()),
+            ]
+        );
+
+        // the withdrawer cannot cancel, a guardian can and the deposit is restored
+        let abuser_err = cancel_withdrawal(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(withdrawer.as_str(), &[]),
+            0,
+        )
+        .unwrap_err();
+        assert!(matches!(abuser_err, ContractError::Unauthorized {}));
+        cancel_withdrawal(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(guardian.as_str(), &[]),
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            BALANCE
+                .load(deps.as_ref().storage, (gateway.clone(), DENOM.to_string()))
+                .unwrap(),
+            Uint128::new(100000)
+        );
+
+        withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(gateway.as_str(), &[]),
+            Some(withdrawer.clone()),
+            coin(60000, DENOM),
+        )
+        .unwrap();
+        let locked = finalize_withdrawal(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(withdrawer.as_str(), &[]),
+            1,
+        )
+        .unwrap_err();
+        assert!(matches!(locked, ContractError::WithdrawalLocked {}));
+
+        env.block.time = env.block.time.plus_seconds(600);
+        let released = cancel_withdrawal(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(guardian.as_str(), &[]),
+            1,
+        )
+        .unwrap_err();
+        assert!(matches!(released, ContractError::WithdrawalReleased {}));
+
+        let finalized =
+            finalize_withdrawal(deps.as_mut(), env, mock_info(withdrawer.as_str(), &[]), 1)
+                .unwrap();
+        assert_eq!(
+            finalized.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: withdrawer.to_string(),
+                amount: vec![coin(60000, DENOM)],
+            })]
+        );
+    }
 }
@@ -1,6 +1,7 @@
 pub mod contracts;
 mod error;
 pub mod execute;
+pub mod migrations;
 pub mod query;
 pub mod state;
 
@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+
+use crate::{
+    state::{
+        bond::{unbonds, BondInfo, UnbondInfo, BONDS, BONDS_KEY, UNBONDS_KEY},
+        delegates::{DELEGATE_ASSETS, DELEGATE_BALANCE, DELEGATE_BALANCES_KEY},
+        rewards::{STAKED, TOTAL_STAKED},
+        withdrawals::{init_withdrawals_id, WITHDRAWALS_ID},
+        VaultInfo, LP_DENOMS, VAULTS,
+    },
+    ContractError,
+};
+
+// managers instantiated before vaults held a single asset under these keys
+pub const LEGACY_DENOM_KEY: &str = "denom";
+pub const LEGACY_DENOM: Item<LegacyDenomInfo> = Item::new(LEGACY_DENOM_KEY);
+
+pub const LEGACY_CONFIG_KEY: &str = "config";
+pub const LEGACY_CONFIG: Item<LegacyConfigInfo> = Item::new(LEGACY_CONFIG_KEY);
+
+pub const LEGACY_DELEGATE_BALANCE: Item<Uint128> = Item::new(DELEGATE_BALANCES_KEY);
+pub const LEGACY_BONDS: Map<Addr, BondInfo> = Map::new(BONDS_KEY);
+pub const LEGACY_UNBONDS: Map<u64, LegacyUnbondInfo> = Map::new(UNBONDS_KEY);
+
+#[cw_serde]
+pub struct LegacyDenomInfo {
+    pub denom: String,
+    pub lp_denom: String,
+}
+
+#[cw_serde]
+pub struct LegacyConfigInfo {
+    pub unbonding_period: u64,
+}
+
+#[cw_serde]
+pub struct LegacyUnbondInfo {
+    pub unbond_id: u64,
+    pub owner: Addr,
+    pub amount: Uint128,
+    pub unbond_time: u64,
+}
+
+// migrate_legacy upgrades a manager still on the single asset layout. Its asset
+// becomes a vault, and the supply, bonds and unbonds are moved under the
+// vault's denom. LP was minted 1:1 back then, so the supply is also the
+// vault's assets. Returns whether there was anything to upgrade.
+pub fn migrate_legacy(storage: &mut dyn Storage) -> Result<bool, ContractError> {
+    let legacy = match LEGACY_DENOM.may_load(storage)? {
+        Some(legacy) => legacy,
+        None => return Ok(false),
+    };
+    // the LP denom is only set once its creation was replied to
+    if legacy.lp_denom.is_empty() {
+        return Err(ContractError::InvalidArgument {
+            msg: "cannot migrate a manager without an LP denom".to_string(),
+        });
+    }
+    let denom = legacy.denom.as_str();
+
+    let vault = VaultInfo {
+        denom: legacy.denom.clone(),
+        lp_denom: legacy.lp_denom.clone(),
+        unbonding_period: LEGACY_CONFIG.load(storage)?.unbonding_period,
+    };
+    VAULTS.save(storage, denom, &vault)?;
+    LP_DENOMS.save(storage, &vault.lp_denom, &vault.denom)?;
+    LEGACY_DENOM.remove(storage);
+    LEGACY_CONFIG.remove(storage);
+
+    let supply = LEGACY_DELEGATE_BALANCE
+        .may_load(storage)?
+        .unwrap_or_default();
+    LEGACY_DELEGATE_BALANCE.remove(storage);
+    DELEGATE_BALANCE.save(storage, denom, &supply)?;
+    DELEGATE_ASSETS.save(storage, denom, &supply)?;
+
+    // pending unbonds are no longer staked for rewards
+    let mut pending: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    let legacy_unbonds = LEGACY_UNBONDS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, unbond) in legacy_unbonds {
+        *pending.entry(unbond.owner.clone()).or_default() += unbond.amount;
+
+        let new_unbond = UnbondInfo {
+            unbond_id: unbond.unbond_id,
+            owner: unbond.owner,
+            denom: vault.denom.clone(),
+            amount: unbond.amount,
+            unbond_time: unbond.unbond_time,
+        };
+        // the owner index is unchanged, only the stored value gains a denom
+        unbonds().replace(storage, id, Some(&new_unbond), None)?;
+    }
+
+    let legacy_bonds = LEGACY_BONDS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut total_staked = Uint128::zero();
+    for (bonder, bond) in legacy_bonds {
+        LEGACY_BONDS.remove(storage, bonder.clone());
+        BONDS.save(storage, (denom, bonder.clone()), &bond)?;
+
+        let staked = bond
+            .amount
+            .saturating_sub(pending.get(&bonder).copied().unwrap_or_default());
+        if !staked.is_zero() {
+            STAKED.save(storage, (denom, bonder), &staked)?;
+            total_staked += staked;
+        }
+    }
+    TOTAL_STAKED.save(storage, denom, &total_staked)?;
+
+    if WITHDRAWALS_ID.may_load(storage)?.is_none() {
+        init_withdrawals_id(storage)?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{
+        attr,
+        testing::{mock_dependencies, mock_env},
+    };
+    use cw2::{get_contract_version, set_contract_version};
+    use cw_storage_plus::{Index, IndexList, IndexedMap, MultiIndex};
+    use mitosis_interface::liquidity_manager::MigrateMsg;
+
+    use crate::{
+        contracts::migrate,
+        state::{
+            bond::{query_unbonds_by_owner, UNBONDS_ID},
+            load_vault_by_lp,
+        },
+        CONTRACT_NAME, CONTRACT_VERSION,
+    };
+
+    use super::*;
+
+    const DENOM: &str = "uusdc";
+    const LP_DENOM: &str = "factory/contract/lpusdc";
+
+    fn bond_info(amount: u128) -> BondInfo {
+        BondInfo {
+            amount: Uint128::new(amount),
+            bond_time: 10,
+        }
+    }
+
+    fn save_legacy_layout(storage: &mut dyn Storage, alice: &Addr, bob: &Addr) {
+        LEGACY_DENOM
+            .save(
+                storage,
+                &LegacyDenomInfo {
+                    denom: DENOM.to_string(),
+                    lp_denom: LP_DENOM.to_string(),
+                },
+            )
+            .unwrap();
+        LEGACY_CONFIG
+            .save(
+                storage,
+                &LegacyConfigInfo {
+                    unbonding_period: 600,
+                },
+            )
+            .unwrap();
+        LEGACY_DELEGATE_BALANCE
+            .save(storage, &Uint128::new(5000))
+            .unwrap();
+        LEGACY_BONDS
+            .save(storage, alice.clone(), &bond_info(1000))
+            .unwrap();
+        LEGACY_BONDS
+            .save(storage, bob.clone(), &bond_info(300))
+            .unwrap();
+
+        // the unbond index as the old contract wrote it
+        let legacy_unbonds = IndexedMap::new(
+            UNBONDS_KEY,
+            LegacyUnbondsIndexes {
+                owner: MultiIndex::new(
+                    |_, u: &LegacyUnbondInfo| u.owner.clone(),
+                    UNBONDS_KEY,
+                    "UNBOND_OWNER",
+                ),
+            },
+        );
+        for (id, owner, amount) in [(0, alice, 400), (1, bob, 300)] {
+            legacy_unbonds
+                .save(
+                    storage,
+                    id,
+                    &LegacyUnbondInfo {
+                        unbond_id: id,
+                        owner: owner.clone(),
+                        amount: Uint128::new(amount),
+                        unbond_time: 700,
+                    },
+                )
+                .unwrap();
+        }
+        UNBONDS_ID.save(storage, &2).unwrap();
+    }
+
+    struct LegacyUnbondsIndexes<'a> {
+        owner: MultiIndex<'a, Addr, LegacyUnbondInfo, u64>,
+    }
+
+    impl<'a> IndexList<LegacyUnbondInfo> for LegacyUnbondsIndexes<'a> {
+        fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<LegacyUnbondInfo>> + '_> {
+            let v: Vec<&dyn Index<LegacyUnbondInfo>> = vec![&self.owner];
+            Box::new(v.into_iter())
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        save_legacy_layout(deps.as_mut().storage, &alice, &bob);
+
+        let result = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("method", "migrate"),
+                attr("from_version", "0.0.1"),
+                attr("legacy_layout", "true"),
+            ]
+        );
+        assert_eq!(
+            get_contract_version(&deps.storage).unwrap().version,
+            CONTRACT_VERSION
+        );
+
+        let storage = &deps.storage;
+        assert_eq!(
+            load_vault_by_lp(storage, LP_DENOM).unwrap(),
+            VaultInfo {
+                denom: DENOM.to_string(),
+                lp_denom: LP_DENOM.to_string(),
+                unbonding_period: 600,
+            }
+        );
+        assert!(LEGACY_DENOM.may_load(storage).unwrap().is_none());
+        assert!(LEGACY_CONFIG.may_load(storage).unwrap().is_none());
+
+        assert_eq!(
+            DELEGATE_BALANCE.load(storage, DENOM).unwrap(),
+            Uint128::new(5000)
+        );
+        assert_eq!(
+            DELEGATE_ASSETS.load(storage, DENOM).unwrap(),
+            Uint128::new(5000)
+        );
+
+        assert_eq!(
+            BONDS.load(storage, (DENOM, alice.clone())).unwrap(),
+            bond_info(1000)
+        );
+        assert_eq!(
+            BONDS.load(storage, (DENOM, bob.clone())).unwrap(),
+            bond_info(300)
+        );
+
+        let alice_unbonds = query_unbonds_by_owner(storage, alice.clone()).unwrap();
+        assert_eq!(
+            alice_unbonds,
+            vec![UnbondInfo {
+                unbond_id: 0,
+                owner: alice.clone(),
+                denom: DENOM.to_string(),
+                amount: Uint128::new(400),
+                unbond_time: 700,
+            }]
+        );
+        assert_eq!(
+            query_unbonds_by_owner(storage, bob.clone()).unwrap().len(),
+            1
+        );
+
+        // bob is unbonding all of his bond
+        assert_eq!(
+            STAKED.load(storage, (DENOM, alice)).unwrap(),
+            Uint128::new(600)
+        );
+        assert!(STAKED.may_load(storage, (DENOM, bob)).unwrap().is_none());
+        assert_eq!(
+            TOTAL_STAKED.load(storage, DENOM).unwrap(),
+            Uint128::new(600)
+        );
+        assert_eq!(WITHDRAWALS_ID.load(storage).unwrap(), 0);
+
+        // a second run has nothing left to upgrade
+        let result = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(result.attributes[2], attr("legacy_layout", "false"));
+    }
+
+    #[test]
+    fn test_migrate_legacy_without_lp_denom() {
+        let mut deps = mock_dependencies();
+
+        LEGACY_DENOM
+            .save(
+                deps.as_mut().storage,
+                &LegacyDenomInfo {
+                    denom: DENOM.to_string(),
+                    lp_denom: "".to_string(),
+                },
+            )
+            .unwrap();
+
+        let err = migrate_legacy(deps.as_mut().storage).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidArgument { .. }));
+    }
+
+    #[test]
+    fn test_migrate_other_contract() {
+        let mut deps = mock_dependencies();
+
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "1.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidArgument { .. }));
+    }
+}
@@ -1,30 +1,63 @@
 use cosmwasm_std::{to_binary, Addr, Coin, Deps, Env, QueryResponse};
 use mitosis_interface::liquidity_manager::{
-    ConfigResponse, GetBalanceResponse, GetBondResponse, GetTotalDelegatesResponse,
-    GetUnbondListResponse, GetUnbondResponse, PauseInfoResponse,
+    ConfigResponse, ExchangeRateResponse, GetBalanceResponse, GetBondResponse,
+    GetTotalDelegatesResponse, GetUnbondListResponse, GetUnbondResponse, PauseInfoResponse,
+    PendingOwnerResponse, PendingRewardsResponse, VaultListResponse, VaultResponse,
+    WithdrawTiersResponse, WithdrawalListResponse, WithdrawalResponse,
 };
 
 use crate::{
     state::{
         balances::inquiry_balance,
         bond::{query_bond, query_unbond, query_unbonds_by_owner},
-        delegates::DELEGATE_BALANCE,
-        rbac::OWNER,
-        ConfigInfo, DenomInfo, CONFIG, DENOM, PAUSED,
+        delegates::{exchange_rate, total_assets, DELEGATE_BALANCE},
+        load_vault, query_vaults,
+        rbac::{OWNER, PENDING_OWNER},
+        rewards::query_pending_rewards,
+        withdrawals::{query_withdraw_tiers, query_withdrawal, query_withdrawals, WithdrawalInfo},
+        VaultInfo, PAUSED,
     },
     ContractError,
 };
 
 pub fn get_config(deps: Deps, _env: Env) -> Result<QueryResponse, ContractError> {
     let owner = OWNER.load(deps.storage)?;
-    let config: ConfigInfo = CONFIG.load(deps.storage)?;
-    let denom: DenomInfo = DENOM.load(deps.storage)?;
-
-    Ok(to_binary(&ConfigResponse {
-        owner,
-        unbonding_period: config.unbonding_period,
-        denom: denom.denom,
-        lp_denom: denom.lp_denom,
+
+    Ok(to_binary(&ConfigResponse { owner })?)
+}
+
+fn vault_response(vault: VaultInfo) -> VaultResponse {
+    VaultResponse {
+        denom: vault.denom,
+        lp_denom: vault.lp_denom,
+        unbonding_period: vault.unbonding_period,
+    }
+}
+
+pub fn get_vault(deps: Deps, denom: String) -> Result<QueryResponse, ContractError> {
+    let vault = load_vault(deps.storage, &denom)?;
+
+    Ok(to_binary(&vault_response(vault))?)
+}
+
+pub fn get_vaults(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let vaults = query_vaults(deps.storage, start_after, limit)?;
+
+    Ok(to_binary(&VaultListResponse {
+        items: vaults.into_iter().map(vault_response).collect(),
+    })?)
+}
+
+pub fn get_pending_owner(deps: Deps) -> Result<QueryResponse, ContractError> {
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+
+    Ok(to_binary(&PendingOwnerResponse {
+        pending_owner: pending.as_ref().map(|p| p.owner.clone()),
+        expires_at: pending.and_then(|p| p.expires_at),
     })?)
 }
 
@@ -46,8 +79,8 @@ pub fn get_balance(deps: Deps, env: Env, depositor: Addr) -> Result<QueryRespons
     })?)
 }
 
-pub fn get_bonds(deps: Deps, bonder: Addr) -> Result<QueryResponse, ContractError> {
-    let result = query_bond(deps.storage, bonder)?;
+pub fn get_bonds(deps: Deps, bonder: Addr, denom: String) -> Result<QueryResponse, ContractError> {
+    let result = query_bond(deps.storage, &denom, bonder)?;
 
     Ok(to_binary(&GetBondResponse {
         amount: result.amount,
@@ -61,6 +94,7 @@ pub fn get_unbond(deps: Deps, unbond_id: u64) -> Result<QueryResponse, ContractE
     Ok(to_binary(&GetUnbondResponse {
         unbond_id: result.unbond_id,
         owner: result.owner,
+        denom: result.denom,
         amount: result.amount,
         unbond_time: result.unbond_time,
     })?)
@@ -73,6 +107,7 @@ pub fn get_unbonds_by_owner(deps: Deps, owner: Addr) -> Result<QueryResponse, Co
         .map(|r| GetUnbondResponse {
             unbond_id: r.unbond_id,
             owner: r.owner,
+            denom: r.denom,
             amount: r.amount,
             unbond_time: r.unbond_time,
         })
@@ -83,14 +118,76 @@ pub fn get_unbonds_by_owner(deps: Deps, owner: Addr) -> Result<QueryResponse, Co
     })?)
 }
 
-pub fn get_total_delegates(deps: Deps) -> Result<QueryResponse, ContractError> {
-    let denom_info = DENOM.load(deps.storage)?;
-    let delegate_amounts = DELEGATE_BALANCE.load(deps.storage)?;
+pub fn get_pending_rewards(
+    deps: Deps,
+    bonder: Addr,
+    denom: String,
+) -> Result<QueryResponse, ContractError> {
+    let vault = load_vault(deps.storage, &denom)?;
+    let rewards = query_pending_rewards(deps.storage, &vault.denom, bonder.clone())?;
+
+    Ok(to_binary(&PendingRewardsResponse { bonder, rewards })?)
+}
+
+pub fn get_total_delegates(deps: Deps, denom: String) -> Result<QueryResponse, ContractError> {
+    let vault = load_vault(deps.storage, &denom)?;
+    let delegate_amounts = DELEGATE_BALANCE.load(deps.storage, &vault.denom)?;
 
     Ok(to_binary(&GetTotalDelegatesResponse {
         amount: Coin {
-            denom: denom_info.lp_denom,
+            denom: vault.lp_denom,
             amount: delegate_amounts,
         },
     })?)
 }
+
+pub fn get_exchange_rate(deps: Deps, denom: String) -> Result<QueryResponse, ContractError> {
+    let vault = load_vault(deps.storage, &denom)?;
+
+    Ok(to_binary(&ExchangeRateResponse {
+        total_assets: Coin {
+            denom: vault.denom.clone(),
+            amount: total_assets(deps.storage, &vault.denom)?,
+        },
+        total_shares: Coin {
+            denom: vault.lp_denom,
+            amount: DELEGATE_BALANCE.load(deps.storage, &vault.denom)?,
+        },
+        rate: exchange_rate(deps.storage, &vault.denom)?,
+    })?)
+}
+
+pub fn get_withdraw_tiers(deps: Deps, denom: String) -> Result<QueryResponse, ContractError> {
+    let tiers = query_withdraw_tiers(deps.storage, &denom)?;
+
+    Ok(to_binary(&WithdrawTiersResponse { denom, tiers })?)
+}
+
+fn withdrawal_response(withdrawal: WithdrawalInfo) -> WithdrawalResponse {
+    WithdrawalResponse {
+        withdrawal_id: withdrawal.withdrawal_id,
+        depositor: withdrawal.depositor,
+        withdrawer: withdrawal.withdrawer,
+        amount: withdrawal.amount,
+        release_at: withdrawal.release_at,
+        status: withdrawal.status,
+    }
+}
+
+pub fn get_withdrawal(deps: Deps, withdrawal_id: u64) -> Result<QueryResponse, ContractError> {
+    let withdrawal = query_withdrawal(deps.storage, withdrawal_id)?;
+
+    Ok(to_binary(&withdrawal_response(withdrawal))?)
+}
+
+pub fn get_pending_withdrawals(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<QueryResponse, ContractError> {
+    let withdrawals = query_withdrawals(deps.storage, start_after, limit)?;
+
+    Ok(to_binary(&WithdrawalListResponse {
+        items: withdrawals.into_iter().map(withdrawal_response).collect(),
+    })?)
+}
@@ -4,10 +4,11 @@ use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 use crate::ContractError;
 
-use super::CONFIG;
+use super::VaultInfo;
 
+// bonds are keyed by the underlying denom of their vault
 pub const BONDS_KEY: &str = "bonds";
-pub const BONDS: Map<Addr, BondInfo> = Map::new(BONDS_KEY);
+pub const BONDS: Map<(&str, Addr), BondInfo> = Map::new(BONDS_KEY);
 
 pub const UNBONDS_KEY: &str = "unbonds";
 pub const UNBONDS_ID_KEY: &str = "unbond_index";
@@ -23,6 +24,7 @@ pub struct BondInfo {
 pub struct UnbondInfo {
     pub unbond_id: u64,
     pub owner: Addr,
+    pub denom: String,
     pub amount: Uint128,
     pub unbond_time: u64, // expected unbond time
 }
@@ -68,14 +70,15 @@ pub fn get_unbonds_by_owner(storage: &dyn Storage, bonder: Addr) -> StdResult<Ve
 pub fn bond(
     storage: &mut dyn Storage,
     env: Env,
+    denom: &str,
     bonder: Addr,
     amount: Uint128,
 ) -> StdResult<BondInfo> {
     // TODO: consider more cases
-    match BONDS.may_load(storage, bonder.clone())? {
+    match BONDS.may_load(storage, (denom, bonder.clone()))? {
         Some(mut bond) => {
             bond.amount = bond.amount.checked_add(amount)?;
-            BONDS.save(storage, bonder, &bond)?;
+            BONDS.save(storage, (denom, bonder), &bond)?;
 
             Ok(bond)
         }
@@ -84,7 +87,7 @@ pub fn bond(
                 amount,
                 bond_time: env.block.time.seconds(),
             };
-            BONDS.save(storage, bonder, &bond)?;
+            BONDS.save(storage, (denom, bonder), &bond)?;
 
             Ok(bond)
         }
@@ -94,15 +97,16 @@ pub fn bond(
 pub fn start_unbond(
     storage: &mut dyn Storage,
     env: Env,
+    vault: &VaultInfo,
     bonder: Addr,
     amount: Uint128,
 ) -> Result<UnbondInfo, ContractError> {
-    let bonds = BONDS.load(storage, bonder.clone())?;
+    let bonds = BONDS.load(storage, (vault.denom.as_str(), bonder.clone()))?;
     let current_unbonds = get_unbonds_by_owner(storage, bonder.clone())?;
-    let config = CONFIG.load(storage)?;
 
     let unbond_amount: Uint128 = current_unbonds
         .into_iter()
+        .filter(|x| x.denom == vault.denom)
         .map(|x| x.amount)
         .reduce(|a, b| a.checked_add(b).unwrap())
         .unwrap_or(Uint128::new(0));
@@ -118,7 +122,8 @@ pub fn start_unbond(
         unbond_id,
         amount,
         owner: bonder,
-        unbond_time: env.block.time.seconds() + config.unbonding_period, // saving expected unbond time.
+        denom: vault.denom.clone(),
+        unbond_time: env.block.time.seconds() + vault.unbonding_period, // saving expected unbond time.
     };
 
     unbonds().save(storage, unbond_id, &new_unbond)?;
@@ -134,7 +139,8 @@ pub fn finish_unbond(
     unbond_id: u64,
 ) -> Result<UnbondInfo, ContractError> {
     let unbond = unbonds().load(storage, unbond_id)?;
-    let mut bond: BondInfo = BONDS.load(storage, bonder.clone())?;
+    let key = (unbond.denom.as_str(), bonder.clone());
+    let mut bond: BondInfo = BONDS.load(storage, key.clone())?;
 
     if unbond.owner != bonder {
         return Err(ContractError::Unauthorized {});
@@ -144,13 +150,13 @@ pub fn finish_unbond(
 
     unbonds().remove(storage, unbond_id)?;
     bond.amount = bond.amount.checked_sub(unbond.amount).unwrap();
-    BONDS.save(storage, bonder, &bond)?;
+    BONDS.save(storage, key, &bond)?;
 
     Ok(unbond)
 }
 
-pub fn query_bond(storage: &dyn Storage, bonder: Addr) -> StdResult<BondInfo> {
-    match BONDS.may_load(storage, bonder)? {
+pub fn query_bond(storage: &dyn Storage, denom: &str, bonder: Addr) -> StdResult<BondInfo> {
+    match BONDS.may_load(storage, (denom, bonder))? {
         Some(bond_info) => Ok(bond_info),
         None => Ok(BondInfo {
             amount: Uint128::zero(),
@@ -171,16 +177,25 @@ pub fn query_unbonds_by_owner(storage: &dyn Storage, bonder: Addr) -> StdResult<
 mod test {
     use cosmwasm_std::{testing::mock_env, testing::MockStorage};
 
-    use crate::state::ConfigInfo;
+    use crate::state::VaultInfo;
 
     use super::*;
 
     const ADDR1_VALUE: &str = "addr1";
     const ADDR2_VALUE: &str = "addr2";
+    const DENOM: &str = "uusdc";
+
+    fn mock_vault() -> VaultInfo {
+        VaultInfo {
+            denom: DENOM.to_string(),
+            lp_denom: "factory/contract/uusdc".to_string(),
+            unbonding_period: 20u64,
+        }
+    }
 
     fn initialize_bond(storage: &mut dyn Storage, bonder: Addr, amount: Uint128, bond_time: u64) {
         BONDS
-            .save(storage, bonder, &BondInfo { amount, bond_time })
+            .save(storage, (DENOM, bonder), &BondInfo { amount, bond_time })
             .unwrap();
     }
 
@@ -195,6 +210,7 @@ mod test {
             unbond_id,
             amount,
             owner,
+            denom: DENOM.to_string(),
             unbond_time,
         };
 
@@ -210,8 +226,15 @@ mod test {
         let mut storage: cosmwasm_std::MemoryStorage = MockStorage::new();
         let env = mock_env();
 
-        let result = bond(&mut storage, env, bonder.clone(), Uint128::new(100000)).unwrap();
-        let saved_info = BONDS.load(&storage, bonder).unwrap();
+        let result = bond(
+            &mut storage,
+            env,
+            DENOM,
+            bonder.clone(),
+            Uint128::new(100000),
+        )
+        .unwrap();
+        let saved_info = BONDS.load(&storage, (DENOM, bonder)).unwrap();
 
         assert_eq!(result, saved_info);
     }
@@ -224,8 +247,15 @@ mod test {
 
         initialize_bond(&mut storage, bonder.clone(), Uint128::new(100000), 0);
 
-        let result = bond(&mut storage, env, bonder.clone(), Uint128::new(100000)).unwrap();
-        let saved_info = BONDS.load(&storage, bonder).unwrap();
+        let result = bond(
+            &mut storage,
+            env,
+            DENOM,
+            bonder.clone(),
+            Uint128::new(100000),
+        )
+        .unwrap();
+        let saved_info = BONDS.load(&storage, (DENOM, bonder)).unwrap();
 
         assert_eq!(result, saved_info);
         assert_eq!(result.amount, Uint128::new(200000));
@@ -237,21 +267,13 @@ mod test {
         let mut storage = MockStorage::new();
         let env = mock_env();
 
-        CONFIG
-            .save(
-                &mut storage,
-                &ConfigInfo {
-                    unbonding_period: 20u64,
-                },
-            )
-            .unwrap();
-
         initialize_bond(&mut storage, bonder.clone(), Uint128::new(100000), 0);
         init_unbonds_id(&mut storage).unwrap();
 
         let first_unbonding = start_unbond(
             &mut storage,
             env.clone(),
+            &mock_vault(),
             bonder.clone(),
             Uint128::new(50000),
         )
@@ -259,8 +281,14 @@ mod test {
         assert_eq!(first_unbonding.amount, Uint128::new(50000));
         assert_eq!(first_unbonding.unbond_id, 0u64); // first initialize
 
-        let second_unbonding =
-            start_unbond(&mut storage, env, bonder, Uint128::new(40000)).unwrap();
+        let second_unbonding = start_unbond(
+            &mut storage,
+            env,
+            &mock_vault(),
+            bonder,
+            Uint128::new(40000),
+        )
+        .unwrap();
 
         assert_eq!(second_unbonding.amount, Uint128::new(40000));
         assert_eq!(second_unbonding.unbond_id, 1u64); // second initialize
@@ -272,22 +300,19 @@ mod test {
         let mut storage = MockStorage::new();
         let env = mock_env();
 
-        CONFIG
-            .save(
-                &mut storage,
-                &ConfigInfo {
-                    unbonding_period: 20u64,
-                },
-            )
-            .unwrap();
-
         initialize_bond(&mut storage, bonder.clone(), Uint128::new(100000), 0);
         init_unbonds_id(&mut storage).unwrap();
         initialize_unbond(&mut storage, bonder.clone(), Uint128::new(40000), 0u64);
         initialize_unbond(&mut storage, bonder.clone(), Uint128::new(60000), 0u64);
 
-        let insufficient_err =
-            start_unbond(&mut storage, env, bonder, Uint128::new(50000)).unwrap_err();
+        let insufficient_err = start_unbond(
+            &mut storage,
+            env,
+            &mock_vault(),
+            bonder,
+            Uint128::new(50000),
+        )
+        .unwrap_err();
         assert!(matches!(
             insufficient_err,
             ContractError::InsufficientBondAmount {}
@@ -302,15 +327,6 @@ mod test {
         let mut storage = MockStorage::new();
         let env = mock_env();
 
-        CONFIG
-            .save(
-                &mut storage,
-                &ConfigInfo {
-                    unbonding_period: 20u64,
-                },
-            )
-            .unwrap();
-
         initialize_bond(&mut storage, bonder.clone(), Uint128::new(100000), 0);
         initialize_bond(&mut storage, not_bonder.clone(), Uint128::new(100000), 0);
 
@@ -347,15 +363,6 @@ mod test {
         let mut storage: cosmwasm_std::MemoryStorage = MockStorage::new();
         let env = mock_env();
 
-        CONFIG
-            .save(
-                &mut storage,
-                &ConfigInfo {
-                    unbonding_period: 20u64,
-                },
-            )
-            .unwrap();
-
         initialize_bond(&mut storage, bonder.clone(), Uint128::new(100000), 0);
 
         init_unbonds_id(&mut storage).unwrap();
@@ -372,7 +379,7 @@ mod test {
         assert_eq!(success_unbonding.amount, Uint128::new(40000));
 
         // removed amount successfully
-        let bond = BONDS.load(&storage, bonder).unwrap();
+        let bond = BONDS.load(&storage, (DENOM, bonder)).unwrap();
         assert_eq!(bond.amount, Uint128::new(60000));
 
         // successfully removed
@@ -384,7 +391,7 @@ mod test {
         let mut storage: cosmwasm_std::MemoryStorage = MockStorage::new();
 
         let bonder = Addr::unchecked(ADDR1_VALUE);
-        let empty_bond = query_bond(&storage, bonder.clone()).unwrap();
+        let empty_bond = query_bond(&storage, DENOM, bonder.clone()).unwrap();
 
         assert_eq!(
             empty_bond,
@@ -395,7 +402,7 @@ mod test {
         );
 
         initialize_bond(&mut storage, bonder.clone(), Uint128::new(100000), 12);
-        let initialized_bond = query_bond(&storage, bonder).unwrap();
+        let initialized_bond = query_bond(&storage, DENOM, bonder).unwrap();
         assert_eq!(
             initialized_bond,
             BondInfo {
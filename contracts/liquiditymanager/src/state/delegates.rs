@@ -1,34 +1,128 @@
-use cosmwasm_std::{Storage, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Decimal, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::Map;
 
 use crate::ContractError;
 
+// DELEGATE_BALANCE is the LP supply of each vault, DELEGATE_ASSETS the
+// underlying backing it. Both are keyed by the underlying denom.
 pub const DELEGATE_BALANCES_KEY: &str = "delegate_balances";
-pub const DELEGATE_BALANCE: Item<Uint128> = Item::new(DELEGATE_BALANCES_KEY);
+pub const DELEGATE_BALANCE: Map<&str, Uint128> = Map::new(DELEGATE_BALANCES_KEY);
+
+pub const DELEGATE_ASSETS_KEY: &str = "delegate_assets";
+pub const DELEGATE_ASSETS: Map<&str, Uint128> = Map::new(DELEGATE_ASSETS_KEY);
+
+// Virtual shares and assets added on both sides of the exchange rate. They keep
+// the initial rate at 1:1 and make inflating it with a donation to an almost
+// empty vault cost the attacker most of the donation.
+pub const VIRTUAL_OFFSET: Uint128 = Uint128::new(1_000_000);
+
+pub fn init_vault_balances(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    DELEGATE_BALANCE.save(storage, denom, &Uint128::zero())?;
+    DELEGATE_ASSETS.save(storage, denom, &Uint128::zero())?;
+
+    Ok(())
+}
+
+pub fn total_assets(storage: &dyn Storage, denom: &str) -> StdResult<Uint128> {
+    DELEGATE_ASSETS.load(storage, denom)
+}
+
+pub fn exchange_rate(storage: &dyn Storage, denom: &str) -> StdResult<Decimal> {
+    let supply = DELEGATE_BALANCE.load(storage, denom)?;
+    let assets = total_assets(storage, denom)?;
+
+    Ok(Decimal::from_ratio(
+        assets + VIRTUAL_OFFSET,
+        supply + VIRTUAL_OFFSET,
+    ))
+}
+
+// to_shares and to_assets both round down, in favor of the vault.
+pub fn to_shares(
+    storage: &dyn Storage,
+    denom: &str,
+    assets: Uint128,
+) -> Result<Uint128, ContractError> {
+    let supply = DELEGATE_BALANCE.load(storage, denom)?;
+    let total = total_assets(storage, denom)?;
+
+    let shares = assets
+        .checked_multiply_ratio(supply + VIRTUAL_OFFSET, total + VIRTUAL_OFFSET)
+        .map_err(|_| ContractError::InvalidArgument {
+            msg: "share amount overflows".to_string(),
+        })?;
+
+    Ok(shares)
+}
+
+pub fn to_assets(
+    storage: &dyn Storage,
+    denom: &str,
+    shares: Uint128,
+) -> Result<Uint128, ContractError> {
+    let supply = DELEGATE_BALANCE.load(storage, denom)?;
+    let total = total_assets(storage, denom)?;
+
+    let assets = shares
+        .checked_multiply_ratio(total + VIRTUAL_OFFSET, supply + VIRTUAL_OFFSET)
+        .map_err(|_| ContractError::InvalidArgument {
+            msg: "asset amount overflows".to_string(),
+        })?;
+
+    Ok(assets)
+}
+
+pub fn add_assets(
+    storage: &mut dyn Storage,
+    denom: &str,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let new_amount = total_assets(storage, denom)?
+        .checked_add(amount)
+        .map_err(StdError::from)?;
+    DELEGATE_ASSETS.save(storage, denom, &new_amount)?;
+
+    Ok(new_amount)
+}
+
+pub fn sub_assets(
+    storage: &mut dyn Storage,
+    denom: &str,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let new_amount = total_assets(storage, denom)?
+        .checked_sub(amount)
+        .map_err(|_| ContractError::InsufficientVaultAssets {})?;
+    DELEGATE_ASSETS.save(storage, denom, &new_amount)?;
+
+    Ok(new_amount)
+}
 
 pub fn delegate_balance(
     storage: &mut dyn Storage,
+    denom: &str,
     amount: Uint128,
 ) -> Result<Uint128, ContractError> {
     // Save whole sended balances;
-    let delegates = DELEGATE_BALANCE.load(storage)?;
+    let delegates = DELEGATE_BALANCE.load(storage, denom)?;
     let new_amount = delegates.checked_add(amount).unwrap(); // cannot be errored
 
-    DELEGATE_BALANCE.save(storage, &new_amount).unwrap();
+    DELEGATE_BALANCE.save(storage, denom, &new_amount).unwrap();
 
     Ok(new_amount)
 }
 
 pub fn undelegate_balance(
     storage: &mut dyn Storage,
+    denom: &str,
     amount: Uint128,
 ) -> Result<Uint128, ContractError> {
-    let delegates = DELEGATE_BALANCE.load(storage)?;
+    let delegates = DELEGATE_BALANCE.load(storage, denom)?;
     let new_amount = delegates
         .checked_sub(amount)
         .map_err(|_| ContractError::InsufficientUndelegateAsset {})?;
 
-    DELEGATE_BALANCE.save(storage, &new_amount).unwrap();
+    DELEGATE_BALANCE.save(storage, denom, &new_amount).unwrap();
 
     Ok(new_amount)
 }
@@ -39,19 +133,21 @@ mod test {
 
     use super::*;
 
+    const DENOM: &str = "uusdc";
+
     #[test]
     fn test_delegates() {
         let mut storage = MockStorage::new();
         let expected = Uint128::new(3000);
 
         DELEGATE_BALANCE
-            .save(&mut storage, &Uint128::new(1000))
+            .save(&mut storage, DENOM, &Uint128::new(1000))
             .unwrap();
 
-        let result = delegate_balance(&mut storage, Uint128::new(2000)).unwrap();
+        let result = delegate_balance(&mut storage, DENOM, Uint128::new(2000)).unwrap();
         assert_eq!(result, expected);
 
-        let saved = DELEGATE_BALANCE.load(&storage).unwrap();
+        let saved = DELEGATE_BALANCE.load(&storage, DENOM).unwrap();
         assert_eq!(saved, expected)
     }
 
@@ -61,13 +157,13 @@ mod test {
         let expected = Uint128::new(1000);
 
         DELEGATE_BALANCE
-            .save(&mut storage, &Uint128::new(3000))
+            .save(&mut storage, DENOM, &Uint128::new(3000))
             .unwrap();
 
-        let result = undelegate_balance(&mut storage, Uint128::new(2000)).unwrap();
+        let result = undelegate_balance(&mut storage, DENOM, Uint128::new(2000)).unwrap();
         assert_eq!(result, expected);
 
-        let saved = DELEGATE_BALANCE.load(&storage).unwrap();
+        let saved = DELEGATE_BALANCE.load(&storage, DENOM).unwrap();
         assert_eq!(saved, expected);
     }
 
@@ -75,13 +171,87 @@ mod test {
     fn test_undelegate_failure() {
         let mut storage = MockStorage::new();
         DELEGATE_BALANCE
-            .save(&mut storage, &Uint128::new(3000))
+            .save(&mut storage, DENOM, &Uint128::new(3000))
             .unwrap();
 
-        let result = undelegate_balance(&mut storage, Uint128::new(4000)).unwrap_err();
+        let result = undelegate_balance(&mut storage, DENOM, Uint128::new(4000)).unwrap_err();
         assert!(matches!(
             result,
             ContractError::InsufficientUndelegateAsset {}
         ))
     }
+
+    #[test]
+    fn test_share_conversion() {
+        let mut storage = MockStorage::new();
+        init_vault_balances(&mut storage, DENOM).unwrap();
+
+        // empty vault mints 1:1
+        assert_eq!(
+            to_shares(&storage, DENOM, Uint128::new(1000)).unwrap(),
+            Uint128::new(1000)
+        );
+        assert_eq!(exchange_rate(&storage, DENOM).unwrap(), Decimal::one());
+
+        DELEGATE_BALANCE
+            .save(&mut storage, DENOM, &Uint128::new(1_000_000))
+            .unwrap();
+        DELEGATE_ASSETS
+            .save(&mut storage, DENOM, &Uint128::new(1_000_000))
+            .unwrap();
+
+        // with 1_000_000 of yield a share is worth (2M + 1M) / (1M + 1M)
+        add_assets(&mut storage, DENOM, Uint128::new(1_000_000)).unwrap();
+        assert_eq!(
+            exchange_rate(&storage, DENOM).unwrap(),
+            Decimal::from_ratio(3u128, 2u128)
+        );
+        assert_eq!(
+            to_shares(&storage, DENOM, Uint128::new(1000)).unwrap(),
+            Uint128::new(666)
+        );
+        assert_eq!(
+            to_assets(&storage, DENOM, Uint128::new(1000)).unwrap(),
+            Uint128::new(1500)
+        );
+        // rounding down both ways never pays out more than was paid in
+        assert_eq!(
+            to_assets(&storage, DENOM, Uint128::new(1)).unwrap(),
+            Uint128::one()
+        );
+        assert_eq!(
+            to_shares(&storage, DENOM, Uint128::one()).unwrap(),
+            Uint128::zero()
+        );
+
+        let lost = sub_assets(&mut storage, DENOM, Uint128::new(4_000_000)).unwrap_err();
+        assert!(matches!(lost, ContractError::InsufficientVaultAssets {}));
+        assert_eq!(
+            sub_assets(&mut storage, DENOM, Uint128::new(1_500_000)).unwrap(),
+            Uint128::new(500_000)
+        );
+    }
+
+    #[test]
+    fn test_inflation_attack() {
+        let mut storage = MockStorage::new();
+
+        // the attacker holds the only share and donates a large yield
+        DELEGATE_BALANCE
+            .save(&mut storage, DENOM, &Uint128::one())
+            .unwrap();
+        DELEGATE_ASSETS
+            .save(&mut storage, DENOM, &Uint128::one())
+            .unwrap();
+        add_assets(&mut storage, DENOM, Uint128::new(10_000_000)).unwrap();
+
+        // the victim still gets most of its deposit in shares, while the
+        // attacker's share is worth 10 of the 10_000_001 it put in
+        let victim = to_shares(&storage, DENOM, Uint128::new(10_000_000)).unwrap();
+        assert_eq!(victim, Uint128::new(909_091));
+        assert_eq!(
+            to_assets(&storage, DENOM, Uint128::one()).unwrap(),
+            Uint128::new(10)
+        );
+    }
 }
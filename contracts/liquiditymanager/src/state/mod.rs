@@ -2,21 +2,32 @@ pub mod balances;
 pub mod bond;
 pub mod delegates;
 pub mod rbac;
+pub mod rewards;
+pub mod withdrawals;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Env, StdResult, Storage};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Env, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
 
 use crate::ContractError;
 
 pub const PAUSED_KEY: &str = "paused";
 pub const PAUSED: Item<PauseInfo> = Item::new(PAUSED_KEY);
 
-pub const DENOM_KEY: &str = "denom";
-pub const DENOM: Item<DenomInfo> = Item::new(DENOM_KEY);
+// VAULTS holds one vault per underlying denom, LP_DENOMS maps each LP denom
+// back to it.
+pub const VAULTS_KEY: &str = "vaults";
+pub const VAULTS: Map<&str, VaultInfo> = Map::new(VAULTS_KEY);
 
-pub const CONFIG_KEY: &str = "config";
-pub const CONFIG: Item<ConfigInfo> = Item::new(CONFIG_KEY);
+pub const LP_DENOMS_KEY: &str = "lp_denoms";
+pub const LP_DENOMS: Map<&str, String> = Map::new(LP_DENOMS_KEY);
+
+// vaults waiting for their LP denom, keyed by LP subdenom
+pub const PENDING_VAULTS_KEY: &str = "pending_vaults";
+pub const PENDING_VAULTS: Map<&str, PendingVault> = Map::new(PENDING_VAULTS_KEY);
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
 
 #[cw_serde]
 #[derive(Default)]
@@ -26,16 +37,51 @@ pub struct PauseInfo {
 }
 
 #[cw_serde]
-pub struct DenomInfo {
+pub struct VaultInfo {
     pub denom: String,
     pub lp_denom: String,
+    pub unbonding_period: u64,
 }
 
 #[cw_serde]
-pub struct ConfigInfo {
+pub struct PendingVault {
+    pub denom: String,
     pub unbonding_period: u64,
 }
 
+pub fn load_vault(storage: &dyn Storage, denom: &str) -> Result<VaultInfo, ContractError> {
+    VAULTS
+        .may_load(storage, denom)?
+        .ok_or(ContractError::DenomNotFound {
+            denom: denom.to_string(),
+        })
+}
+
+pub fn load_vault_by_lp(storage: &dyn Storage, lp_denom: &str) -> Result<VaultInfo, ContractError> {
+    let denom = LP_DENOMS
+        .may_load(storage, lp_denom)?
+        .ok_or(ContractError::DenomNotFound {
+            denom: lp_denom.to_string(),
+        })?;
+
+    load_vault(storage, &denom)
+}
+
+pub fn query_vaults(
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<VaultInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    VAULTS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|r| r.map(|(_, vault)| vault))
+        .collect()
+}
+
 impl PauseInfo {
     pub fn refresh(self, storage: &mut dyn Storage, env: &Env) -> StdResult<Self> {
         if self.paused {
@@ -1,3 +1,4 @@
+use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Storage};
 use cw_storage_plus::{Item, Map};
 
@@ -6,12 +7,18 @@ use crate::error::ContractError;
 pub const OWNER_KEY: &str = "owner";
 pub const OWNER: Item<Addr> = Item::new(OWNER_KEY);
 
+pub const PENDING_OWNER_KEY: &str = "pending_owner";
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new(PENDING_OWNER_KEY);
+
 pub const ADDR_ROLE_KEY: &str = "roles";
 pub const ADDR_ROLE: Map<(String, Addr), bool> = Map::new(ADDR_ROLE_KEY);
 
-/** You might add ROLES here */
+// roles granted through ADDR_ROLE
 pub const GATEWAY_ROLE: &str = "gateway_role";
-/** You might add ROLES here */
+// guardians can pause, but not release or administer
+pub const GUARDIAN_ROLE: &str = "guardian_role";
+// distributors fund bond rewards alongside the owner
+pub const DISTRIBUTOR_ROLE: &str = "distributor_role";
 
 pub fn assert_owned(storage: &dyn Storage, sender: Addr) -> Result<(), ContractError> {
     let owner = OWNER.load(storage)?;
@@ -33,12 +40,81 @@ pub fn assert_role(storage: &dyn Storage, role: String, addr: Addr) -> Result<()
     }
 }
 
+// assert_pauser returns the role the sender pauses with, the owner or a guardian.
+pub fn assert_pauser(storage: &dyn Storage, sender: Addr) -> Result<&'static str, ContractError> {
+    if assert_owned(storage, sender.clone()).is_ok() {
+        return Ok("owner");
+    }
+
+    assert_role(storage, GUARDIAN_ROLE.to_string(), sender)
+        .map(|_| "guardian")
+        .map_err(|_| ContractError::Unauthorized {})
+}
+
 pub fn change_owner(storage: &mut dyn Storage, new_owner: Addr) -> Result<(), ContractError> {
     OWNER.save(storage, &new_owner)?;
 
     Ok(())
 }
 
+// PendingOwner is an owner proposed by ChangeOwner, until it accepts.
+#[cw_serde]
+pub struct PendingOwner {
+    pub owner: Addr,
+    pub expires_at: Option<u64>,
+}
+
+pub fn propose_owner(
+    storage: &mut dyn Storage,
+    new_owner: Addr,
+    expires_at: Option<u64>,
+) -> Result<(), ContractError> {
+    PENDING_OWNER.save(
+        storage,
+        &PendingOwner {
+            owner: new_owner,
+            expires_at,
+        },
+    )?;
+
+    Ok(())
+}
+
+// accept_owner hands the ownership over to the pending owner, if it is the sender
+// and its proposal hasn't expired.
+pub fn accept_owner(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    now: u64,
+) -> Result<(), ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    if pending.owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if matches!(pending.expires_at, Some(expires_at) if expires_at <= now) {
+        return Err(ContractError::PendingOwnerExpired {});
+    }
+
+    change_owner(storage, pending.owner)?;
+    PENDING_OWNER.remove(storage);
+
+    Ok(())
+}
+
+pub fn cancel_owner(storage: &mut dyn Storage) -> Result<Addr, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    PENDING_OWNER.remove(storage);
+
+    Ok(pending.owner)
+}
+
 pub fn grant_role(
     storage: &mut dyn Storage,
     role: String,
@@ -89,6 +165,36 @@ mod test {
         assert!(matches!(failed, ContractError::Unauthorized {}));
     }
 
+    #[test]
+    fn test_assert_pauser() {
+        let mut storage = MockStorage::new();
+
+        let owner = Addr::unchecked(ADDR1_VALUE);
+        let guardian = Addr::unchecked(ADDR2_VALUE);
+        let abuser = Addr::unchecked("addr3");
+
+        mock_owner(&mut storage, owner.clone());
+        ADDR_ROLE
+            .save(
+                &mut storage,
+                (GUARDIAN_ROLE.to_string(), guardian.clone()),
+                &true,
+            )
+            .unwrap();
+        ADDR_ROLE
+            .save(
+                &mut storage,
+                (GATEWAY_ROLE.to_string(), abuser.clone()),
+                &true,
+            )
+            .unwrap();
+
+        assert_eq!(assert_pauser(&storage, owner).unwrap(), "owner");
+        assert_eq!(assert_pauser(&storage, guardian).unwrap(), "guardian");
+        let failed = assert_pauser(&storage, abuser).unwrap_err();
+        assert!(matches!(failed, ContractError::Unauthorized {}));
+    }
+
     #[test]
     fn test_change_owner() {
         let mut storage = MockStorage::new();
@@ -100,6 +206,46 @@ mod test {
         assert_eq!(saved, new_owner);
     }
 
+    #[test]
+    fn test_accept_owner() {
+        let mut storage = MockStorage::new();
+
+        let owner = Addr::unchecked(ADDR1_VALUE);
+        let new_owner = Addr::unchecked(ADDR2_VALUE);
+
+        mock_owner(&mut storage, owner.clone());
+
+        let no_pending = accept_owner(&mut storage, new_owner.clone(), 0).unwrap_err();
+        assert!(matches!(no_pending, ContractError::NoPendingOwner {}));
+
+        propose_owner(&mut storage, new_owner.clone(), Some(100)).unwrap();
+        assert_eq!(OWNER.load(&storage).unwrap(), owner);
+
+        let other = accept_owner(&mut storage, owner.clone(), 0).unwrap_err();
+        assert!(matches!(other, ContractError::Unauthorized {}));
+        let expired = accept_owner(&mut storage, new_owner.clone(), 100).unwrap_err();
+        assert!(matches!(expired, ContractError::PendingOwnerExpired {}));
+
+        accept_owner(&mut storage, new_owner.clone(), 99).unwrap();
+        assert_eq!(OWNER.load(&storage).unwrap(), new_owner);
+        assert!(PENDING_OWNER.may_load(&storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cancel_owner() {
+        let mut storage = MockStorage::new();
+        let new_owner = Addr::unchecked(ADDR2_VALUE);
+
+        let no_pending = cancel_owner(&mut storage).unwrap_err();
+        assert!(matches!(no_pending, ContractError::NoPendingOwner {}));
+
+        propose_owner(&mut storage, new_owner.clone(), None).unwrap();
+        assert_eq!(cancel_owner(&mut storage).unwrap(), new_owner);
+
+        let cancelled = accept_owner(&mut storage, new_owner, 0).unwrap_err();
+        assert!(matches!(cancelled, ContractError::NoPendingOwner {}));
+    }
+
     #[test]
     fn test_assert_role() {
         let mut storage = MockStorage::new();
@@ -0,0 +1,240 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{coin, Addr, Coin, Decimal, Order, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::Map;
+
+use crate::ContractError;
+
+// Rewards are tracked per vault, i.e. per underlying denom. REWARD_INDEX is
+// the reward paid per bonded LP of a vault so far, by reward denom.
+pub const REWARD_INDEX_KEY: &str = "reward_index";
+pub const REWARD_INDEX: Map<(&str, &str), Decimal> = Map::new(REWARD_INDEX_KEY);
+
+pub const REWARD_CHECKPOINTS_KEY: &str = "reward_checkpoints";
+pub const REWARD_CHECKPOINTS: Map<(&str, Addr, &str), RewardCheckpoint> =
+    Map::new(REWARD_CHECKPOINTS_KEY);
+
+// LP earning rewards, i.e. bonded and not unbonding
+pub const STAKED_KEY: &str = "reward_staked";
+pub const STAKED: Map<(&str, Addr), Uint128> = Map::new(STAKED_KEY);
+
+pub const TOTAL_STAKED_KEY: &str = "reward_total_staked";
+pub const TOTAL_STAKED: Map<&str, Uint128> = Map::new(TOTAL_STAKED_KEY);
+
+#[cw_serde]
+#[derive(Default)]
+pub struct RewardCheckpoint {
+    pub index: Decimal,
+    pub accrued: Uint128,
+}
+
+fn staked(storage: &dyn Storage, vault: &str, bonder: Addr) -> StdResult<Uint128> {
+    Ok(STAKED
+        .may_load(storage, (vault, bonder))?
+        .unwrap_or_default())
+}
+
+fn total_staked(storage: &dyn Storage, vault: &str) -> StdResult<Uint128> {
+    Ok(TOTAL_STAKED.may_load(storage, vault)?.unwrap_or_default())
+}
+
+// settle brings a checkpoint up to the current index.
+fn settle(checkpoint: RewardCheckpoint, index: Decimal, staked: Uint128) -> RewardCheckpoint {
+    let earned = staked * (index - checkpoint.index);
+
+    RewardCheckpoint {
+        index,
+        accrued: checkpoint.accrued + earned,
+    }
+}
+
+// checkpoint settles the rewards of bonder under its current stake. It must run
+// before the stake changes.
+pub fn checkpoint(storage: &mut dyn Storage, vault: &str, bonder: Addr) -> StdResult<()> {
+    let staked = staked(storage, vault, bonder.clone())?;
+    let indexes = REWARD_INDEX
+        .prefix(vault)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (denom, index) in indexes {
+        let key = (vault, bonder.clone(), denom.as_str());
+        let current = REWARD_CHECKPOINTS
+            .may_load(storage, key.clone())?
+            .unwrap_or_default();
+
+        REWARD_CHECKPOINTS.save(storage, key, &settle(current, index, staked))?;
+    }
+
+    Ok(())
+}
+
+pub fn add_stake(
+    storage: &mut dyn Storage,
+    vault: &str,
+    bonder: Addr,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    checkpoint(storage, vault, bonder.clone())?;
+
+    let new_staked = staked(storage, vault, bonder.clone())?.checked_add(amount)?;
+    STAKED.save(storage, (vault, bonder), &new_staked)?;
+    let total = total_staked(storage, vault)?.checked_add(amount)?;
+    TOTAL_STAKED.save(storage, vault, &total)?;
+
+    Ok(new_staked)
+}
+
+pub fn remove_stake(
+    storage: &mut dyn Storage,
+    vault: &str,
+    bonder: Addr,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    checkpoint(storage, vault, bonder.clone())?;
+
+    let new_staked = staked(storage, vault, bonder.clone())?.checked_sub(amount)?;
+    STAKED.save(storage, (vault, bonder), &new_staked)?;
+    let total = total_staked(storage, vault)?.checked_sub(amount)?;
+    TOTAL_STAKED.save(storage, vault, &total)?;
+
+    Ok(new_staked)
+}
+
+// fund spreads amounts over the LP of vault staked right now.
+pub fn fund(storage: &mut dyn Storage, vault: &str, amounts: &[Coin]) -> Result<(), ContractError> {
+    let total = total_staked(storage, vault)?;
+    if total.is_zero() {
+        return Err(ContractError::NoBondedShares {});
+    }
+
+    for amount in amounts {
+        let key = (vault, amount.denom.as_str());
+        let index = REWARD_INDEX
+            .may_load(storage, key)?
+            .unwrap_or_default()
+            .checked_add(Decimal::from_ratio(amount.amount, total))
+            .map_err(StdError::from)?;
+
+        REWARD_INDEX.save(storage, key, &index)?;
+    }
+
+    Ok(())
+}
+
+// claim settles and empties the accrued rewards of bonder in vault.
+pub fn claim(storage: &mut dyn Storage, vault: &str, bonder: Addr) -> StdResult<Vec<Coin>> {
+    checkpoint(storage, vault, bonder.clone())?;
+
+    let checkpoints = REWARD_CHECKPOINTS
+        .prefix((vault, bonder.clone()))
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut claimed = vec![];
+    for (denom, mut checkpoint) in checkpoints {
+        if checkpoint.accrued.is_zero() {
+            continue;
+        }
+
+        claimed.push(coin(checkpoint.accrued.u128(), denom.clone()));
+        checkpoint.accrued = Uint128::zero();
+        REWARD_CHECKPOINTS.save(
+            storage,
+            (vault, bonder.clone(), denom.as_str()),
+            &checkpoint,
+        )?;
+    }
+
+    Ok(claimed)
+}
+
+pub fn query_pending_rewards(
+    storage: &dyn Storage,
+    vault: &str,
+    bonder: Addr,
+) -> StdResult<Vec<Coin>> {
+    let staked = staked(storage, vault, bonder.clone())?;
+
+    REWARD_INDEX
+        .prefix(vault)
+        .range(storage, None, None, Order::Ascending)
+        .map(|r| {
+            let (denom, index) = r?;
+            let current = REWARD_CHECKPOINTS
+                .may_load(storage, (vault, bonder.clone(), denom.as_str()))?
+                .unwrap_or_default();
+
+            Ok(coin(settle(current, index, staked).accrued.u128(), denom))
+        })
+        .filter(|r| !matches!(r, Ok(c) if c.amount.is_zero()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::MockStorage;
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+    const VAULT: &str = "uusdc";
+
+    #[test]
+    fn test_rewards_accrue_by_stake() {
+        let mut storage = MockStorage::new();
+        let alice = Addr::unchecked(ADDR1);
+        let bob = Addr::unchecked(ADDR2);
+
+        let unbonded = fund(&mut storage, VAULT, &[coin(100, "uosmo")]).unwrap_err();
+        assert!(matches!(unbonded, ContractError::NoBondedShares {}));
+
+        add_stake(&mut storage, VAULT, alice.clone(), Uint128::new(100)).unwrap();
+        fund(&mut storage, VAULT, &[coin(1000, "uosmo")]).unwrap();
+
+        // bob joins after the first funding and only earns from the second
+        add_stake(&mut storage, VAULT, bob.clone(), Uint128::new(300)).unwrap();
+        fund(
+            &mut storage,
+            VAULT,
+            &[coin(400, "uosmo"), coin(40, "uatom")],
+        )
+        .unwrap();
+
+        assert_eq!(
+            query_pending_rewards(&storage, VAULT, alice.clone()).unwrap(),
+            vec![coin(10, "uatom"), coin(1100, "uosmo")]
+        );
+        assert_eq!(
+            query_pending_rewards(&storage, VAULT, bob.clone()).unwrap(),
+            vec![coin(30, "uatom"), coin(300, "uosmo")]
+        );
+
+        // alice stops earning on the LP being unbonded
+        remove_stake(&mut storage, VAULT, alice.clone(), Uint128::new(100)).unwrap();
+        fund(&mut storage, VAULT, &[coin(300, "uosmo")]).unwrap();
+
+        assert_eq!(
+            claim(&mut storage, VAULT, alice.clone()).unwrap(),
+            vec![coin(10, "uatom"), coin(1100, "uosmo")]
+        );
+        assert!(claim(&mut storage, VAULT, alice.clone())
+            .unwrap()
+            .is_empty());
+        assert!(query_pending_rewards(&storage, VAULT, alice)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            claim(&mut storage, VAULT, bob).unwrap(),
+            vec![coin(30, "uatom"), coin(600, "uosmo")]
+        );
+        assert_eq!(
+            TOTAL_STAKED.load(&storage, VAULT).unwrap(),
+            Uint128::new(300)
+        );
+
+        // other vaults keep their own index
+        let other = fund(&mut storage, "uatom", &[coin(100, "uosmo")]).unwrap_err();
+        assert!(matches!(other, ContractError::NoBondedShares {}));
+    }
+}
@@ -0,0 +1,422 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Order, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+use mitosis_interface::liquidity_manager::{WithdrawTier, WithdrawalStatus};
+
+use crate::ContractError;
+
+use super::{balances::BALANCE, DEFAULT_LIMIT, MAX_LIMIT};
+
+// withdraw tiers by denom, ascending by min_amount
+pub const WITHDRAW_TIERS_KEY: &str = "withdraw_tiers";
+pub const WITHDRAW_TIERS: Map<&str, Vec<WithdrawTier>> = Map::new(WITHDRAW_TIERS_KEY);
+
+// tiers apply to what a denom withdrew over the window, so splitting a large
+// withdrawal doesn't dodge its tier. Amounts are kept in hourly slots.
+pub const WITHDRAW_WINDOW: u64 = 24 * 60 * 60;
+pub const WITHDRAW_SLOT: u64 = 60 * 60;
+pub const MAX_WITHDRAW_DELAY: u64 = 30 * 24 * 60 * 60;
+
+// (denom, slot) -> amount withdrawn within the slot
+pub const WITHDRAWN_KEY: &str = "withdrawn";
+pub const WITHDRAWN: Map<(&str, u64), Uint128> = Map::new(WITHDRAWN_KEY);
+
+// withdrawals debited from a deposit and waiting for their delay
+pub const WITHDRAWALS_KEY: &str = "withdrawals";
+pub const WITHDRAWALS: Map<u64, WithdrawalInfo> = Map::new(WITHDRAWALS_KEY);
+
+// finalized and cancelled withdrawals, kept so that a depositor can tell where
+// the funds of a withdrawal went
+pub const CLOSED_WITHDRAWALS_KEY: &str = "closed_withdrawals";
+pub const CLOSED_WITHDRAWALS: Map<u64, WithdrawalInfo> = Map::new(CLOSED_WITHDRAWALS_KEY);
+
+pub const WITHDRAWALS_ID_KEY: &str = "withdrawal_index";
+pub const WITHDRAWALS_ID: Item<u64> = Item::new(WITHDRAWALS_ID_KEY);
+
+#[cw_serde]
+pub struct WithdrawalInfo {
+    pub withdrawal_id: u64,
+    pub depositor: Addr,
+    pub withdrawer: Addr,
+    pub amount: Coin,
+    pub release_at: u64,
+    #[serde(default)]
+    pub status: WithdrawalStatus,
+}
+
+pub fn init_withdrawals_id(storage: &mut dyn Storage) -> StdResult<()> {
+    WITHDRAWALS_ID.save(storage, &0u64)?;
+    Ok(())
+}
+
+pub fn set_withdraw_tiers(
+    storage: &mut dyn Storage,
+    denom: &str,
+    tiers: Vec<WithdrawTier>,
+) -> Result<(), ContractError> {
+    if tiers.is_empty() {
+        WITHDRAW_TIERS.remove(storage, denom);
+        return Ok(());
+    }
+
+    for (i, tier) in tiers.iter().enumerate() {
+        if tier.min_amount.is_zero() || tier.delay == 0 {
+            return Err(ContractError::InvalidArgument {
+                msg: "tier min_amount and delay must be positive".to_string(),
+            });
+        }
+        if tier.delay > MAX_WITHDRAW_DELAY {
+            return Err(ContractError::InvalidArgument {
+                msg: format!("tier delay must not exceed {MAX_WITHDRAW_DELAY} seconds"),
+            });
+        }
+        if i > 0 && tiers[i - 1].min_amount >= tier.min_amount {
+            return Err(ContractError::InvalidArgument {
+                msg: "tiers must be ascending by min_amount".to_string(),
+            });
+        }
+    }
+
+    WITHDRAW_TIERS.save(storage, denom, &tiers)?;
+
+    Ok(())
+}
+
+pub fn query_withdraw_tiers(storage: &dyn Storage, denom: &str) -> StdResult<Vec<WithdrawTier>> {
+    Ok(WITHDRAW_TIERS.may_load(storage, denom)?.unwrap_or_default())
+}
+
+fn first_slot(now: u64) -> u64 {
+    (now / WITHDRAW_SLOT).saturating_sub(WITHDRAW_WINDOW / WITHDRAW_SLOT - 1)
+}
+
+// withdrawn sums what denom withdrew within the window, queued or not.
+pub fn withdrawn(storage: &dyn Storage, denom: &str, now: u64) -> StdResult<Uint128> {
+    WITHDRAWN
+        .prefix(denom)
+        .range(
+            storage,
+            Some(Bound::inclusive(first_slot(now))),
+            None,
+            Order::Ascending,
+        )
+        .try_fold(Uint128::zero(), |total, r| Ok(total.checked_add(r?.1)?))
+}
+
+// withdraw_delay records amount and returns the delay of the highest tier the
+// withdrawals of the window reach with it, zero below the first tier. A
+// cancelled withdrawal keeps counting until it leaves the window.
+pub fn withdraw_delay(storage: &mut dyn Storage, amount: &Coin, now: u64) -> StdResult<u64> {
+    let tiers = query_withdraw_tiers(storage, &amount.denom)?;
+    if tiers.is_empty() {
+        return Ok(0);
+    }
+
+    let stale: Vec<u64> = WITHDRAWN
+        .prefix(&amount.denom)
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(first_slot(now))),
+            Order::Ascending,
+        )
+        .collect::<StdResult<_>>()?;
+    for slot in stale {
+        WITHDRAWN.remove(storage, (&amount.denom, slot));
+    }
+
+    let total = withdrawn(storage, &amount.denom, now)?.checked_add(amount.amount)?;
+    let slot = now / WITHDRAW_SLOT;
+    let current = WITHDRAWN
+        .may_load(storage, (&amount.denom, slot))?
+        .unwrap_or_default();
+    WITHDRAWN.save(
+        storage,
+        (&amount.denom, slot),
+        &current.checked_add(amount.amount)?,
+    )?;
+
+    let delay = tiers
+        .into_iter()
+        .filter(|tier| tier.min_amount <= total)
+        .map(|tier| tier.delay)
+        .max()
+        .unwrap_or_default();
+
+    Ok(delay)
+}
+
+pub fn queue_withdrawal(
+    storage: &mut dyn Storage,
+    depositor: Addr,
+    withdrawer: Addr,
+    amount: Coin,
+    release_at: u64,
+) -> StdResult<WithdrawalInfo> {
+    let withdrawal_id = WITHDRAWALS_ID.load(storage)?;
+    let withdrawal = WithdrawalInfo {
+        withdrawal_id,
+        depositor,
+        withdrawer,
+        amount,
+        release_at,
+        status: WithdrawalStatus::Queued,
+    };
+
+    WITHDRAWALS.save(storage, withdrawal_id, &withdrawal)?;
+    WITHDRAWALS_ID.save(storage, &(withdrawal_id + 1))?;
+
+    Ok(withdrawal)
+}
+
+fn load_withdrawal(storage: &dyn Storage, id: u64) -> Result<WithdrawalInfo, ContractError> {
+    WITHDRAWALS
+        .may_load(storage, id)?
+        .ok_or(ContractError::WithdrawalNotFound { id })
+}
+
+fn close_withdrawal(
+    storage: &mut dyn Storage,
+    mut withdrawal: WithdrawalInfo,
+    status: WithdrawalStatus,
+) -> StdResult<WithdrawalInfo> {
+    WITHDRAWALS.remove(storage, withdrawal.withdrawal_id);
+    withdrawal.status = status;
+    CLOSED_WITHDRAWALS.save(storage, withdrawal.withdrawal_id, &withdrawal)?;
+
+    Ok(withdrawal)
+}
+
+// cancel_withdrawal drops a withdrawal still in its delay and credits the
+// amount back to the deposit it was taken from.
+pub fn cancel_withdrawal(
+    storage: &mut dyn Storage,
+    id: u64,
+    now: u64,
+) -> Result<WithdrawalInfo, ContractError> {
+    let withdrawal = load_withdrawal(storage, id)?;
+    if now >= withdrawal.release_at {
+        return Err(ContractError::WithdrawalReleased {});
+    }
+
+    let key = (
+        withdrawal.depositor.clone(),
+        withdrawal.amount.denom.clone(),
+    );
+    let balance = BALANCE
+        .may_load(storage, key.clone())?
+        .unwrap_or_default()
+        .checked_add(withdrawal.amount.amount)
+        .map_err(StdError::from)?;
+    BALANCE.save(storage, key, &balance)?;
+
+    Ok(close_withdrawal(
+        storage,
+        withdrawal,
+        WithdrawalStatus::Cancelled,
+    )?)
+}
+
+pub fn finalize_withdrawal(
+    storage: &mut dyn Storage,
+    id: u64,
+    now: u64,
+) -> Result<WithdrawalInfo, ContractError> {
+    let withdrawal = load_withdrawal(storage, id)?;
+    if now < withdrawal.release_at {
+        return Err(ContractError::WithdrawalLocked {});
+    }
+
+    Ok(close_withdrawal(
+        storage,
+        withdrawal,
+        WithdrawalStatus::Finalized,
+    )?)
+}
+
+pub fn query_withdrawal(storage: &dyn Storage, id: u64) -> Result<WithdrawalInfo, ContractError> {
+    match CLOSED_WITHDRAWALS.may_load(storage, id)? {
+        Some(withdrawal) => Ok(withdrawal),
+        None => load_withdrawal(storage, id),
+    }
+}
+
+pub fn query_withdrawals(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<WithdrawalInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    WITHDRAWALS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|r| r.map(|(_, withdrawal)| withdrawal))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::{coin, testing::MockStorage, Uint128};
+
+    use super::*;
+
+    const ADDR1: &str = "addr1";
+    const ADDR2: &str = "addr2";
+    const DENOM: &str = "uosmo";
+
+    fn tier(min_amount: u128, delay: u64) -> WithdrawTier {
+        WithdrawTier {
+            min_amount: Uint128::new(min_amount),
+            delay,
+        }
+    }
+
+    #[test]
+    fn test_withdraw_tiers() {
+        let mut storage = MockStorage::new();
+
+        let unordered =
+            set_withdraw_tiers(&mut storage, DENOM, vec![tier(1000, 60), tier(1000, 120)])
+                .unwrap_err();
+        assert!(matches!(unordered, ContractError::InvalidArgument { .. }));
+        let no_delay = set_withdraw_tiers(&mut storage, DENOM, vec![tier(1000, 0)]).unwrap_err();
+        assert!(matches!(no_delay, ContractError::InvalidArgument { .. }));
+        let too_long = set_withdraw_tiers(
+            &mut storage,
+            DENOM,
+            vec![tier(1000, MAX_WITHDRAW_DELAY + 1)],
+        )
+        .unwrap_err();
+        assert!(matches!(too_long, ContractError::InvalidArgument { .. }));
+
+        set_withdraw_tiers(&mut storage, DENOM, vec![tier(1000, 60), tier(5000, 600)]).unwrap();
+        assert_eq!(query_withdraw_tiers(&storage, DENOM).unwrap().len(), 2);
+
+        set_withdraw_tiers(&mut storage, DENOM, vec![]).unwrap();
+        assert!(query_withdraw_tiers(&storage, DENOM).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_withdraw_delay() {
+        let mut storage = MockStorage::new();
+        let now = 100 * WITHDRAW_WINDOW;
+
+        set_withdraw_tiers(&mut storage, DENOM, vec![tier(1000, 60), tier(5000, 600)]).unwrap();
+
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(5000, "uatom"), now).unwrap(),
+            0
+        );
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(999, DENOM), now).unwrap(),
+            0
+        );
+
+        // the window adds up, split withdrawals reach the tiers as well
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(1, DENOM), now).unwrap(),
+            60
+        );
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(1000, DENOM), now + WITHDRAW_SLOT).unwrap(),
+            60
+        );
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(3000, DENOM), now + WITHDRAW_SLOT).unwrap(),
+            600
+        );
+        assert_eq!(
+            withdrawn(&storage, DENOM, now + WITHDRAW_SLOT).unwrap(),
+            Uint128::new(5000)
+        );
+
+        // the first slot leaves the window and is pruned
+        let later = now + WITHDRAW_WINDOW;
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(1, DENOM), later).unwrap(),
+            60
+        );
+        assert_eq!(
+            withdrawn(&storage, DENOM, later).unwrap(),
+            Uint128::new(4001)
+        );
+        assert!(WITHDRAWN
+            .may_load(&storage, (DENOM, now / WITHDRAW_SLOT))
+            .unwrap()
+            .is_none());
+
+        let idle = later + WITHDRAW_WINDOW;
+        assert_eq!(
+            withdraw_delay(&mut storage, &coin(999, DENOM), idle).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_withdrawal_queue() {
+        let mut storage = MockStorage::new();
+        let depositor = Addr::unchecked(ADDR1);
+        let withdrawer = Addr::unchecked(ADDR2);
+
+        init_withdrawals_id(&mut storage).unwrap();
+        for _ in 0..3 {
+            queue_withdrawal(
+                &mut storage,
+                depositor.clone(),
+                withdrawer.clone(),
+                coin(100, DENOM),
+                1000,
+            )
+            .unwrap();
+        }
+
+        let page = query_withdrawals(&storage, None, Some(2)).unwrap();
+        assert_eq!(
+            page.iter().map(|w| w.withdrawal_id).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        let page = query_withdrawals(&storage, Some(1), None).unwrap();
+        assert_eq!(
+            page.iter().map(|w| w.withdrawal_id).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        // cancelling is only possible within the delay
+        let released = cancel_withdrawal(&mut storage, 0, 1000).unwrap_err();
+        assert!(matches!(released, ContractError::WithdrawalReleased {}));
+        cancel_withdrawal(&mut storage, 0, 999).unwrap();
+        assert_eq!(
+            BALANCE
+                .load(&storage, (depositor, DENOM.to_string()))
+                .unwrap(),
+            Uint128::new(100)
+        );
+
+        // finalizing only after it
+        let locked = finalize_withdrawal(&mut storage, 1, 999).unwrap_err();
+        assert!(matches!(locked, ContractError::WithdrawalLocked {}));
+        let finalized = finalize_withdrawal(&mut storage, 1, 1000).unwrap();
+        assert_eq!(finalized.withdrawer, withdrawer);
+
+        for id in [0, 1] {
+            let gone = finalize_withdrawal(&mut storage, id, 1000).unwrap_err();
+            assert!(matches!(gone, ContractError::WithdrawalNotFound { .. }));
+        }
+        assert_eq!(query_withdrawals(&storage, None, None).unwrap().len(), 1);
+
+        // closed withdrawals keep their outcome
+        let statuses = (0..3)
+            .map(|id| query_withdrawal(&storage, id).unwrap().status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                WithdrawalStatus::Cancelled,
+                WithdrawalStatus::Finalized,
+                WithdrawalStatus::Queued,
+            ]
+        );
+    }
+}
@@ -6,11 +6,29 @@ pub struct InstantiateMsg {}
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    AddAlias { token: String, denom: String },
-    ChangeOwner { new_owner: Addr },
-    GrantRole { role: String, addr: Addr },
-    RevokeRole { role: String, addr: Addr },
-    Pause { expires_at: u64 },
+    AddAlias {
+        token: String,
+        denom: String,
+    },
+    // proposes new_owner, who takes over with AcceptOwner before expires_at
+    ChangeOwner {
+        new_owner: Addr,
+        expires_at: Option<u64>,
+    },
+    AcceptOwner {},
+    CancelOwner {},
+    GrantRole {
+        role: String,
+        addr: Addr,
+    },
+    RevokeRole {
+        role: String,
+        addr: Addr,
+    },
+    // by the owner or a guardian, paused until released if expires_at is omitted
+    Pause {
+        expires_at: Option<u64>,
+    },
     Release {},
 }
 
@@ -23,6 +41,9 @@ pub enum QueryMsg {
     #[returns(ConfigResponse)]
     GetConfig {},
 
+    #[returns(PendingOwnerResponse)]
+    GetPendingOwner {},
+
     #[returns(ConvertResponse)]
     Convert { token: String },
 }
@@ -32,6 +53,12 @@ pub struct ConfigResponse {
     pub owner: Addr,
 }
 
+#[cw_serde]
+pub struct PendingOwnerResponse {
+    pub pending_owner: Option<Addr>,
+    pub expires_at: Option<u64>,
+}
+
 #[cw_serde]
 pub struct ConvertResponse {
     pub token: String,
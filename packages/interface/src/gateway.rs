@@ -1,48 +1,341 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, CosmosMsg, HexBinary};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, HexBinary, StdResult, Uint128,
+};
+use cw_utils::Expiration;
+
+// Bump this whenever the layout of SignPayload changes.
+pub const SIGN_PAYLOAD_VERSION: &str = "mitosis-gateway/v1";
+
+// Channel version negotiated with the counterpart gateway.
+pub const IBC_APP_VERSION: &str = "mitosis-gateway-1";
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub liquidity_manager: Addr,
     pub denom_manager: Addr,
+    pub signers: Vec<Signer>,
+    pub threshold: u64,
+    // seconds after which a pending send can be refunded, at most a year
+    pub operation_timeout: u64,
+    // messages allowed through Execute, nothing is allowed if omitted
+    pub execute_policy: Option<ExecutePolicy>,
+    // bech32 prefix of addresses the gateway derives, taken from the contract
+    // address if omitted
+    pub address_prefix: Option<String>,
+}
+
+// SignatureScheme selects how a signer's signatures are verified. Every scheme
+// signs the SHA-256 digest of the sign bytes.
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum SignatureScheme {
+    // public_key is a secp256k1 key, compressed (33 bytes) or uncompressed
+    // (65 bytes)
+    #[default]
+    Secp256k1,
+    // public_key is a 32-byte ed25519 key
+    Ed25519,
+    // public_key is the 20-byte Ethereum address. The digest is signed with
+    // the personal-sign prefix and the signature is 65 bytes (r || s || v).
+    Eip191,
+}
+
+#[cw_serde]
+pub struct Signer {
+    pub public_key: HexBinary,
+    pub weight: u64,
+    pub scheme: SignatureScheme,
+}
+
+// RetiringSigner is a rotated-out key which is still accepted in place of its
+// successor until expires_at (in seconds).
+#[cw_serde]
+pub struct RetiringSigner {
+    pub public_key: HexBinary,
+    pub scheme: SignatureScheme,
+    pub successor: HexBinary,
+    pub expires_at: u64,
+}
+
+#[cw_serde]
+pub struct SignerSignature {
     pub public_key: HexBinary,
+    pub signature: HexBinary,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    // proposes new_owner, who takes over with AcceptOwner before expires_at
     ChangeOwner {
         new_owner: Addr,
-        new_public_key: HexBinary,
+        expires_at: Option<u64>,
     },
+    AcceptOwner {},
+    CancelOwner {},
     ChangeLiquidityManager {
         new_liquidity_manager: Addr,
     },
     ChangeDenomManager {
         new_denom_manager: Addr,
     },
+    ChangeOperationTimeout {
+        operation_timeout: u64,
+    },
+    GrantRole {
+        role: String,
+        addr: Addr,
+    },
+    RevokeRole {
+        role: String,
+        addr: Addr,
+    },
+    // by the owner or a guardian, paused until released if expires_at is omitted
     Pause {
-        expires_at: u64,
+        expires_at: Option<u64>,
     },
     Send {
         op_id: u64,
         op_args: Vec<String>,
     },
+    // like Send, but forwards every attached coin in one operation
+    SendMulti {
+        op_id: u64,
+        op_args: Vec<String>,
+    },
+    // refunds a pending send to its sender once the deadline has passed, or
+    // withdraws again the part of a refund whose queued withdrawal was cancelled
+    Refund {
+        seq: u64,
+    },
+    // resolves a refund leg the liquidity manager queued behind a withdraw tier
+    // once the withdrawal is finalized or cancelled
+    SettleRefund {
+        withdrawal_id: u64,
+    },
+    // Send through an ibc-hooks transfer memo, called by the hook's
+    // intermediary sender derived from channel and original_sender
+    HookSend {
+        channel: String,
+        original_sender: String,
+        op_id: u64,
+        op_args: Vec<String>,
+    },
+    // resends a refund of a hook send whose transfer failed or timed out
+    RetryRefund {
+        seq: u64,
+    },
     Execute {
         msgs: Vec<CosmosMsg>,
         req_op_id: u64,
-        signature: HexBinary,
+        expires_at: Option<Expiration>,
+        signatures: Vec<SignerSignature>,
+    },
+    // CommitRoot authorizes every leaf of a Merkle tree of MerkleLeaf with a
+    // single set of signatures, until expires_at. See ExecuteLeaf.
+    CommitRoot {
+        root: HexBinary,
+        req_op_id: u64,
+        expires_at: Expiration,
+        signatures: Vec<SignerSignature>,
+    },
+    // ExecuteLeaf executes a leaf of a committed root once. The proof holds the
+    // sibling hashes from the leaf up to the root. A root which expired or was
+    // signed by a replaced signer set is removed instead.
+    ExecuteLeaf {
+        root: HexBinary,
+        leaf: MerkleLeaf,
+        proof: Vec<HexBinary>,
+    },
+    // by the owner or a guardian, drops a committed root before it expires
+    RevokeRoot {
+        root: HexBinary,
+    },
+    // ExecuteBatch dispatches every message on its own, so a failing message
+    // doesn't revert the others. See GetBatchOutcomes.
+    ExecuteBatch {
+        msgs: Vec<CosmosMsg>,
+        req_op_id: u64,
+        expires_at: Option<Expiration>,
+        signatures: Vec<SignerSignature>,
+    },
+    UpdateSignerSet {
+        signers: Vec<Signer>,
+        threshold: u64,
+        req_op_id: u64,
+        expires_at: Option<Expiration>,
+        signatures: Vec<SignerSignature>,
+    },
+    // replaces old_public_key in its slot, signed by a quorum of the current set
+    RotateSigner {
+        old_public_key: HexBinary,
+        new_public_key: HexBinary,
+        scheme: SignatureScheme,
+        grace_period: u64,
+        req_op_id: u64,
+        expires_at: Option<Expiration>,
+        signatures: Vec<SignerSignature>,
+    },
+    // withdraws from the liquidity manager to `to`, authorized by the signer set
+    Withdraw {
+        to: Addr,
+        amounts: Vec<Coin>,
+        req_op_id: u64,
+        expires_at: Option<Expiration>,
+        signatures: Vec<SignerSignature>,
+    },
+    // registers or replaces an operation spec
+    RegisterOp {
+        op_id: u64,
+        name: String,
+        args: Vec<OpArgType>,
+        enabled: bool,
+    },
+    // replaces the allow-list applied to Execute
+    SetExecutePolicy {
+        policy: ExecutePolicy,
+    },
+    // sets or removes (limit: None) the outflow cap of a denom. Denom "*" is the
+    // global cap, which counts the outflows of every weighted denom in the
+    // units of SetOutflowWeight.
+    SetRateLimit {
+        denom: String,
+        limit: Option<RateLimit>,
+    },
+    // sets or removes (weight: None) the units of the global cap one unit of
+    // the denom counts for, e.g. 0.000001 for a 6 decimal denom against a cap in
+    // whole tokens
+    SetOutflowWeight {
+        denom: String,
+        weight: Option<Decimal>,
+    },
+    // allows channels from the given port over the given connection, or removes
+    // the counterparty if port_id is None
+    SetIbcCounterparty {
+        connection_id: String,
+        port_id: Option<String>,
+    },
+    // channel sends are announced on, the relayer is used if None
+    SetSendChannel {
+        channel_id: Option<String>,
+    },
+    // only callable by the gateway itself, dispatches a received packet
+    HandlePacket {
+        channel_id: String,
+        packet: PacketV1,
+    },
+    // only callable by the gateway itself, i.e. through a signed Execute
+    UpdateOperationStatus {
+        seq: u64,
+        status: OperationStatus,
     },
     Release {},
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    // required when migrating a gateway which still holds a single public key
+    pub operation_timeout: Option<u64>,
+}
+
+// SudoMsg is called by the ibc-hooks middleware for transfers sent with an
+// ibc_callback memo.
+#[cw_serde]
+pub enum SudoMsg {
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     GetConfig {},
+
+    #[returns(PendingOwnerResponse)]
+    GetPendingOwner {},
+
+    #[returns(IsExecutedResponse)]
+    IsExecuted { req_op_id: u64 },
+
+    #[returns(RootResponse)]
+    GetRoot { root: HexBinary },
+
+    #[returns(SignerSetResponse)]
+    GetSignerSet {},
+
+    #[returns(ExecutePolicy)]
+    GetExecutePolicy {},
+
+    #[returns(RateLimitResponse)]
+    GetRateLimit { denom: String },
+
+    #[returns(RateLimitListResponse)]
+    GetRateLimits {},
+
+    #[returns(OutflowWeightListResponse)]
+    GetOutflowWeights {},
+
+    #[returns(IbcChannelInfo)]
+    GetChannel { channel_id: String },
+
+    #[returns(ChannelListResponse)]
+    GetChannels {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(BatchOutcomesResponse)]
+    GetBatchOutcomes {
+        req_op_id: u64,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+
+    #[returns(OpSpec)]
+    GetOp { op_id: u64 },
+
+    #[returns(OpListResponse)]
+    GetOps {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(OperationResponse)]
+    GetOperation { seq: u64 },
+
+    #[returns(OperationListResponse)]
+    GetOperations {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(OperationListResponse)]
+    GetOperationsBySender {
+        sender: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(OperationListResponse)]
+    GetOperationsByStatus {
+        status: OperationStatus,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -50,5 +343,360 @@ pub struct ConfigResponse {
     pub owner: Addr,
     pub liquidity_manager: Addr,
     pub denom_manager: Addr,
-    pub public_key: HexBinary,
+    pub operation_timeout: u64,
+    pub send_channel: Option<String>,
+    pub address_prefix: String,
+}
+
+#[cw_serde]
+pub struct PendingOwnerResponse {
+    pub pending_owner: Option<Addr>,
+    pub expires_at: Option<u64>,
+}
+
+#[cw_serde]
+pub struct IbcChannelInfo {
+    pub channel_id: String,
+    pub connection_id: String,
+    pub counterparty_port_id: String,
+    pub counterparty_channel_id: String,
+    pub open: bool,
+}
+
+#[cw_serde]
+pub struct ChannelListResponse {
+    pub items: Vec<IbcChannelInfo>,
+}
+
+// MessageOutcome is the result of the message at index of an ExecuteBatch.
+#[cw_serde]
+pub struct MessageOutcome {
+    pub index: u32,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[cw_serde]
+pub struct BatchOutcomesResponse {
+    pub req_op_id: u64,
+    pub items: Vec<MessageOutcome>,
+}
+
+// GatewayPacket is the data of every packet exchanged with the counterpart
+// gateway. New layouts are added as new versions.
+#[cw_serde]
+pub enum GatewayPacket {
+    V1(PacketV1),
+}
+
+#[cw_serde]
+pub enum PacketV1 {
+    // outbound, announces a send recorded in the operation ledger
+    Send {
+        seq: u64,
+        sender: Addr,
+        amounts: Vec<Coin>,
+        // denom manager aliases of amounts, in the same order
+        aliases: Vec<String>,
+        op_id: u64,
+        op_args: Vec<String>,
+    },
+    // inbound, same as a signed Execute
+    Execute {
+        req_op_id: u64,
+        msgs: Vec<CosmosMsg>,
+    },
+    // inbound, same as a signed Withdraw
+    Withdraw {
+        req_op_id: u64,
+        to: Addr,
+        amounts: Vec<Coin>,
+    },
+}
+
+#[cw_serde]
+pub enum GatewayAck {
+    Result(Binary),
+    Error(String),
+}
+
+// MsgKind classifies the messages a signed Execute may dispatch.
+#[cw_serde]
+#[derive(Copy)]
+pub enum MsgKind {
+    Bank,
+    // execute on a contract listed in ExecutePolicy::wasm_targets
+    WasmExecute,
+    WasmInstantiate,
+    // migrate on a contract listed in ExecutePolicy::wasm_targets
+    WasmMigrate,
+    // update or clear the admin of a contract listed in ExecutePolicy::wasm_targets
+    WasmAdmin,
+    // any of ExecutePolicy::stargate_type_urls
+    Stargate,
+    Ibc,
+    Gov,
+}
+
+#[cw_serde]
+pub struct WasmTarget {
+    pub contract: Addr,
+    // allowed top-level keys of the execute message, any key if empty
+    pub messages: Vec<String>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ExecutePolicy {
+    pub kinds: Vec<MsgKind>,
+    pub wasm_targets: Vec<WasmTarget>,
+    pub stargate_type_urls: Vec<String>,
+}
+
+// RateLimit caps the amount of a denom, or the weighted amount of all denoms,
+// leaving the gateway within any rolling window.
+#[cw_serde]
+pub struct RateLimit {
+    pub max_amount: Uint128,
+    // seconds
+    pub window: u64,
+}
+
+#[cw_serde]
+pub struct RateLimitResponse {
+    pub denom: String,
+    pub limit: RateLimit,
+    pub used: Uint128,
+    pub remaining: Uint128,
+}
+
+#[cw_serde]
+pub struct RateLimitListResponse {
+    pub items: Vec<RateLimitResponse>,
+}
+
+#[cw_serde]
+pub struct OutflowWeight {
+    pub denom: String,
+    pub weight: Decimal,
+}
+
+#[cw_serde]
+pub struct OutflowWeightListResponse {
+    pub items: Vec<OutflowWeight>,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum OpArgType {
+    // bech32 address of any chain holding 20 or 32 bytes, or 0x-prefixed
+    // 20-byte hex address
+    Address,
+    // decimal unsigned integer up to 256 bits
+    Uint,
+    Denom,
+    // non-empty hex string, optionally 0x-prefixed
+    Bytes,
+}
+
+#[cw_serde]
+pub struct OpSpec {
+    pub op_id: u64,
+    pub name: String,
+    pub args: Vec<OpArgType>,
+    pub enabled: bool,
+}
+
+#[cw_serde]
+pub struct OpListResponse {
+    pub items: Vec<OpSpec>,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum OperationStatus {
+    Pending,
+    Relayed,
+    Finalized,
+    Refunded,
+}
+
+#[cw_serde]
+pub struct OperationResponse {
+    pub seq: u64,
+    pub sender: Addr,
+    pub amounts: Vec<Coin>,
+    pub aliases: Vec<String>,
+    pub op_id: u64,
+    pub op_args: Vec<String>,
+    pub height: u64,
+    pub deadline: u64,
+    pub status: OperationStatus,
+    pub origin: Option<HookOrigin>,
+}
+
+// HookOrigin is the remote sender of a send made through an ibc-hooks transfer.
+#[cw_serde]
+pub struct HookOrigin {
+    // channel on this chain the transfer was received on
+    pub channel: String,
+    pub original_sender: String,
+}
+
+#[cw_serde]
+pub struct OperationListResponse {
+    pub items: Vec<OperationResponse>,
+}
+
+#[cw_serde]
+pub struct IsExecutedResponse {
+    pub req_op_id: u64,
+    pub executed: bool,
+}
+
+// MerkleLeaf is an Execute committed to by a root. Leaves are hashed as
+// sha256(0x00 || json(leaf)) and nodes as sha256(0x01 || min(a, b) || max(a, b)),
+// so a proof doesn't need to tell left from right.
+#[cw_serde]
+pub struct MerkleLeaf {
+    pub req_op_id: u64,
+    pub msgs: Vec<CosmosMsg>,
+}
+
+#[cw_serde]
+pub struct RootResponse {
+    pub root: HexBinary,
+    pub req_op_id: u64,
+    pub expires_at: Expiration,
+    pub signer_set_version: u64,
+    pub expired: bool,
+}
+
+#[cw_serde]
+pub struct SignerSetResponse {
+    pub signers: Vec<Signer>,
+    pub retiring: Vec<RetiringSigner>,
+    pub threshold: u64,
+    pub version: u64,
+}
+
+/// SignPayload is the envelope signed off-chain to authorize a gateway operation.
+/// It binds the signature to a single chain, gateway instance and request id.
+#[cw_serde]
+pub struct SignPayload {
+    pub version: String,
+    pub chain_id: String,
+    pub contract: String,
+    pub req_op_id: u64,
+    pub expires_at: Option<Expiration>,
+    pub action: SignAction,
+}
+
+#[cw_serde]
+pub enum SignAction {
+    Execute {
+        msgs: Vec<CosmosMsg>,
+    },
+    ExecuteBatch {
+        msgs: Vec<CosmosMsg>,
+    },
+    CommitRoot {
+        root: HexBinary,
+    },
+    Withdraw {
+        to: Addr,
+        amounts: Vec<Coin>,
+    },
+    // version is the version of the signer set being replaced.
+    UpdateSignerSet {
+        signers: Vec<Signer>,
+        threshold: u64,
+        version: u64,
+    },
+    // version is the version of the signer set the key is rotated in.
+    RotateSigner {
+        old_public_key: HexBinary,
+        new_public_key: HexBinary,
+        scheme: SignatureScheme,
+        grace_period: u64,
+        version: u64,
+    },
+}
+
+impl SignPayload {
+    pub fn new(
+        chain_id: impl Into<String>,
+        contract: impl Into<String>,
+        req_op_id: u64,
+        expires_at: Option<Expiration>,
+        action: SignAction,
+    ) -> Self {
+        Self {
+            version: SIGN_PAYLOAD_VERSION.to_string(),
+            chain_id: chain_id.into(),
+            contract: contract.into(),
+            req_op_id,
+            expires_at,
+            action,
+        }
+    }
+
+    /// Returns the exact bytes to be signed. Signers hash these bytes with sha256.
+    pub fn to_sign_bytes(&self) -> StdResult<Binary> {
+        to_binary(self)
+    }
+}
+
+/// Builds the bytes to sign for `ExecuteMsg::Execute`.
+pub fn execute_sign_bytes(
+    chain_id: impl Into<String>,
+    contract: impl Into<String>,
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    msgs: Vec<CosmosMsg>,
+) -> StdResult<Binary> {
+    SignPayload::new(
+        chain_id,
+        contract,
+        req_op_id,
+        expires_at,
+        SignAction::Execute { msgs },
+    )
+    .to_sign_bytes()
+}
+
+/// Builds the bytes to sign for `ExecuteMsg::ExecuteBatch`.
+pub fn execute_batch_sign_bytes(
+    chain_id: impl Into<String>,
+    contract: impl Into<String>,
+    req_op_id: u64,
+    expires_at: Option<Expiration>,
+    msgs: Vec<CosmosMsg>,
+) -> StdResult<Binary> {
+    SignPayload::new(
+        chain_id,
+        contract,
+        req_op_id,
+        expires_at,
+        SignAction::ExecuteBatch { msgs },
+    )
+    .to_sign_bytes()
+}
+
+/// Builds the bytes to sign for `ExecuteMsg::CommitRoot`.
+pub fn commit_root_sign_bytes(
+    chain_id: impl Into<String>,
+    contract: impl Into<String>,
+    req_op_id: u64,
+    expires_at: Expiration,
+    root: HexBinary,
+) -> StdResult<Binary> {
+    SignPayload::new(
+        chain_id,
+        contract,
+        req_op_id,
+        Some(expires_at),
+        SignAction::CommitRoot { root },
+    )
+    .to_sign_bytes()
 }
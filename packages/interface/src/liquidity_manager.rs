@@ -1,10 +1,17 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
 
 #[cw_serde]
 pub struct InstantiateMsg {
+    pub vaults: Vec<VaultMsg>,
+}
+
+// VaultMsg describes a vault for an underlying denom. Its LP denom is created
+// as factory/{contract}/{lp_subdenom}.
+#[cw_serde]
+pub struct VaultMsg {
     pub denom: String,
-    pub lp_denom: String,
+    pub lp_subdenom: String,
     pub unbonding_period: u64,
 }
 
@@ -13,22 +20,44 @@ pub enum ExecuteMsg {
     Deposit {
         depositor: Option<Addr>,
     },
+    // by a gateway: debits the caller's deposit and pays withdrawer, or the caller.
+    // Amounts reaching a withdraw tier are queued instead, see FinalizeWithdrawal.
     Withdraw {
         withdrawer: Option<Addr>,
         amount: Coin,
     },
+    // the vault is picked by the denom of the sent underlying or LP
     Delegate {},
     Undelegate {},
+    // by the owner: pays underlying into the vault, raising the LP exchange rate
+    ReportYield {},
+    // by the owner: writes down underlying lost upstream, lowering the LP exchange rate
+    ReportLoss {
+        denom: String,
+        amount: Uint128,
+    },
     Bond {},
     StartUnbond {
+        denom: String,
         amount: Uint128,
     },
     Unbond {
         unbond_id: u64,
     },
+    // by the owner or a distributor: splits the sent funds over the bonded LP
+    FundRewards {
+        denom: String,
+    },
+    ClaimRewards {
+        denom: String,
+    },
+    // proposes new_owner, who takes over with AcceptOwner before expires_at
     ChangeOwner {
         new_owner: Addr,
+        expires_at: Option<u64>,
     },
+    AcceptOwner {},
+    CancelOwner {},
     GrantRole {
         role: String,
         addr: Addr,
@@ -37,17 +66,43 @@ pub enum ExecuteMsg {
         role: String,
         addr: Addr,
     },
+    // by the owner or a guardian, paused until released if expires_at is omitted
     Pause {
-        expires_at: u64,
+        expires_at: Option<u64>,
     },
     Release {},
+    // by the owner: adds a vault, along with its LP denom
+    CreateVault(VaultMsg),
     ChangeConfig {
+        denom: String,
         unbonding_period: u64,
     },
+    // by the owner: withdrawals of denom wait for the delay of the highest tier
+    // the denom's withdrawals over the last 24 hours reach, an empty list
+    // withdraws immediately. A delay is at most 30 days.
+    SetWithdrawTiers {
+        denom: String,
+        tiers: Vec<WithdrawTier>,
+    },
+    // by the owner or a guardian before release_at, credits the deposit back
+    CancelWithdrawal {
+        withdrawal_id: u64,
+    },
+    // by anyone from release_at, pays the withdrawer
+    FinalizeWithdrawal {
+        withdrawal_id: u64,
+    },
 }
 
 #[cw_serde]
-pub enum MigrateMsg {}
+pub struct WithdrawTier {
+    pub min_amount: Uint128,
+    // seconds
+    pub delay: u64,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
 
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -56,6 +111,18 @@ pub enum QueryMsg {
     #[returns(ConfigResponse)]
     GetConfig {},
 
+    #[returns(PendingOwnerResponse)]
+    GetPendingOwner {},
+
+    #[returns(VaultResponse)]
+    GetVault { denom: String },
+
+    #[returns(VaultListResponse)]
+    GetVaults {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     #[returns(PauseInfoResponse)]
     PauseInfo {},
 
@@ -63,24 +130,57 @@ pub enum QueryMsg {
     GetBalance { depositor: Addr },
 
     #[returns(GetTotalDelegatesResponse)]
-    GetTotalDelegates {},
+    GetTotalDelegates { denom: String },
+
+    #[returns(ExchangeRateResponse)]
+    GetExchangeRate { denom: String },
 
     #[returns(GetBondResponse)]
-    GetBond { bonder: Addr },
+    GetBond { bonder: Addr, denom: String },
 
     #[returns(GetUnbondResponse)]
     GetUnbond { unbond_id: u64 },
 
     #[returns(GetUnbondListResponse)]
     GetUnbondsByOwner { owner: Addr },
+
+    #[returns(PendingRewardsResponse)]
+    GetPendingRewards { bonder: Addr, denom: String },
+
+    #[returns(WithdrawTiersResponse)]
+    GetWithdrawTiers { denom: String },
+
+    #[returns(WithdrawalResponse)]
+    GetWithdrawal { withdrawal_id: u64 },
+
+    #[returns(WithdrawalListResponse)]
+    GetPendingWithdrawals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
 pub struct ConfigResponse {
     pub owner: Addr,
-    pub unbonding_period: u64,
+}
+
+#[cw_serde]
+pub struct VaultResponse {
     pub denom: String,
     pub lp_denom: String,
+    pub unbonding_period: u64,
+}
+
+#[cw_serde]
+pub struct VaultListResponse {
+    pub items: Vec<VaultResponse>,
+}
+
+#[cw_serde]
+pub struct PendingOwnerResponse {
+    pub pending_owner: Option<Addr>,
+    pub expires_at: Option<u64>,
 }
 
 #[cw_serde]
@@ -105,6 +205,7 @@ pub struct GetBondResponse {
 pub struct GetUnbondResponse {
     pub unbond_id: u64,
     pub owner: Addr,
+    pub denom: String,
     pub amount: Uint128,
     pub unbond_time: u64,
 }
@@ -114,7 +215,54 @@ pub struct GetUnbondListResponse {
     pub items: Vec<GetUnbondResponse>,
 }
 
+#[cw_serde]
+pub struct PendingRewardsResponse {
+    pub bonder: Addr,
+    pub rewards: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct WithdrawTiersResponse {
+    pub denom: String,
+    pub tiers: Vec<WithdrawTier>,
+}
+
+// WithdrawalStatus tells a depositor whether a queued withdrawal was paid out
+// or credited back to its deposit.
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum WithdrawalStatus {
+    #[default]
+    Queued,
+    Finalized,
+    Cancelled,
+}
+
+#[cw_serde]
+pub struct WithdrawalResponse {
+    pub withdrawal_id: u64,
+    pub depositor: Addr,
+    pub withdrawer: Addr,
+    pub amount: Coin,
+    pub release_at: u64,
+    pub status: WithdrawalStatus,
+}
+
+#[cw_serde]
+pub struct WithdrawalListResponse {
+    pub items: Vec<WithdrawalResponse>,
+}
+
 #[cw_serde]
 pub struct GetTotalDelegatesResponse {
     pub amount: Coin,
 }
+
+#[cw_serde]
+pub struct ExchangeRateResponse {
+    // underlying backing the LP supply
+    pub total_assets: Coin,
+    pub total_shares: Coin,
+    // underlying per LP share
+    pub rate: Decimal,
+}
@@ -56,8 +56,8 @@ check "$DENOM_MGR_ADDR" "DENOM_MGR_ADDR"
 echo "================ Deploying liquidity manager contracts ================"
 LM_INIT_MSG=$(
     cat $(pwd)/scripts/$NETWORK/liquidity_manager.json | \
-    jq -c '.denom = "'$DENOM'"' | \
-    jq -c '.lp_denom = "'$LP_DENOM'"'
+    jq -c '.vaults[0].denom = "'$DENOM'"' | \
+    jq -c '.vaults[0].lp_subdenom = "'$LP_DENOM'"'
 )
 beaker wasm deploy \
     --raw $LM_INIT_MSG \
@@ -75,7 +75,7 @@ GW_INIT_MSG=$(
     cat $(pwd)/scripts/$NETWORK/gateway.json | \
     jq -c '.denom_manager = "'$DENOM_MGR_ADDR'"' | \
     jq -c '.liquidity_manager = "'$LMGR_ADDR'"' | \
-    jq -c '.public_key = "'$PUBLIC_KEY'"'
+    jq -c '.signers = [{"public_key": "'$PUBLIC_KEY'", "weight": 1, "scheme": "secp256k1"}]'
 )
 echo $GW_INIT_MSG
 beaker wasm deploy \
@@ -89,3 +89,10 @@ beaker wasm deploy \
 GW_ADDR=$(cat $(pwd)/.beaker/state.json | jq -r '.'$NETWORK'["mitosis-gateway"].addresses.default')
 echo "GW ADDR: $GW_ADDR"
 
+
+echo "================ Granting gateway role on liquidity manager ================"
+beaker wasm execute \
+    --raw '{"grant_role":{"role":"gateway_role","addr":"'$GW_ADDR'"}}' \
+    --network $NETWORK \
+    $SIGNER_FLAG \
+    mitosis-liquidity-manager